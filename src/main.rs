@@ -1,34 +1,485 @@
-use std::{fs, io, process};
-use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
-use std::path::{Path, PathBuf};
+use std::{fmt, fs, io, process};
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use clap::builder::PossibleValuesParser;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use dotenv::dotenv;
 use std::env;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use rayon::prelude::*;
 use regex::Regex;
+use serde_json::{json, Value};
+use tokio::sync::{oneshot, Mutex as AsyncMutex, Semaphore};
 
 const DB_TYPES: &[&str] = &[
     "INTEGER", "BIGINT", "FLOAT", "REAL", "DOUBLE", "DECIMAL", "STRING", "TEXT",
-    "BOOLEAN", "DATE", "DATEONLY", "TIME", "UUID", "JSON",
+    "BOOLEAN", "DATE", "DATEONLY", "TIME", "UUID", "JSON", "FILE", "VIRTUAL", "ENUM",
+    "GEOMETRY", "GEOGRAPHY", "CITEXT",
 ];
 
+// PostGIS columns, holding GeoJSON geometries.
+const SPATIAL_TYPES: &[&str] = &["GEOMETRY", "GEOGRAPHY"];
+
 const DB_ATTR: &[&str] = &[
     "@PrimaryKey", "@AutoIncrement", "@Unique", "@Index",
     "@CreatedAt", "@UpdatedAt", "@DeletedAt", "@ForeignKey", "@BelongsTo",
     "@HasMany", "@HasOne", "@DefaultScope", "@Scopes", "@AllowNull",
-    "@Comment", "@Default", "@Length", "@References",
+    "@Comment", "@Default", "@Length", "@References", "@Hidden", "@UniqueInsensitive",
 ];
 
+// Attributes Crudify interprets itself and never renders as Sequelize decorators.
+const GENERATOR_ATTR: &[&str] = &["@Hidden", "@References", "@UniqueInsensitive"];
+
+// Marks generated code the user may edit, regeneration keeps whatever is between them.
+const KEEP_START: &str = "crudify:keep ";
+const KEEP_END: &str = "crudify:end ";
+
+// Mark the field block of a model, regeneration replaces what is between them and keeps the rest of the class.
+const FIELDS_START: &str = "crudify:fields";
+const FIELDS_END: &str = "crudify:fields-end";
+
 const JS_TYPES: &[&str] = &[
     "number", "string", "boolean", "float", "double", "Date", "object",
-    "function", "undefined", "symbol", "null"
+    "function", "undefined", "symbol", "null", "bigint"
+];
+
+const RESERVED_WORDS: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default",
+    "delete", "do", "else", "enum", "export", "extends", "false", "finally", "for",
+    "function", "if", "import", "in", "instanceof", "new", "null", "return", "super",
+    "switch", "this", "throw", "true", "try", "typeof", "var", "void", "while", "with",
+    "implements", "interface", "let", "package", "private", "protected", "public",
+    "static", "yield", "await",
+];
+
+fn identifier_regex() -> &'static Regex {
+    static IDENTIFIER: OnceLock<Regex> = OnceLock::new();
+    IDENTIFIER.get_or_init(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap())
+}
+
+const DIALECTS: &[&str] = &["postgres", "mysql", "sqlite", "mssql"];
+
+const DIALECT_UNSUPPORTED_TYPES: &[(&str, &[&str])] = &[
+    ("postgres", &[]),
+    ("mysql", &["GEOMETRY", "GEOGRAPHY", "CITEXT"]),
+    ("sqlite", &["GEOMETRY", "GEOGRAPHY", "CITEXT"]),
+    ("mssql", &["JSON", "DOUBLE", "GEOMETRY", "GEOGRAPHY", "CITEXT"]),
 ];
 
+const FOREIGN_KEY_TYPES: &[&str] = &["INTEGER", "BIGINT", "UUID"];
+
+fn attr_name(attribute: &str) -> &str {
+    attribute.split('(').next().unwrap_or(attribute).trim()
+}
+
+fn attr_args(attribute: &str) -> Option<&str> {
+    let start = attribute.find('(')?;
+    let end = attribute.rfind(')')?;
+    (start < end).then(|| attribute[start + 1..end].trim())
+}
+
+fn graphql_type(db_type: &str) -> &'static str {
+    match db_type {
+        "INTEGER" | "BIGINT" => "Int",
+        "FLOAT" | "REAL" | "DOUBLE" | "DECIMAL" => "Float",
+        "BOOLEAN" => "Boolean",
+        "UUID" => "ID",
+        _ => "String",
+    }
+}
+
+fn json_schema_type(db_type: &str) -> Value {
+    match db_type {
+        "INTEGER" | "BIGINT" => json!({ "type": "integer" }),
+        "FLOAT" | "REAL" | "DOUBLE" | "DECIMAL" => json!({ "type": "number" }),
+        "BOOLEAN" => json!({ "type": "boolean" }),
+        "DATE" => json!({ "type": "string", "format": "date-time" }),
+        "DATEONLY" => json!({ "type": "string", "format": "date" }),
+        "TIME" => json!({ "type": "string", "format": "time" }),
+        "UUID" => json!({ "type": "string", "format": "uuid" }),
+        "JSON" => json!({ "type": "object" }),
+        _ => json!({ "type": "string" }),
+    }
+}
+
+fn zod_type(db_type: &str) -> &'static str {
+    match db_type {
+        "INTEGER" | "BIGINT" => "z.number().int()",
+        "FLOAT" | "REAL" | "DOUBLE" | "DECIMAL" => "z.number()",
+        "BOOLEAN" => "z.boolean()",
+        "DATE" | "DATEONLY" => "z.coerce.date()",
+        "UUID" => "z.string().uuid()",
+        "JSON" => "z.unknown()",
+        _ => "z.string()",
+    }
+}
+
+fn jsdoc_type(js_type: &str) -> &str {
+    match js_type {
+        "float" | "double" => "number",
+        "object" => "Object",
+        other => other,
+    }
+}
+
+// Column transforms of the schema, all but custom for string columns only.
+const TRANSFORMS: &[&str] = &["lowercase", "trim", "encrypt", "custom"];
+
+const BIGINT_AS: &[&str] = &["bigint", "string"];
+
+const REFERENTIAL_ACTIONS: &[&str] = &["CASCADE", "SET NULL", "RESTRICT", "NO ACTION", "SET DEFAULT"];
+
+// A @References attribute: @References(User), @References(User, key: "id", onDelete: CASCADE, onUpdate: SET NULL).
+// The target is a schema model or else a table name.
+#[derive(Debug, Clone, Default)]
+struct Reference {
+    target: String,
+    key: Option<String>,
+    on_delete: Option<String>,
+    on_update: Option<String>
+}
+
+impl Reference {
+    fn parse(attribute: &str) -> Result<Self, String> {
+        let unquote = |value: &str| value.trim().trim_matches(['"', '\'', '`']).to_string();
+        let mut reference = Self::default();
+        let args = attr_args(attribute).unwrap_or_default();
+
+        for arg in args.split(',').map(str::trim).filter(|arg| !arg.is_empty()) {
+            match arg.split_once(':').map(|(key, value)| (key.trim(), unquote(value))) {
+                Some(("key", key)) => reference.key = Some(key),
+                Some(("onDelete", action)) => reference.on_delete = Some(Self::action(&action)?),
+                Some(("onUpdate", action)) => reference.on_update = Some(Self::action(&action)?),
+                Some((key, _)) => return Err(format!("Unknown @References option: {key}")),
+                None if reference.target.is_empty() => reference.target = unquote(arg.trim_start_matches("()").trim_start().trim_start_matches("=>")),
+                None => return Err(format!("Invalid @References arguments: {attribute}")),
+            }
+        }
+        if reference.target.is_empty() {
+            return Err("@References needs the model or table it references".to_string());
+        }
+
+        Ok(reference)
+    }
+
+    fn action(action: &str) -> Result<String, String> {
+        let action = action.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase();
+        if !REFERENTIAL_ACTIONS.contains(&action.as_str()) {
+            return Err(format!("Unsupported referential action: {action}, expected CASCADE, SET NULL, RESTRICT, NO ACTION or SET DEFAULT"));
+        }
+        Ok(action)
+    }
+
+    fn target_model(&self) -> Option<&'static Model> {
+        SCHEMA_MODELS.get().into_iter().flatten().find(|model| model.name == self.target)
+    }
+
+    fn actions(&self) -> Vec<String> {
+        let mut actions = Vec::new();
+        if let Some(on_delete) = &self.on_delete {
+            actions.push(format!("onDelete: \"{on_delete}\""));
+        }
+        if let Some(on_update) = &self.on_update {
+            actions.push(format!("onUpdate: \"{on_update}\""));
+        }
+        actions
+    }
+
+    // The column options, the key defaults to the primary key of the referenced model.
+    fn options(&self) -> Vec<String> {
+        let (table, key) = match self.target_model() {
+            Some(model) => (model.migration_table(), model.column_name(&model.primary_key().name)),
+            None => (format!("\"{}\"", self.target), "id".to_string()),
+        };
+        let key = self.key.clone().unwrap_or(key);
+        std::iter::once(format!("references: {{ model: {table}, key: \"{key}\" }}")).chain(self.actions()).collect()
+    }
+}
+
+const INDEX_METHODS: &[&str] = &["BTREE", "HASH", "GIST", "SPGIST", "GIN", "BRIN"];
+
+// An @Index attribute, the fields sharing a name form one composite index.
+#[derive(Debug, Clone, Default)]
+struct Index {
+    name: Option<String>,
+    fields: Vec<String>,
+    unique: Option<bool>,
+    using: Option<String>
+}
+
+impl Index {
+    // @Index, @Index("idx_posts_author") and @Index("idx_posts_author", { unique: true, using: gin }).
+    fn parse(attribute: &str) -> Result<Self, String> {
+        let unquote = |value: &str| value.trim().trim_matches(['"', '\'', '`']).to_string();
+        let mut index = Self::default();
+        let args = attr_args(attribute).unwrap_or_default().replace(['{', '}'], "");
+
+        for arg in args.split(',').map(str::trim).filter(|arg| !arg.is_empty()) {
+            match arg.split_once(':').map(|(key, value)| (key.trim(), unquote(value))) {
+                Some(("name", name)) => index.name = Some(name),
+                Some(("unique", unique)) => {
+                    index.unique = Some(unique.parse().map_err(|_| format!("@Index unique expects true or false, got {unique}"))?);
+                }
+                Some(("using", using)) => {
+                    let using = using.to_uppercase();
+                    if !INDEX_METHODS.contains(&using.as_str()) {
+                        return Err(format!("Unsupported index method: {using}"));
+                    }
+                    index.using = Some(using);
+                }
+                Some((key, _)) => return Err(format!("Unknown @Index option: {key}")),
+                None if index.name.is_none() => index.name = Some(unquote(arg)),
+                None => return Err(format!("Invalid @Index arguments: {attribute}")),
+            }
+        }
+
+        Ok(index)
+    }
+
+    fn options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(name) = &self.name {
+            options.push(format!("name: \"{name}\""));
+        }
+        if let Some(unique) = self.unique {
+            options.push(format!("unique: {unique}"));
+        }
+        if let Some(using) = &self.using {
+            options.push(format!("using: \"{using}\""));
+        }
+        options
+    }
+
+    fn quoted_fields(&self) -> String {
+        self.fields.iter().map(|field| format!("\"{field}\"")).collect::<Vec<_>>().join(", ")
+    }
+
+    fn to_decorator(&self) -> String {
+        match (&self.name, self.unique.is_some() || self.using.is_some()) {
+            (None, false) => "@Index".to_string(),
+            (Some(name), false) => format!("@Index(\"{name}\")"),
+            _ => format!("@Index({{ {} }})", self.options().join(", ")),
+        }
+    }
+
+    // Entry of the `indexes` model option.
+    fn to_table_option(&self) -> String {
+        let mut options = self.options();
+        options.insert(usize::from(self.name.is_some()), format!("fields: [{}]", self.quoted_fields()));
+        format!("{{ {} }}", options.join(", "))
+    }
+
+    fn to_migration(&self, model: &Model) -> String {
+        let options = self.options();
+        let options = if options.is_empty() { String::new() } else { format!(", {{ {} }}", options.join(", ")) };
+        let columns = self.fields.iter().map(|field| format!("\"{}\"", model.column_name(field))).collect::<Vec<_>>().join(", ");
+        format!("\t\tawait queryInterface.addIndex({}, [{columns}]{options});", model.migration_table())
+    }
+}
+
+const SHAPE_TYPES: &[&str] = &["string", "number", "boolean", "Date"];
+
+// Declared structure of a JSON column, given as `shape` in the schema file.
+#[derive(Debug, Clone)]
+enum Shape {
+    Primitive(String),
+    Array(Box<Shape>),
+    Object(Vec<(String, Shape)>)
+}
+
+impl Shape {
+    // "string", "string[]" (or [string]) and nested mappings.
+    fn parse(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(name) => match name.strip_suffix("[]") {
+                Some(item) => Ok(Self::Array(Box::new(Self::parse(&json!(item))?))),
+                None if SHAPE_TYPES.contains(&name.as_str()) => Ok(Self::Primitive(name.clone())),
+                None => Err(format!("Unknown shape type: {name}")),
+            },
+            Value::Array(items) => match items.as_slice() {
+                [item] => Ok(Self::Array(Box::new(Self::parse(item)?))),
+                _ => Err("Array shapes take exactly one item type".to_string()),
+            },
+            Value::Object(fields) => fields
+                .iter()
+                .map(|(name, field)| Ok((name.clone(), Self::parse(field)?)))
+                .collect::<Result<_, String>>()
+                .map(Self::Object),
+            _ => Err(format!("Invalid shape: {value}")),
+        }
+    }
+
+    // Type of the shape, declaring an interface (a JSDoc typedef for JavaScript)
+    // named after it for every nested mapping.
+    fn declare(&self, name: &str, declarations: &mut Vec<String>) -> String {
+        match self {
+            Self::Primitive(primitive) => primitive.clone(),
+            Self::Array(item) => format!("{}[]", item.declare(name, declarations)),
+            Self::Object(fields) => {
+                let properties: Vec<(String, String)> = fields
+                    .iter()
+                    .map(|(field, shape)| (field.clone(), shape.declare(&format!("{name}{}", capitalize(field)), declarations)))
+                    .collect();
+                let declaration = if output_style().is_js() {
+                    let properties: String = properties.iter().map(|(field, shape)| format!("\n * @property {{{shape}}} {field}")).collect();
+                    format!("/**\n * @typedef {{Object}} {name}{properties}\n */")
+                } else {
+                    let properties: String = properties.iter().map(|(field, shape)| format!("\t{field}: {shape};\n")).collect();
+                    format!("export interface {name} {{\n{properties}}}")
+                };
+                declarations.push(declaration);
+                name.to_string()
+            }
+        }
+    }
+
+    fn to_zod(&self) -> String {
+        match self {
+            Self::Primitive(primitive) if primitive == "Date" => "z.coerce.date()".to_string(),
+            Self::Primitive(primitive) => format!("z.{primitive}()"),
+            Self::Array(item) => format!("z.array({})", item.to_zod()),
+            Self::Object(fields) => format!(
+                "z.object({{ {} }})",
+                fields.iter().map(|(field, shape)| format!("{field}: {}", shape.to_zod())).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+}
+
+// Subtype and SRID of a GEOMETRY or GEOGRAPHY column, `GEOMETRY(Point, 4326)` in the schema.
+// Without a subtype the column takes any GeoJSON geometry.
+#[derive(Debug, Clone, Default)]
+struct Spatial {
+    point: bool,
+    srid: Option<u32>
+}
+
+impl Spatial {
+    // The arguments of the type, `Point, 4326`. Point is the only subtype the generated code types precisely.
+    fn parse(args: &str) -> Result<Self, String> {
+        let args: Vec<&str> = args.split(',').map(str::trim).collect();
+        let (subtype, srid) = match args.as_slice() {
+            [subtype] => (*subtype, None),
+            [subtype, srid] => (*subtype, Some(srid.parse::<u32>().map_err(|_| format!("SRID {srid} is not a number"))?)),
+            _ => return Err("spatial types take a subtype and an optional SRID, GEOMETRY(Point, 4326)".to_string()),
+        };
+        if !subtype.eq_ignore_ascii_case("point") {
+            return Err(format!("unsupported subtype {subtype}, only Point is supported, leave the subtype out for any geometry"));
+        }
+        Ok(Self { point: true, srid })
+    }
+
+    fn to_data_type(&self, namespace: &str, db_type: &str) -> String {
+        match (self.point, self.srid) {
+            (false, _) => format!("{namespace}.{db_type}"),
+            (true, None) => format!("{namespace}.{db_type}('POINT')"),
+            (true, Some(srid)) => format!("{namespace}.{db_type}('POINT', {srid})"),
+        }
+    }
+
+    // The column type as Postgres spells it, for the Unsupported type of the Prisma schema.
+    fn to_sql_type(&self, db_type: &str) -> String {
+        match (self.point, self.srid) {
+            (false, _) => db_type.to_lowercase(),
+            (true, None) => format!("{}(Point)", db_type.to_lowercase()),
+            (true, Some(srid)) => format!("{}(Point, {srid})", db_type.to_lowercase()),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        if self.point { "GeoPoint" } else { "GeoGeometry" }
+    }
+
+    // The interface of the GeoJSON value in types.ts, a JSDoc typedef for JavaScript.
+    fn declaration(&self) -> String {
+        let (geometry_type, coordinates) = if self.point { ("\"Point\"", "[number, number]") } else { ("string", "unknown[]") };
+        match output_style().is_js() {
+            true => format!("/**\n * @typedef {{Object}} {}\n * @property {{{geometry_type}}} type\n * @property {{{coordinates}}} coordinates\n */", self.type_name()),
+            false => format!("export interface {} {{\n\ttype: {geometry_type};\n\tcoordinates: {coordinates};\n}}", self.type_name()),
+        }
+    }
+
+    fn to_zod(&self) -> String {
+        match self.point {
+            true => "z.object({ type: z.literal(\"Point\"), coordinates: z.tuple([z.number().min(-180).max(180), z.number().min(-90).max(90)]) })".to_string(),
+            false => "z.object({ type: z.string(), coordinates: z.array(z.unknown()) })".to_string(),
+        }
+    }
+
+    fn to_json_schema(&self) -> Value {
+        match self.point {
+            true => json!({
+                "type": "object",
+                "properties": {
+                    "type": { "type": "string", "enum": ["Point"] },
+                    "coordinates": {
+                        "type": "array",
+                        "items": [{ "type": "number", "minimum": -180, "maximum": 180 }, { "type": "number", "minimum": -90, "maximum": 90 }],
+                        "minItems": 2,
+                        "maxItems": 2,
+                    },
+                },
+                "required": ["type", "coordinates"],
+            }),
+            false => json!({
+                "type": "object",
+                "properties": { "type": { "type": "string" }, "coordinates": { "type": "array" } },
+                "required": ["type", "coordinates"],
+            }),
+        }
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut characters = name.chars();
+    characters.next().map_or_else(String::new, |first| first.to_uppercase().chain(characters).collect())
+}
+
+fn prisma_type(db_type: &str) -> (&'static str, Option<&'static str>) {
+    match db_type {
+        "INTEGER" => ("Int", None),
+        "BIGINT" => ("BigInt", None),
+        "FLOAT" | "REAL" | "DOUBLE" => ("Float", None),
+        "DECIMAL" => ("Decimal", None),
+        "TEXT" => ("String", Some("@db.Text")),
+        "CITEXT" => ("String", Some("@db.Citext")),
+        "BOOLEAN" => ("Boolean", None),
+        "DATE" => ("DateTime", None),
+        "DATEONLY" => ("DateTime", Some("@db.Date")),
+        "TIME" => ("DateTime", Some("@db.Time")),
+        "UUID" => ("String", Some("@db.Uuid")),
+        "JSON" => ("Json", None),
+        _ => ("String", None),
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Field {
     attr: Vec<String>,
     name: String,
     db_type: String,
-    js_type: String
+    js_type: String,
+    shape: Option<(String, Shape)>,
+    computed: Option<Computed>,
+    enumeration: Option<(String, Vec<String>)>,
+    spatial: Option<Spatial>,
+    transform: Option<String>,
+    example: Option<Value>
+}
+
+// Getter of a VIRTUAL field, computed from the listed fields and never stored.
+#[derive(Debug, Clone, Default)]
+struct Computed {
+    depends_on: Vec<String>,
+    getter: Option<String>,
+    returns: Option<String>
 }
 
 impl Field {
@@ -38,14 +489,28 @@ impl Field {
             name: name.to_string(),
             db_type: db_type.to_string(),
             js_type: js_type.to_string(),
+            shape: None,
+            computed: None,
+            enumeration: None,
+            spatial: SPATIAL_TYPES.contains(&db_type).then(Spatial::default),
+            transform: None,
+            example: None,
         }
     }
 
-    fn validate(attr: Vec<&str>, name: &str, db_type: &str, js_type: &str) -> Result<Self, String> {
+    fn validate(attr: Vec<&str>, name: &str, db_type: &str, js_type: &str, strict: bool) -> Result<Self, String> {
         if name.trim().is_empty() {
             return Err("Field name cannot be empty".to_string());
         }
 
+        if !identifier_regex().is_match(name) {
+            return Err(format!("Field name {name} is not a valid identifier"));
+        }
+
+        if RESERVED_WORDS.contains(&name) {
+            return Err(format!("Field name {name} is a reserved word"));
+        }
+
         if !DB_TYPES.contains(&db_type) {
             return Err("Invalid database type".to_string());
         }
@@ -55,361 +520,10217 @@ impl Field {
         }
 
         for attribute in &attr {
-            if !DB_ATTR.contains(&attribute) {
+            if !DB_ATTR.contains(&attr_name(attribute)) {
                 return Err(format!("Invalid attribute: {attribute}"));
             }
         }
 
-        Ok(Self::new(attr, name, db_type, js_type))
-    }
-}
-
-const NAME: &str = "Example_model_name";
-const NAME_PLURAL: &str = "Example_model_name_plural";
-
-fn copy_template(template_path: &str) -> io::Result<String>{
-    let mut file = File::open(template_path)?;
-    let mut content = String::new();
-    file.read_to_string(&mut content)?;
-
-    let content = content
-        .replace("{NAME_UPPER}", NAME)
-        .replace("{NAME_UPPER_PLURAL}", NAME_PLURAL)
-        .replace("{NAME_LOWER}", &NAME.to_lowercase())
-        .replace("{NAME_LOWER_PLURAL}", &NAME_PLURAL.to_lowercase());
-
-    Ok(content)
-}
-fn implement_interface(path: PathBuf) -> io::Result<()>{
-    let file_name = format!("I{NAME}Repository.ts");
-    let file_path = path.join(&file_name);
-    let mut file = File::create(&file_path)?;
+        if db_type == "FILE" && js_type != "string" {
+            return Err(format!("Field {name} has type FILE, which is stored as a path and must use the string JavaScript type"));
+        }
 
-    let template_path = env::var("INTERFACE_REPOSITORY_TEMPLATE").expect("INTERFACE_REPOSITORY_TEMPLATE not set in .env file");
+        if js_type == "bigint" && db_type != "BIGINT" {
+            return Err(format!("Field {name} has the bigint JavaScript type, which only BIGINT fields take"));
+        }
 
-    let content: String = copy_template(&template_path)?;
+        if db_type == "ENUM" && js_type != "string" {
+            return Err(format!("Field {name} has type ENUM, whose values are strings and must use the string JavaScript type"));
+        }
 
-    file.write_all(content.as_bytes())?;
+        if db_type == "CITEXT" && js_type != "string" {
+            return Err(format!("Field {name} has type CITEXT, which holds text and must use the string JavaScript type"));
+        }
 
-    Ok(())
-}
+        // @UniqueInsensitive is the functional index of dialects without CITEXT, it replaces @Unique.
+        if attr.iter().any(|attribute| attr_name(attribute) == "@UniqueInsensitive") {
+            if attr.iter().any(|attribute| attr_name(attribute) == "@Unique") {
+                return Err(format!("Field {name} has both @Unique and @UniqueInsensitive, keep one of them"));
+            }
+            if !["STRING", "TEXT", "CITEXT"].contains(&db_type) {
+                return Err(format!("Field {name} has @UniqueInsensitive, which only STRING, TEXT and CITEXT fields take"));
+            }
+        }
 
-fn implement_use_case(path: PathBuf, properties: Vec<Field>) -> io::Result<()>{
-    let name_lower = NAME.to_lowercase();
+        if SPATIAL_TYPES.contains(&db_type) && js_type != "object" {
+            return Err(format!("Field {name} has type {db_type}, whose values are GeoJSON objects and must use the object JavaScript type"));
+        }
 
-    let new_path = path.join(NAME);
+        if db_type == "VIRTUAL" && attr.iter().any(|attribute| ["@PrimaryKey", "@Index"].contains(&attr_name(attribute))) {
+            return Err(format!("Field {name} is VIRTUAL and cannot be a @PrimaryKey or @Index"));
+        }
 
-    fs::create_dir_all(&new_path).expect("Problem creating folder for use_case");
+        for attribute in attr.iter().filter(|attribute| attr_name(attribute) == "@Index") {
+            Index::parse(attribute).map_err(|error| format!("Field {name}: {error}"))?;
+        }
 
-    let add_path = new_path.join(format!("Add{NAME}.ts"));
-    let gets_path = new_path.join(format!("Get{NAME_PLURAL}.ts"));
-    let delete_path = new_path.join(format!("Delete{NAME}.ts"));
-    let update_path = new_path.join(format!("Update{NAME}.ts"));
+        for attribute in attr.iter().filter(|attribute| attr_name(attribute) == "@References") {
+            Reference::parse(attribute).map_err(|error| format!("Field {name}: {error}"))?;
+        }
 
-    let mut add_file = File::create(&add_path)?;
-    let mut gets_file = File::create(&gets_path).expect("File cannot be created");
-    let mut delete_file = File::create(&delete_path)?;
-    let mut update_file = File::create(&update_path)?;
+        if attr.iter().any(|attribute| attr_name(attribute) == "@ForeignKey") && !FOREIGN_KEY_TYPES.contains(&db_type) {
+            let message = format!("Field {name} has @ForeignKey but type {db_type}, expected INTEGER, BIGINT or UUID");
+            if strict {
+                return Err(message);
+            }
+            warn(message);
+        }
 
-    let add_template_path = env::var("ADD_USE_CASE_TEMPLATE").expect("ADD_USE_CASE_TEMPLATE not set in .env file");
-    let gets_template_path = env::var("GETS_USE_CASE_TEMPLATE").expect("GETS_USE_CASE_TEMPLATE not set in .env file");
-    let delete_template_path = env::var("DELETE_USE_CASE_TEMPLATE").expect("DELETE_USE_CASE_TEMPLATE not set in .env file");
-    let update_template_path = env::var("UPDATE_USE_CASE_TEMPLATE").expect("UPDATE_USE_CASE_TEMPLATE not set in .env file");
+        Ok(Self::new(attr, name, db_type, js_type))
+    }
 
-    let mut add_content = copy_template(&add_template_path)?;
-    let mut gets_content = copy_template(&gets_template_path)?;
-    let mut delete_content = copy_template(&delete_template_path)?;
-    let mut update_content = copy_template(&update_template_path)?;
+    fn has_attr(&self, attribute: &str) -> bool {
+        self.attr.iter().any(|value| attr_name(value) == attribute)
+    }
 
-    let mut dynamic_add_properties = String::new();
-    let mut dynamic_update_properties = String::new();
-    for (index, property) in properties.iter().enumerate() {
-        if(property.name == "id"){
-            continue;
-        }
-        if(dynamic_add_properties.len() > 0){
-            dynamic_add_properties.push_str("\t\t\t");
-        }
-        if(dynamic_update_properties.len() > 0){
-            dynamic_update_properties.push_str("\t\t");
-        }
-        dynamic_add_properties.push_str(&format!("{}: request.{},", property.name, property.name));
-        dynamic_update_properties.push_str(&format!("{}.{} = request.{};", name_lower, property.name, property.name));
-        if(index+1 != properties.len()){
-            dynamic_add_properties.push_str("\n\n");
-            dynamic_add_properties.push_str("\n");
+    // Type of the property in the model and attributes, the generated interface for shaped JSON
+    // and spatial columns and the union type of core/utils/enums for ENUM fields.
+    fn property_type(&self) -> &str {
+        match (&self.shape, &self.enumeration, &self.spatial) {
+            (Some((name, _)), _, _) | (None, Some((name, _)), _) => name,
+            (None, None, Some(spatial)) => spatial.type_name(),
+            (None, None, None) => &self.js_type,
         }
     }
-    add_content = add_content.replace("{DYNAMIC_ADD_PROPERTIES}", &dynamic_add_properties);
-    update_content = update_content.replace("{DYNAMIC_UPDATE_PROPERTIES}", &dynamic_update_properties);
 
-    add_file.write_all(add_content.as_bytes()).expect("Error writing to add use case file");
-    gets_file.write_all(gets_content.as_bytes()).expect("Error writing to gets use case file");
-    delete_file.write_all(delete_content.as_bytes()).expect("Error writing to delete use case file");
-    update_file.write_all(update_content.as_bytes()).expect("Error writing to update use case file");
+    // The interface of types.ts the property is typed as, if any.
+    fn interface_name(&self) -> Option<&str> {
+        match (&self.shape, &self.spatial) {
+            (Some((name, _)), _) => Some(name),
+            (None, Some(spatial)) => Some(spatial.type_name()),
+            (None, None) => None,
+        }
+    }
 
-    Ok(())
-}
+    fn enum_name(&self) -> Option<&str> {
+        self.enumeration.as_ref().map(|(name, _)| name.as_str())
+    }
 
-fn implement_utils(path: PathBuf, properties: Vec<Field>) -> io::Result<()>{
-    let new_path = path.join(NAME);
+    // The enum values as arguments, `"pending", "paid"`.
+    fn enum_literals(&self) -> String {
+        self.enumeration
+            .iter()
+            .flat_map(|(_, values)| values)
+            .map(|value| format!("{value:?}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 
-    fs::create_dir_all(&new_path).expect("Problem creating folder for use_case");
+    fn is_virtual(&self) -> bool {
+        self.db_type == "VIRTUAL"
+    }
 
-    let mut request_file = File::create(new_path.join("Request.ts"))?;
-    let mut types_file = File::create(new_path.join("types.ts"))?;
+    // A key the database fills in on insert, an auto increment, a @Default, or the UUID default of the migration.
+    fn is_generated_key(&self, dialect: &str) -> bool {
+        self.has_attr("@PrimaryKey")
+            && (self.has_attr("@AutoIncrement") || self.has_attr("@Default") || (self.db_type == "UUID" && uuid_default(dialect).is_some()))
+    }
 
-    let request_template_path = env::var("REQUEST_UTILS_TEMPLATE").expect("REPOSITORY_TEMPLATE not set in .env file");
-    let types_template_path = env::var("TYPES_UTILS_TEMPLATE").expect("REPOSITORY_TEMPLATE not set in .env file");
+    fn reference(&self) -> Option<Reference> {
+        self.attr.iter().find(|attribute| attr_name(attribute) == "@References").and_then(|attribute| Reference::parse(attribute).ok())
+    }
 
-    let mut request_content = copy_template(&request_template_path)?;
-    let mut types_content = copy_template(&types_template_path)?;
+    // BIGINT typed as bigint, which the database driver hands back as a string.
+    fn is_bigint(&self) -> bool {
+        self.js_type == "bigint"
+    }
 
-    let mut dynamic_properties_attributes = String::new();
-    let mut dynamic_properties_details = String::new();
+    // BIGINT kept as the string of digits the database driver hands back, bigint_as: string.
+    fn is_bigint_string(&self) -> bool {
+        self.db_type == "BIGINT" && self.js_type == "string"
+    }
 
-    for (index, property) in properties.iter().enumerate() {
-        if(dynamic_properties_attributes.len() > 0){
-            dynamic_properties_attributes.push_str("\t");
+    // The get() and set() of the column options, for bigint values and transforms.
+    fn accessors(&self, indent: &str) -> Vec<String> {
+        let name = &self.name;
+        let setter = |value: &str| format!("{indent}set(value) {{\n{indent}\tthis.setDataValue(\"{name}\", {value});\n{indent}}}");
+        let keep = |block: &str, body: &str| {
+            format!("{indent}\t// {KEEP_START}{name}_{block}\n{indent}\t{body}\n{indent}\t// {KEEP_END}{name}_{block}")
+        };
+        let mut accessors = Vec::new();
+        if self.is_bigint() {
+            accessors.push(self.bigint_getter(indent));
         }
-        if(dynamic_properties_details.len() > 0){
-            dynamic_properties_details.push_str("\t");
+        match self.transform.as_deref() {
+            Some("lowercase") => accessors.push(setter("typeof value === \"string\" ? value.toLowerCase() : value")),
+            Some("trim") => accessors.push(setter("typeof value === \"string\" ? value.trim() : value")),
+            Some("encrypt") => {
+                accessors.push(format!(
+                    "{indent}get() {{\n{indent}\tconst value = this.getDataValue(\"{name}\");\n{indent}\treturn typeof value === \"string\" ? decrypt(value) : value;\n{indent}}}"
+                ));
+                accessors.push(setter("typeof value === \"string\" ? encrypt(value) : value"));
+            }
+            Some("custom") => {
+                accessors.push(format!("{indent}get() {{\n{}\n{indent}}}", keep("get", &format!("return this.getDataValue(\"{name}\");"))));
+                accessors.push(format!("{indent}set(value) {{\n{}\n{indent}}}", keep("set", &format!("this.setDataValue(\"{name}\", value);"))));
+            }
+            _ => {}
         }
+        accessors
+    }
 
-        dynamic_properties_attributes.push_str(&format!("{}: {};", property.name, property.js_type));
-        if(property.name != "id"){
-            dynamic_properties_details.push_str(&format!("{}: {};", property.name, property.js_type));
-        }
+    fn bigint_getter(&self, indent: &str) -> String {
+        format!(
+            "{indent}get() {{\n{indent}\tconst value = this.getDataValue(\"{}\");\n{indent}\treturn value === null || value === undefined ? value : BigInt(value);\n{indent}}}",
+            self.name
+        )
+    }
 
-        if(index+1 != properties.len()){
-            dynamic_properties_attributes.push_str("\n");
-            if dynamic_properties_details.len() > 0 {
-                dynamic_properties_details.push_str("\n");
-            };
+    // Type a VIRTUAL field reports, the declared `returns` or one matching its js_type.
+    fn value_type(&self) -> &str {
+        if !self.is_virtual() {
+            return &self.db_type;
+        }
+        if let Some(returns) = self.computed.as_ref().and_then(|computed| computed.returns.as_deref()) {
+            return returns;
+        }
+        match self.js_type.as_str() {
+            "number" | "float" | "double" => "FLOAT",
+            "boolean" => "BOOLEAN",
+            "Date" => "DATE",
+            "object" => "JSON",
+            _ => "STRING",
         }
     }
 
-    types_content = types_content.replace("{DYNAMIC_PROPERTIES_ATTRIBUTES}", &dynamic_properties_attributes);
-    types_content = types_content.replace("{DYNAMIC_PROPERTIES_DETAILS}", &dynamic_properties_details);
+    fn virtual_type(&self, namespace: &str) -> String {
+        let depends_on = self.computed.as_ref().map_or(&[][..], |computed| &computed.depends_on[..]);
+        let value_type = format!("{namespace}.{}", self.value_type());
+        if depends_on.is_empty() {
+            return format!("{namespace}.VIRTUAL({value_type})");
+        }
+        let fields = depends_on.iter().map(|field| format!("\"{field}\"")).collect::<Vec<_>>().join(", ");
+        format!("{namespace}.VIRTUAL({value_type}, [{fields}])")
+    }
 
-    request_file.write_all(request_content.as_bytes())?;
-    types_file.write_all(types_content.as_bytes())?;
+    // Getter body inside a keep block, so hand edits survive regeneration.
+    fn virtual_getter(&self, indent: &str) -> String {
+        let expression = self.computed
+            .as_ref()
+            .and_then(|computed| computed.getter.clone())
+            .unwrap_or_else(|| format!("this.getDataValue(\"{}\")", self.name));
+        format!(
+            "{indent}// {KEEP_START}{0}\n{indent}return {expression};\n{indent}// {KEEP_END}{0}",
+            self.name
+        )
+    }
 
-    Ok(())
-}
+    fn column_type(&self) -> &str {
+        // FILE fields hold the stored upload path.
+        if self.db_type == "FILE" { "STRING" } else { &self.db_type }
+    }
 
-fn implement_repository(path: PathBuf) -> io::Result<()>{
-    let file_path = path.join(&format!("{}Repository.ts", NAME.to_lowercase()));
-    let mut file = File::create(&file_path)?;
+    // The column type under the DataType or Sequelize namespace, with the subtype and SRID of spatial columns.
+    fn data_type(&self, namespace: &str) -> String {
+        match &self.spatial {
+            Some(spatial) => spatial.to_data_type(namespace, &self.db_type),
+            None => format!("{namespace}.{}", self.column_type().to_uppercase()),
+        }
+    }
 
-    let template_path = env::var("REPOSITORY_TEMPLATE").expect("REPOSITORY_TEMPLATE not set in .env file");
+    // The `example` of the schema, or one made up from the type. Request bodies, factories and
+    // the --field-docs comments all take it from here.
+    fn example_value(&self) -> Value {
+        if let Some(example) = &self.example {
+            return example.clone();
+        }
+        match self.db_type.as_str() {
+            "BIGINT" if self.is_bigint() || self.is_bigint_string() => json!("1"),
+            "INTEGER" | "BIGINT" => json!(1),
+            "FLOAT" | "REAL" | "DOUBLE" | "DECIMAL" => json!(9.99),
+            "BOOLEAN" => json!(true),
+            "DATE" => json!("2024-01-01T00:00:00.000Z"),
+            "DATEONLY" => json!("2024-01-01"),
+            "TIME" => json!("12:00:00"),
+            "UUID" => json!("3f2504e0-4f89-41d3-9a0c-0305e82c3301"),
+            "JSON" => json!({}),
+            "ENUM" => self.enumeration.as_ref().and_then(|(_, values)| values.first()).map_or(Value::Null, |value| json!(value)),
+            "GEOMETRY" | "GEOGRAPHY" => json!({ "type": "Point", "coordinates": [13.405, 52.52] }),
+            "TEXT" => json!(format!("Example {} text", self.name)),
+            _ => json!(format!("example {}", self.name)),
+        }
+    }
 
-    let mut content = copy_template(&template_path)?;
+    // JSON Schema of the value as it is serialized into a message, bigint as the string JSON.stringify needs.
+    fn to_json_schema(&self) -> Value {
+        let mut schema = match &self.spatial {
+            _ if self.is_bigint() => json!({ "type": "string" }),
+            Some(spatial) => spatial.to_json_schema(),
+            None => json_schema_type(self.value_type()),
+        };
+        if let Some((_, values)) = &self.enumeration {
+            schema["enum"] = json!(values);
+        }
+        if let Some(description) = self.description() {
+            schema["description"] = json!(description);
+        }
+        if self.has_attr("@AllowNull") {
+            let value_type = schema["type"].clone();
+            schema["type"] = json!([value_type, "null"]);
+        }
+        schema
+    }
 
-    file.write_all(content.as_bytes()).expect("Error writing to the repository file.");
+    // Reads the field from the route params into a const of its name, answering 400 when it does not parse.
+    fn to_param_parser(&self) -> String {
+        let name = &self.name;
+        let reject = |check: String, expected: &str| {
+            format!("    if ({check}) {{\n        res.status(400).json({{ message: \"{name} must be {expected}\" }});\n        return undefined;\n    }}")
+        };
+        match (self.js_type.as_str(), self.db_type.as_str()) {
+            ("bigint", _) => format!("{}\n    const {name} = BigInt(req.params.{name});", reject(format!("!/^-?\\d+$/.test(req.params.{name})"), "an integer")),
+            ("number", "INTEGER" | "BIGINT") => format!("    const {name} = Number.parseInt(req.params.{name}, 10);\n{}", reject(format!("Number.isNaN({name})"), "an integer")),
+            ("number", _) => format!("    const {name} = Number(req.params.{name});\n{}", reject(format!("Number.isNaN({name})"), "a number")),
+            (_, "UUID") => format!(
+                "    const {name} = req.params.{name};\n{}",
+                reject(format!("!/^[0-9a-f]{{8}}-[0-9a-f]{{4}}-[0-9a-f]{{4}}-[0-9a-f]{{4}}-[0-9a-f]{{12}}$/i.test({name})"), "a UUID")
+            ),
+            _ => format!("    const {name} = req.params.{name};"),
+        }
+    }
 
-    Ok(())
-}
+    // The @Comment text, the schema writes it as a string literal.
+    fn description(&self) -> Option<String> {
+        let comment = self.attr.iter().find(|attribute| attr_name(attribute) == "@Comment").and_then(|attribute| attr_args(attribute))?.trim();
+        Some(serde_json::from_str::<String>(comment).unwrap_or_else(|_| comment.trim_matches(['\'', '`']).to_string()))
+    }
 
-// fn implement_controllers(path: PathBuf);
-fn implement_model(path: PathBuf, properties: Vec<Field>) -> io::Result<()>{
-    let name_lower = NAME.to_lowercase();
-    let file_name = format!("{name_lower}Model.ts");
-    let file_path = path.join(&file_name);
-    let mut file = File::create(&file_path)?;
-
-    let template_path = env::var("MODEL_TEMPLATE").expect("MODEL_TEMPLATE not set in .env file");
-
-    let mut dynamic_properties = String::new();
-    for (index, item) in properties.iter().enumerate() {
-        for attribute in &item.attr {
-            dynamic_properties.push_str(&format!("\t{}\n", attribute));
-        }
-        dynamic_properties.push_str(&format!(
-            "\t@Column(DataType.{})\n\t{}!: {};",
-            item.db_type.to_uppercase(),
-            item.name,
-            item.js_type
-        ));
-        if index + 1 < properties.len() {
-            dynamic_properties.push_str("\n\n");
+    // --field-docs block above the property, every line starting with `indent`.
+    fn to_jsdoc(&self, indent: &str) -> String {
+        let mut lines: Vec<String> = self.description().map(|description| description.lines().map(str::to_string).collect()).unwrap_or_default();
+        lines.push(format!("@example {}", self.example_value()));
+        if self.has_attr("@AllowNull") {
+            lines.push("@nullable".to_string());
         }
+        let body: String = lines.iter().map(|line| format!("{indent} * {}", jsdoc_text(line)).trim_end().to_string() + "\n").collect();
+        format!("{indent}/**\n{body}{indent} */\n")
     }
 
-    let mut content: String = copy_template(&template_path)?;
-
-    content = content.replace("{DYNAMIC_PROPERTIES}", &dynamic_properties);
+    // Default of the field in the test factories: null when nullable, then a literal @Default,
+    // then the example value, numbered by the factory sequence for @Unique fields.
+    fn factory_value(&self) -> String {
+        if self.has_attr("@AllowNull") {
+            return if output_style().is_js() { "null".to_string() } else { format!("null as {} | null", self.js_type) };
+        }
+        let default = self.attr
+            .iter()
+            .find(|attribute| attr_name(attribute) == "@Default")
+            .and_then(|attribute| attr_args(attribute))
+            .filter(|value| !value.starts_with("DataType.") && !value.starts_with("Sequelize."));
+        if let Some(value) = default {
+            return value.to_string();
+        }
+        // A random point is a valid value of any geometry.
+        if self.spatial.is_some() {
+            let coordinates = "[Number((Math.random() * 360 - 180).toFixed(6)), Number((Math.random() * 180 - 90).toFixed(6))]";
+            return match output_style().is_js() {
+                true => format!("{{ type: \"Point\", coordinates: {coordinates} }}"),
+                false => format!("{{ type: \"Point\" as const, coordinates: {coordinates} as [number, number] }}"),
+            };
+        }
 
-    file.write_all(content.as_bytes()).expect("Error writing to the model file.");
+        let example = self.example_value();
+        match (self.has_attr("@Unique") || self.has_attr("@UniqueInsensitive"), self.db_type.as_str()) {
+            (true, "UUID") => "randomUUID()".to_string(),
+            (true, "BIGINT") if self.is_bigint() || self.is_bigint_string() => "String(sequence)".to_string(),
+            (true, "INTEGER" | "BIGINT") => "sequence".to_string(),
+            (true, "STRING" | "TEXT" | "CITEXT" | "CHAR" | "FILE") => format!("`{} ${{sequence}}`", example.as_str().unwrap_or_default()),
+            (_, "DATE") => format!("new Date({example})"),
+            _ => example.to_string(),
+        }
+    }
 
-    Ok(())
-}
+    // Table indexes are declared in the @Table options instead of an @Index decorator.
+    fn to_sequelize_string(&self, table_indexes: bool) -> String {
+        let mut decorators = String::new();
+        for attribute in self.attr.iter().filter(|attribute| !GENERATOR_ATTR.contains(&attr_name(attribute))) {
+            match attr_name(attribute) {
+                "@Index" if table_indexes => {}
+                "@Index" => {
+                    let decorator = Index::parse(attribute).map_or_else(|_| attribute.clone(), |index| index.to_decorator());
+                    decorators.push_str(&format!("\t{decorator}\n"));
+                }
+                _ => decorators.push_str(&format!("\t{attribute}\n")),
+            }
+        }
+        if self.is_virtual() {
+            return format!(
+                "{decorators}\t@Column({})\n\tget {}(): {} {{\n{}\n\t}}",
+                self.virtual_type("DataType"),
+                self.name,
+                self.property_type(),
+                self.virtual_getter("\t\t")
+            );
+        }
+        if let Some(enum_name) = self.enum_name() {
+            return format!("{decorators}\t@Column(DataType.ENUM(...{enum_name}))\n\t{}!: {};", self.name, self.property_type());
+        }
 
-fn implement_routes(path: PathBuf) -> io::Result<()>{
-    let file_name = format!("{}Routes.ts", NAME.to_lowercase());
-    let file_path = path.join(&file_name);
-    let mut file = File::create(&file_path)?;
+        // A @ForeignKey column leaves the constraint to its association, which takes the actions instead.
+        let mut column_options = self.accessors("\t\t");
+        if let Some(reference) = self.reference().filter(|_| !self.has_attr("@ForeignKey")) {
+            column_options.extend(reference.options().iter().map(|option| format!("\t\t{option}")));
+        }
+        if !column_options.is_empty() {
+            return format!(
+                "{decorators}\t@Column({{\n\t\ttype: {},\n{},\n\t}})\n\t{}!: {};",
+                self.data_type("DataType"),
+                column_options.join(",\n"),
+                self.name,
+                self.property_type()
+            );
+        }
+        format!(
+            "{decorators}\t@Column({})\n\t{}!: {};",
+            self.data_type("DataType"),
+            self.name,
+            self.property_type()
+        )
+    }
 
-    let template_path = env::var("ROUTES_TEMPLATE").expect("ROUTES_TEMPLATE not set in .env file");
+    // JavaScript types spell the enum out as a union of its values.
+    fn to_jsdoc_property(&self) -> String {
+        if self.enumeration.is_some() {
+            return format!(" * @property {{{}}} {}", self.enum_literals().replace(", ", " | "), self.name);
+        }
+        format!(" * @property {{{}}} {}", jsdoc_type(self.property_type()), self.name)
+    }
 
-    let content: String = copy_template(&template_path)?;
+    // Prisma enum values are identifiers, an enum with other values stays a String column.
+    fn prisma_enum(&self) -> Option<(&str, &[String])> {
+        let (enum_name, values) = self.enumeration.as_ref()?;
+        values.iter().all(|value| identifier_regex().is_match(value)).then_some((enum_name.as_str(), values.as_slice()))
+    }
 
-    file.write_all(content.as_bytes())?;
+    fn to_prisma_string(&self) -> String {
+        let (scalar, native_type) = match &self.spatial {
+            // Prisma has no spatial scalars, the column stays out of the generated client.
+            Some(spatial) => (format!("Unsupported(\"{}\")", spatial.to_sql_type(&self.db_type)), None),
+            None => match self.prisma_enum() {
+                Some((enum_name, _)) => (enum_name.to_string(), None),
+                None => {
+                    let (scalar, native_type) = prisma_type(&self.db_type);
+                    (scalar.to_string(), native_type)
+                }
+            },
+        };
+        let optional = if self.has_attr("@AllowNull") { "?" } else { "" };
+        let mut modifiers: Vec<String> = Vec::new();
 
-    Ok(())
-}
-fn implement_controllers(path: PathBuf, properties: Vec<Field>) -> io::Result<()>{
-    let file_name = format!("{}Controllers.ts", NAME.to_lowercase());
-    let file_path = path.join(&file_name);
-    let mut file = File::create(&file_path)?;
+        for attribute in &self.attr {
+            let modifier = match (attr_name(attribute), attr_args(attribute)) {
+                ("@PrimaryKey", _) => "@id".to_string(),
+                ("@AutoIncrement", _) => "@default(autoincrement())".to_string(),
+                ("@Unique", _) => "@unique".to_string(),
+                ("@CreatedAt", _) => "@default(now())".to_string(),
+                ("@UpdatedAt", _) => "@updatedAt".to_string(),
+                ("@Default", Some(value)) if self.prisma_enum().is_some() => {
+                    format!("@default({})", value.trim_matches(['"', '\'']).rsplit('.').next().unwrap_or(value))
+                },
+                ("@Default", Some(value)) => format!("@default({value})"),
+                ("@Length", Some(length)) => format!("@db.VarChar({length})"),
+                _ => continue,
+            };
+            modifiers.push(modifier);
+        }
 
-    let template_path = env::var("CONTROLLERS_TEMPLATE").expect("CONTROLLERS_TEMPLATE not set in .env file");
+        if self.db_type == "UUID" && self.has_attr("@PrimaryKey") && !self.has_attr("@Default") {
+            modifiers.push("@default(uuid())".to_string());
+        }
+        if let Some(native_type) = native_type {
+            modifiers.push(native_type.to_string());
+        }
 
-    let mut content: String = copy_template(&template_path)?;
+        let mut line = format!("{} {scalar}{optional}", self.name);
+        for modifier in modifiers {
+            line.push(' ');
+            line.push_str(&modifier);
+        }
+        line
+    }
+}
 
-    let mut dynamic_properties_details = String::new();
+// Class names a model cannot take, the globals of TypeScript and what the generated files import.
+const RESERVED_MODEL_NAMES: &[&str] = &[
+    "Array", "Boolean", "Date", "Error", "Function", "JSON", "Map", "Math", "Number", "Object", "Partial",
+    "Promise", "Record", "RegExp", "Set", "String", "Symbol",
+    "Column", "DataType", "DataTypes", "Model", "Op", "Sequelize", "Table", "Transaction",
+    "Container", "Router", "Request", "Response",
+];
 
-    for (index, property) in properties.iter().enumerate() {
+// Name of a model as every generator takes it, a PascalCase identifier that is no reserved class name.
+// snake_case, kebab-case and space separated input is joined into PascalCase, `blog post` becomes `BlogPost`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ModelName(String);
 
-        if(dynamic_properties_details.len() > 0){
-            dynamic_properties_details.push_str("\t\t\t\t");
+impl ModelName {
+    fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err("Model name cannot be empty".to_string());
         }
-
-        if(property.name != "id"){
-            dynamic_properties_details.push_str(&format!("{}: req.body.{},", property.name, property.name));
+        if input.starts_with(|character: char| character.is_ascii_digit()) {
+            return Err(format!("Model name {input} starts with a digit"));
         }
 
-        if(index+1 != properties.len()){
-            if dynamic_properties_details.len() > 0 {
-                dynamic_properties_details.push_str("\n");
-            };
+        let name: String = input
+            .split(['_', '-', ' '])
+            .filter(|word| !word.is_empty())
+            .map(capitalize)
+            .collect();
+        if !identifier_regex().is_match(&name) {
+            return Err(format!("Model name {input} has characters other than letters, digits, _, - and spaces"));
+        }
+        if RESERVED_MODEL_NAMES.contains(&name.as_str()) {
+            return Err(format!("Model name {name} is reserved"));
         }
-    }
 
-    content = content.replace("{DYNAMIC_PROPERTIES_DETAILS}", &dynamic_properties_details);
+        Ok(Self(name))
+    }
 
-    file.write_all(content.as_bytes())?;
+    // The example model keeps the name its output has always had, it is not user input.
+    fn example(name: &'static str) -> Self {
+        Self(name.to_string())
+    }
 
-    Ok(())
+    fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
-fn update_sequelize(path: PathBuf) -> io::Result<()>{
-    let sequelize_path = path.join("sequelize.ts");
-    let mut file_content = String::new();
-    {
-        let mut file = OpenOptions::new().read(true).open(sequelize_path.clone())?;
-        file.read_to_string(&mut file_content)?;
+impl Deref for ModelName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
     }
+}
 
-    let import = format!("import {{ {} }} from \"@infrastructure/models/{}Model\";\n", NAME, NAME.to_lowercase());
-    if !file_content.contains(&import) {
-        file_content = import + &file_content;
+impl PartialEq<str> for ModelName {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
     }
+}
 
-    // Add model to models array
-    let models_regex = Regex::new(r"models:\s*\[\s*(.*?)\s*]").unwrap();
-    if let Some(captures) = models_regex.captures(&file_content) {
-        let models_content = captures.get(1).unwrap().as_str();
-        if !models_content.contains(NAME) {
-            let updated_models_content = if models_content.is_empty() {
-                format!("models: [{}]", NAME)
-            } else {
-                format!("models: [{}]", models_content.split(", ").chain(std::iter::once(NAME)).collect::<Vec<_>>().join(", "))
-            };
-            file_content = models_regex.replace(&file_content, updated_models_content).into_owned();
-        }
+impl PartialEq<String> for ModelName {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
     }
+}
 
-    {
-        let mut file = OpenOptions::new().write(true).truncate(true).open(sequelize_path)?;
-        file.write_all(file_content.as_bytes())?;
+impl fmt::Display for ModelName {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
     }
+}
 
-    Ok(())
+#[derive(Debug, Clone)]
+struct Model {
+    name: ModelName,
+    name_plural: ModelName,
+    fields: Vec<Field>,
+    route_prefix: Option<String>,
+    group: Option<String>,
+    table: TableOptions,
+    // `tenant: false` in the schema leaves the model out of --tenant-field.
+    tenant_scoped: bool,
+    tenant_field: Option<String>
 }
 
-fn main() -> io::Result<()> {
-    dotenv().ok();
+// Sequelize table options set on the model in the schema, unset ones keep the Sequelize defaults.
+#[derive(Debug, Clone, Default)]
+struct TableOptions {
+    table_name: Option<String>,
+    schema: Option<String>,
+    underscored: bool,
+    paranoid: bool,
+    timestamps: Option<bool>
+}
 
-    let main = Path::new("C:/Users/erlan/Documents/Spark/Clean Architecture");
+// A closing */ in a description would end the comment early.
+fn jsdoc_text(text: &str) -> String {
+    text.replace("*/", "*\\/")
+}
 
-    let directories = vec![
-        ("core",
-            vec!["interfaces", "use_cases", "utils"]),
-        ("presentation",
-            vec!["controllers"]),
-        ("infrastructure",
-            vec!["config", "models", "repositories", "routes"]),
-    ];
+// "firstName" becomes "first_name", the column naming of underscored tables.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut previous_lower = false;
+    for character in name.chars() {
+        if character.is_uppercase() && previous_lower {
+            snake.push('_');
+        }
+        previous_lower = character.is_lowercase() || character.is_ascii_digit();
+        snake.extend(character.to_lowercase());
+    }
+    snake
+}
 
-    let properties: Vec<Field> = vec![
-        Field::validate(
-            vec!["@PrimaryKey", "@AutoIncrement"],
-            "id",
-            "INTEGER",
-            "number"
-        ).unwrap_or_else(|error| {
-            println!("Error in fields: {error}");
-            process::exit(1);
-        }),
-        Field::validate(
-            vec![],
-            "content",
-            "STRING",
-            "string"
-        ).unwrap_or_else(|error| {
-            println!("Error in fields: {error}");
-            process::exit(1);
-        }),
-        Field::validate(
-            vec![],
-            "name",
-            "STRING",
-            "string"
-        ).unwrap_or_else(|error| {
-            println!("Error in fields: {error}");
-            process::exit(1);
-        })
-    ];
+impl Model {
+    fn new(name: ModelName, name_plural: ModelName, fields: Vec<Field>) -> Self {
+        Self {
+            name,
+            name_plural,
+            fields,
+            route_prefix: None,
+            group: None,
+            table: TableOptions::default(),
+            tenant_scoped: true,
+            tenant_field: None,
+        }
+    }
 
-    for (dir, subdirs) in directories{
+    fn validate(name: ModelName, name_plural: ModelName, fields: Vec<Field>) -> Result<Self, String> {
+        let primary_keys = fields.iter().filter(|field| field.has_attr("@PrimaryKey")).count();
 
-        for subdir in subdirs{
-            let current_dir = main.join(dir).join(subdir);
+        if primary_keys == 0 {
+            return Err(format!("Model {name} has no @PrimaryKey field"));
+        }
+
+        if primary_keys > 1 {
+            return Err(format!("Model {name} has {primary_keys} @PrimaryKey fields, pass --composite-pk for a composite key"));
+        }
 
-            if !current_dir.exists() {
-                fs::create_dir_all(&current_dir)?;
+        for field in &fields {
+            if field.has_attr("@AutoIncrement") && !field.has_attr("@PrimaryKey") {
+                warn(format!("field {} has @AutoIncrement without @PrimaryKey", field.name));
             }
-            match current_dir.to_str() {
-                Some(path_str) => println!("{}", path_str),
-                None => println!("Failed to convert PathBuf to string"),
+            for dependency in field.computed.iter().flat_map(|computed| &computed.depends_on) {
+                if !fields.iter().any(|other| &other.name == dependency && !other.is_virtual()) {
+                    return Err(format!("Field {} depends on {dependency}, which is not a stored field of {name}", field.name));
+                }
+            }
+        }
+
+        // A composite index may state its options on any of its fields, but they have to agree.
+        let mut named_indexes: Vec<(Index, &str)> = Vec::new();
+        for field in &fields {
+            for attribute in field.attr.iter().filter(|attribute| attr_name(attribute) == "@Index") {
+                let index = Index::parse(attribute).map_err(|error| format!("Field {}: {error}", field.name))?;
+                let Some(index_name) = index.name.clone() else { continue };
+                match named_indexes.iter_mut().find(|(other, _)| other.name == index.name) {
+                    Some((other, first)) => {
+                        if other.unique.zip(index.unique).is_some_and(|(left, right)| left != right)
+                            || other.using.as_ref().zip(index.using.as_ref()).is_some_and(|(left, right)| left != right)
+                        {
+                            return Err(format!("Index {index_name} has conflicting options on {first} and {}", field.name));
+                        }
+                        other.unique = other.unique.or(index.unique);
+                        other.using = other.using.take().or(index.using);
+                    }
+                    None => named_indexes.push((index, &field.name)),
+                }
+            }
+        }
+
+        Ok(Self::new(name, name_plural, fields))
+    }
+
+    fn to_sequelize_class(&self, table_indexes: bool, field_docs: bool) -> String {
+        let belongs_to = Regex::new(r"^@BelongsTo\(\s*\(\)\s*=>\s*(\w+)\s*\)$").unwrap();
+        let mut fields = self.fields.clone();
+
+        // The actions of a @ForeignKey @References column go on the @BelongsTo of the same model.
+        for reference in self.fields.iter().filter(|field| field.has_attr("@ForeignKey")).filter_map(Field::reference) {
+            if reference.actions().is_empty() {
+                continue;
             }
-            if subdir == "models"{
-                implement_model(current_dir.clone(), properties.clone())?;
+            let association = fields
+                .iter_mut()
+                .flat_map(|field| field.attr.iter_mut())
+                .find(|attribute| belongs_to.captures(attribute).is_some_and(|captures| captures[1] == reference.target));
+            match association {
+                Some(attribute) => *attribute = format!("@BelongsTo(() => {}, {{ {} }})", reference.target, reference.actions().join(", ")),
+                None => warn(format!(
+                    "{} references {} through @ForeignKey but has no @BelongsTo(() => {}) to put its actions on",
+                    self.name, reference.target, reference.target
+                )),
             }
-            if subdir == "interfaces" {
-                implement_interface(current_dir.clone())?;
+        }
+
+        fields
+            .iter()
+            .map(|field| match field_docs {
+                true => format!("{}{}", field.to_jsdoc("\t"), field.to_sequelize_string(table_indexes)),
+                false => field.to_sequelize_string(table_indexes),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    // Model.init attributes of the JavaScript models, typed like the migration columns.
+    fn to_sequelize_attributes(&self, dialect: &str, field_docs: bool) -> String {
+        self.fields
+            .iter()
+            .map(|field| {
+                let attribute = match field.is_virtual() {
+                    true => format!(
+                        "\t\t\t{}: {{\n\t\t\t\ttype: {},\n\t\t\t\tget() {{\n{}\n\t\t\t\t}},\n\t\t\t}},",
+                        field.name,
+                        field.virtual_type("DataTypes"),
+                        field.virtual_getter("\t\t\t\t\t")
+                    ),
+                    false => match field.enum_name() {
+                        Some(enum_name) => migration_column(field, &field.name, dialect)
+                            .replace(&format!("type: {}", migration_type(field, dialect)), &format!("type: DataTypes.ENUM(...{enum_name})")),
+                        None if !field.accessors("").is_empty() => {
+                            let column = migration_column(field, &field.name, dialect).replace("type: Sequelize.", "type: DataTypes.");
+                            let column = column.strip_suffix("\n\t\t\t},").unwrap_or(&column);
+                            format!("{column}\n{},\n\t\t\t}},", field.accessors("\t\t\t\t").join(",\n"))
+                        }
+                        None => migration_column(field, &field.name, dialect).replace("type: Sequelize.", "type: DataTypes."),
+                    },
+                };
+                if field_docs { format!("{}{attribute}", field.to_jsdoc("\t\t\t")) } else { attribute }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Indexes in field order, named ones merged across the fields that share the name.
+    fn indexes(&self) -> Vec<Index> {
+        let mut indexes: Vec<Index> = Vec::new();
+        for field in &self.fields {
+            for attribute in field.attr.iter().filter(|attribute| attr_name(attribute) == "@Index") {
+                let Ok(index) = Index::parse(attribute) else { continue };
+                match indexes.iter_mut().find(|other| other.name.is_some() && other.name == index.name) {
+                    Some(other) => {
+                        other.fields.push(field.name.clone());
+                        other.unique = other.unique.or(index.unique);
+                        other.using = other.using.take().or(index.using);
+                    }
+                    None => indexes.push(Index { fields: vec![field.name.clone()], ..index }),
+                }
             }
-            if subdir == "utils" {
-                implement_utils(current_dir.clone(), properties.clone())?;
+        }
+        indexes
+    }
+
+    fn to_table_indexes(&self) -> String {
+        let indexes = self.indexes().iter().map(Index::to_table_option).collect::<Vec<_>>().join(", ");
+        format!("indexes: [{indexes}]")
+    }
+
+    // {NAME}Field, the literal names of the fields, and {NAME}QueryOptions, the options findAll() takes.
+    fn to_query_options(&self) -> String {
+        let fields = self.fields.iter().map(|field| format!("\"{}\"", field.name)).collect::<Vec<_>>().join(" | ");
+        match output_style().is_js() {
+            true => format!(
+                "/** @typedef {{{fields}}} {0}Field */\n\n/**\n * @typedef {{object}} {0}QueryOptions\n * @property {{Partial<{0}Attributes>}} [where]\n * @property {{Array<[{0}Field, \"ASC\" | \"DESC\"]>}} [order]\n * @property {{{0}Field[]}} [attributes]\n * @property {{number}} [limit]\n * @property {{number}} [offset]\n */",
+                self.name
+            ),
+            false => format!(
+                "export type {0}Field = {fields};\n\nexport type {0}QueryOptions = {{\n\twhere?: Partial<{0}Attributes>;\n\torder?: [keyof {0}Attributes, \"ASC\" | \"DESC\"][];\n\tattributes?: {0}Field[];\n\tlimit?: number;\n\toffset?: number;\n}};",
+                self.name
+            ),
+        }
+    }
+
+    fn to_shape_declarations(&self) -> String {
+        let mut declarations = Vec::new();
+        for (name, shape) in self.fields.iter().filter_map(|field| field.shape.as_ref()) {
+            shape.declare(name, &mut declarations);
+        }
+        for spatial in self.fields.iter().filter_map(|field| field.spatial.as_ref()) {
+            let declaration = spatial.declaration();
+            if !declarations.contains(&declaration) {
+                declarations.push(declaration);
             }
-            if subdir == "use_cases" {
-                implement_use_case(current_dir.clone(), properties.clone())?;
+        }
+        declarations.join("\n\n")
+    }
+
+    fn to_prisma_block(&self) -> String {
+        let fields = self.fields
+            .iter()
+            .filter(|field| !field.is_virtual())
+            .map(|field| format!("  {}", field.to_prisma_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("model {} {{\n{fields}\n}}", self.name)
+    }
+
+    // The enum blocks of the Prisma enum fields, each enum once.
+    fn to_prisma_enums(&self) -> Vec<(&str, String)> {
+        let mut blocks: Vec<(&str, String)> = Vec::new();
+        for (enum_name, values) in self.fields.iter().filter(|field| !field.is_virtual()).filter_map(Field::prisma_enum) {
+            if !blocks.iter().any(|(name, _)| *name == enum_name) {
+                let values: String = values.iter().map(|value| format!("  {value}\n")).collect();
+                blocks.push((enum_name, format!("enum {enum_name} {{\n{values}}}")));
             }
-            if subdir == "repositories" {
-                implement_repository(current_dir.clone())?;
+        }
+        blocks
+    }
+
+    fn to_graphql_type(&self) -> String {
+        let mut notes = String::new();
+        if self.fields.iter().any(|field| ["DATE", "DATEONLY", "TIME"].contains(&field.db_type.as_str())) {
+            notes.push_str("  # Date and time columns are exposed as String, import a DateTime scalar to type them precisely\n");
+        }
+        if self.fields.iter().any(|field| field.db_type == "JSON") {
+            notes.push_str("  # JSON columns are exposed as serialized String, import a JSON scalar to type them precisely\n");
+        }
+        if self.fields.iter().any(|field| field.spatial.is_some()) {
+            notes.push_str("  # GEOMETRY and GEOGRAPHY columns are exposed as serialized GeoJSON String, import a JSON scalar to type them precisely\n");
+        }
+        if self.fields.iter().any(Field::is_bigint) {
+            notes.push_str("  # bigint columns are exposed as String, Int only holds 32 bits\n");
+        }
+
+        let fields = self.fields
+            .iter()
+            .map(|field| {
+                let required = if field.has_attr("@AllowNull") { "" } else { "!" };
+                let graphql_type = if field.is_bigint() { "String" } else { graphql_type(field.value_type()) };
+                format!("    {}: {graphql_type}{required}", field.name)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!("{notes}  type {} {{\n{fields}\n  }}", self.name)
+    }
+
+    fn to_zod_schema(&self, dialect: &str) -> String {
+        self.fields
+            .iter()
+            .filter(|field| !field.is_generated_key(dialect))
+            .filter(|field| field.db_type != "FILE" && !field.is_virtual() && !self.is_tenant(field))
+            .map(|field| {
+                let mut schema = match (&field.shape, field.enum_name()) {
+                    (Some((_, shape)), _) => shape.to_zod(),
+                    (None, Some(enum_name)) => format!("z.enum({enum_name})"),
+                    // Clients send bigint values as strings, numbers are taken too.
+                    (None, None) if field.is_bigint() => {
+                        r"z.union([z.string().regex(/^-?\d+$/), z.number().int()]).transform((value) => BigInt(value))".to_string()
+                    }
+                    (None, None) if field.is_bigint_string() => r"z.string().regex(/^-?\d+$/)".to_string(),
+                    (None, None) => match &field.spatial {
+                        Some(spatial) => spatial.to_zod(),
+                        None => zod_type(&field.db_type).to_string(),
+                    },
+                };
+                // Lowercased like the model setter does, so the checks see the stored value.
+                if field.has_attr("@UniqueInsensitive") {
+                    schema.push_str(".toLowerCase()");
+                }
+                if let Some(length) = field.attr.iter().find(|attribute| attr_name(attribute) == "@Length").and_then(|attribute| attr_args(attribute)) {
+                    match length.split_once(',') {
+                        Some((min, max)) => schema.push_str(&format!(".min({}).max({})", min.trim(), max.trim())),
+                        None => schema.push_str(&format!(".max({length})")),
+                    }
+                }
+                if field.has_attr("@AllowNull") {
+                    schema.push_str(".nullable()");
+                }
+                if field.has_attr("@Default") {
+                    schema.push_str(".optional()");
+                }
+                format!("\t{}: {schema},", field.name)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn check_dialect(&self, dialect: &str) -> Result<(), String> {
+        let unsupported = DIALECT_UNSUPPORTED_TYPES
+            .iter()
+            .find(|(name, _)| *name == dialect)
+            .map_or(&[][..], |(_, types)| *types);
+
+        for field in &self.fields {
+            if unsupported.contains(&field.db_type.as_str()) {
+                return Err(format!("Field {} uses {} which the {dialect} dialect does not support", field.name, field.db_type));
             }
-            if subdir == "controllers" {
-                implement_controllers(current_dir.clone(), properties.clone())?;
+        }
+
+        Ok(())
+    }
+
+    fn example_body(&self) -> Value {
+        let body: serde_json::Map<String, Value> = self.fields
+            .iter()
+            .filter(|field| self.is_supplied(field) && field.db_type != "FILE" && !field.is_virtual())
+            .map(|field| (field.name.clone(), field.example_value()))
+            .collect();
+        Value::Object(body)
+    }
+
+    // Object schema of the attributes, every property optional for the partial payload of an update.
+    fn to_json_schema(&self, partial: bool) -> Value {
+        let fields: Vec<&Field> = self.fields.iter().filter(|field| !field.is_virtual()).collect();
+        let properties: serde_json::Map<String, Value> = fields.iter().map(|field| (field.name.clone(), field.to_json_schema())).collect();
+        let mut schema = json!({ "type": "object", "properties": properties });
+        let required: Vec<&str> = fields.iter().filter(|field| !field.has_attr("@AllowNull")).map(|field| field.name.as_str()).collect();
+        if !partial && !required.is_empty() {
+            schema["required"] = json!(required);
+        }
+        schema
+    }
+
+    fn table_name(&self) -> &str {
+        self.table.table_name.as_deref().unwrap_or(&self.name_plural)
+    }
+
+    // Table argument of the queryInterface calls, an object when the table lives in a schema.
+    fn migration_table(&self) -> String {
+        match &self.table.schema {
+            Some(schema) => format!("{{ tableName: \"{}\", schema: \"{schema}\" }}", self.table_name()),
+            None => format!("\"{}\"", self.table_name()),
+        }
+    }
+
+    fn column_name(&self, field_name: &str) -> String {
+        if self.table.underscored { snake_case(field_name) } else { field_name.to_string() }
+    }
+
+    fn timestamps(&self) -> bool {
+        self.table.timestamps != Some(false)
+    }
+
+    // Entries of the table options the schema sets besides the table name.
+    fn table_options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(schema) = &self.table.schema {
+            options.push(format!("schema: \"{schema}\""));
+        }
+        if self.table.underscored {
+            options.push("underscored: true".to_string());
+        }
+        if self.table.paranoid {
+            options.push("paranoid: true".to_string());
+        }
+        if let Some(timestamps) = self.table.timestamps {
+            options.push(format!("timestamps: {timestamps}"));
+        }
+        options
+    }
+
+    fn check_table_options(&self) -> Result<(), String> {
+        let timestamp_attributes = ["@CreatedAt", "@UpdatedAt", "@DeletedAt"];
+        if !self.timestamps() {
+            if let Some(field) = self.fields.iter().find(|field| timestamp_attributes.iter().any(|attribute| field.has_attr(attribute))) {
+                return Err(format!("Field {} is a timestamp, but timestamps is false", field.name));
             }
-            if subdir == "routes" {
-                implement_routes(current_dir.clone())?;
+            if self.table.paranoid {
+                return Err("paranoid needs timestamps to record deletedAt".to_string());
             }
-            if subdir == "config" {
-                update_sequelize(current_dir.clone())?;
+        }
+        if self.table.paranoid {
+            if let Some(field) = self.fields.iter().find(|field| field.has_attr("@DeletedAt") && field.db_type != "DATE") {
+                return Err(format!("Field {} is the paranoid deletedAt, which has to be a DATE", field.name));
             }
         }
+        Ok(())
     }
 
-    Ok(())
+    fn primary_key(&self) -> &Field {
+        self.fields.iter().find(|field| field.has_attr("@PrimaryKey")).expect("Model is validated to have a primary key")
+    }
+
+    fn primary_keys(&self) -> Vec<&Field> {
+        self.fields.iter().filter(|field| field.has_attr("@PrimaryKey")).collect()
+    }
+
+    // The route params of the key, one segment per field of a composite key.
+    fn key_params(&self) -> String {
+        self.primary_keys().iter().map(|field| format!("/:{}", field.name)).collect()
+    }
+
+    // The key as the controllers parse it and the Get, Update and Delete requests carry it.
+    fn key_type(&self) -> String {
+        let fields: Vec<String> = self.primary_keys().iter().map(|field| format!("{}: {}", field.name, field.js_type)).collect();
+        format!("{{ {} }}", fields.join("; "))
+    }
+
+    fn has_composite_key(&self) -> bool {
+        self.primary_keys().len() > 1
+    }
+
+    // Whether the create request carries the field, a composite key is made of client supplied values.
+    fn is_supplied(&self, field: &Field) -> bool {
+        (!field.has_attr("@PrimaryKey") || self.has_composite_key()) && !self.is_tenant(field)
+    }
+
+    // The --tenant-field of a scoped model, set from the request context and never by the client.
+    fn tenant(&self) -> Option<&Field> {
+        self.tenant_field.as_ref().and_then(|name| self.fields.iter().find(|field| &field.name == name))
+    }
+
+    fn is_tenant(&self, field: &Field) -> bool {
+        self.tenant_field.as_ref() == Some(&field.name)
+    }
+
+    // A join or tag table, nothing but the key for a create or update to set.
+    fn is_empty(&self) -> bool {
+        !self.fields.iter().any(|field| self.is_supplied(field) && !field.is_virtual())
+    }
+
+    // --composite-pk moves the key onto the named fields, the former key stays a plain column
+    // and no key column increments on its own.
+    fn set_composite_key(&mut self, key: &[String]) -> Result<(), String> {
+        for field in self.fields.iter().filter(|field| key.contains(&field.name)) {
+            if field.is_virtual() || field.db_type == "FILE" {
+                return Err(format!("Field {} of {} has no stored column to be part of the primary key", field.name, self.name));
+            }
+        }
+        for field in &mut self.fields {
+            let was_key = field.has_attr("@PrimaryKey");
+            let keyed = key.contains(&field.name);
+            field.attr.retain(|attribute| match attr_name(attribute) {
+                "@PrimaryKey" => false,
+                "@AutoIncrement" => !was_key && !keyed,
+                _ => true,
+            });
+            if keyed {
+                field.attr.insert(0, "@PrimaryKey".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // The unique indexes on LOWER(column) of the @UniqueInsensitive fields.
+    fn insensitive_unique_indexes(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|field| field.has_attr("@UniqueInsensitive"))
+            .map(|field| {
+                let column = self.column_name(&field.name);
+                format!(
+                    "\t\tawait queryInterface.addIndex({}, [Sequelize.fn(\"lower\", Sequelize.col(\"{column}\"))], {{ unique: true, name: \"{}_{}_lower_unique\" }});",
+                    self.migration_table(),
+                    snake_case(self.table_name()),
+                    snake_case(&column)
+                )
+            })
+            .collect()
+    }
+
+    fn composite_key_constraint(&self) -> String {
+        let columns = self.primary_keys().iter().map(|field| format!("\"{}\"", self.column_name(&field.name))).collect::<Vec<_>>().join(", ");
+        format!(
+            "\t\tawait queryInterface.addConstraint({}, {{ fields: [{columns}], type: \"primary key\", name: \"{}_pkey\" }});",
+            self.migration_table(),
+            self.table_name()
+        )
+    }
+
+    fn file_fields(&self) -> Vec<&Field> {
+        self.fields.iter().filter(|field| field.db_type == "FILE").collect()
+    }
+
+    // Classes named in the relation decorators, `@BelongsTo(() => User)` refers to User.
+    fn relation_targets(&self) -> Vec<String> {
+        let target = Regex::new(r"\(\s*\(\)\s*=>\s*(\w+)").unwrap();
+        let mut targets: Vec<String> = Vec::new();
+        for attribute in self.fields.iter().flat_map(|field| &field.attr) {
+            if !["@BelongsTo", "@HasMany", "@HasOne", "@ForeignKey"].contains(&attr_name(attribute)) {
+                continue;
+            }
+            for captures in target.captures_iter(attribute) {
+                if !targets.iter().any(|other| *other == captures[1]) {
+                    targets.push(captures[1].to_string());
+                }
+            }
+        }
+        targets
+    }
+
+    // Other schema models named in the relation decorators.
+    fn related_models(&self) -> Vec<&'static Model> {
+        self.relation_targets()
+            .iter()
+            .filter_map(|target| SCHEMA_MODELS.get().into_iter().flatten().find(|model| model.name == *target && model.name != self.name))
+            .collect()
+    }
+
+    fn enum_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for enum_name in self.fields.iter().filter_map(Field::enum_name) {
+            if !names.contains(&enum_name) {
+                names.push(enum_name);
+            }
+        }
+        names
+    }
+
+    fn has_relations(&self) -> bool {
+        self.fields.iter().any(|field| field.has_attr("@HasMany") || field.has_attr("@HasOne"))
+    }
+
+    fn has_bigint(&self) -> bool {
+        self.fields.iter().any(Field::is_bigint)
+    }
+
+    fn has_encrypted(&self) -> bool {
+        self.fields.iter().any(|field| field.transform.as_deref() == Some("encrypt"))
+    }
+}
+
+const NAME: &str = "Example_model_name";
+const NAME_PLURAL: &str = "Example_model_name_plural";
+
+const HEALTH_ROUTES_TEMPLATE: &str = include_str!("../templates/health_routes.txt");
+const TSCONFIG_TEMPLATE: &str = include_str!("../templates/tsconfig.txt");
+const PACKAGE_JSON_TEMPLATE: &str = include_str!("../templates/package_json.txt");
+const UNIT_OF_WORK_TEMPLATE: &str = include_str!("../templates/unit_of_work.txt");
+const API_RESPONSE_TEMPLATE: &str = include_str!("../templates/api_response.txt");
+const BIGINT_TEMPLATE: &str = include_str!("../templates/bigint.txt");
+const CRYPTO_TEMPLATE: &str = include_str!("../templates/crypto.txt");
+const FACTORY_TEMPLATE: &str = include_str!("../templates/factory.txt");
+const USE_CASE_TEST_TEMPLATE: &str = include_str!("../templates/use_case_test.txt");
+const AGGREGATE_TEMPLATE: &str = include_str!("../templates/aggregate.txt");
+const EVENT_STORE_TEMPLATE: &str = include_str!("../templates/event_store.txt");
+const PROJECTION_TEMPLATE: &str = include_str!("../templates/projection.txt");
+const KAFKA_CONSUMER_TEMPLATE: &str = include_str!("../templates/kafka_consumer.txt");
+const SERVICE_TEMPLATE: &str = include_str!("../templates/service.txt");
+const SERVICE_CONTROLLERS_TEMPLATE: &str = include_str!("../templates/service_controllers.txt");
+const ABSTRACT_REPOSITORY_TEMPLATE: &str = include_str!("../templates/abstract_repository.txt");
+const ABSTRACT_USE_CASE_TEMPLATE: &str = include_str!("../templates/abstract_use_case.txt");
+const CACHED_REPOSITORY_TEMPLATE: &str = include_str!("../templates/cached_repository.txt");
+const REDIS_TEMPLATE: &str = include_str!("../templates/redis.txt");
+const PRISMA_SCHEMA_HEADER: &str = "generator client {\n  provider = \"prisma-client-js\"\n}\n\ndatasource db {\n  provider = \"postgresql\"\n  url      = env(\"DATABASE_URL\")\n}\n";
+const CONTAINER_TSYRINGE_TEMPLATE: &str = include_str!("../templates/container_tsyringe.txt");
+const CONTAINER_INVERSIFY_TEMPLATE: &str = include_str!("../templates/container_inversify.txt");
+const INVERSIFY_BINDINGS_TEMPLATE: &str = include_str!("../templates/inversify_bindings.txt");
+const TSYRINGE_DI_TEMPLATE: &str = include_str!("../templates/tsyringe_di.txt");
+const DI_LIBRARIES: &[&str] = &["tsyringe", "inversify", "none"];
+const GRAPHQL_TYPEDEFS_TEMPLATE: &str = include_str!("../templates/graphql_typedefs.txt");
+const FEDERATION_ENTITY_TEMPLATE: &str = include_str!("../templates/federation_entity.txt");
+const FEDERATION_RESOLVER_TEMPLATE: &str = include_str!("../templates/federation_resolver.txt");
+const EVENTS_TEMPLATE: &str = include_str!("../templates/events.txt");
+const EVENT_BUS_TEMPLATE: &str = include_str!("../templates/event_bus.txt");
+const ZOD_SCHEMA_TEMPLATE: &str = include_str!("../templates/zod_schema.txt");
+const VALIDATION_LIBRARIES: &[&str] = &["zod"];
+const ENTITY_TEMPLATE: &str = include_str!("../templates/entity.txt");
+const ENTITY_MAPPER_TEMPLATE: &str = include_str!("../templates/entity_mapper.txt");
+const UPLOAD_MIDDLEWARE_TEMPLATE: &str = include_str!("../templates/upload.txt");
+const REQUEST_LOGGER_PINO_TEMPLATE: &str = include_str!("../templates/request_logger_pino.txt");
+const REQUEST_LOGGER_MORGAN_TEMPLATE: &str = include_str!("../templates/request_logger_morgan.txt");
+const TENANT_CONTEXT_TEMPLATE: &str = include_str!("../templates/tenant_context.txt");
+const LOGGERS: &[&str] = &["pino", "morgan"];
+const SEQUELIZE_TEMPLATE: &str = include_str!("../templates/sequelize.txt");
+const MIGRATION_TEMPLATE: &str = include_str!("../templates/migration.txt");
+const ENUM_MIGRATION_TEMPLATE: &str = include_str!("../templates/enum_migration.txt");
+const EXTENSION_MIGRATION_TEMPLATE: &str = include_str!("../templates/extension_migration.txt");
+const DATABASE_CONFIG_TEMPLATE: &str = include_str!("../templates/database.txt");
+const DOCKERFILE_TEMPLATE: &str = include_str!("../templates/dockerfile.txt");
+const SEEDER_TEMPLATE: &str = include_str!("../templates/seeder.txt");
+const MODEL_JS_TEMPLATE: &str = include_str!("../templates/model_js.txt");
+const HEALTH_ROUTES_JS_TEMPLATE: &str = include_str!("../templates/health_routes_js.txt");
+const UNIT_OF_WORK_JS_TEMPLATE: &str = include_str!("../templates/unit_of_work_js.txt");
+const API_RESPONSE_JS_TEMPLATE: &str = include_str!("../templates/api_response_js.txt");
+const BIGINT_JS_TEMPLATE: &str = include_str!("../templates/bigint_js.txt");
+const CRYPTO_JS_TEMPLATE: &str = include_str!("../templates/crypto_js.txt");
+const FACTORY_JS_TEMPLATE: &str = include_str!("../templates/factory_js.txt");
+const USE_CASE_TEST_JS_TEMPLATE: &str = include_str!("../templates/use_case_test_js.txt");
+const AGGREGATE_JS_TEMPLATE: &str = include_str!("../templates/aggregate_js.txt");
+const EVENT_STORE_JS_TEMPLATE: &str = include_str!("../templates/event_store_js.txt");
+const PROJECTION_JS_TEMPLATE: &str = include_str!("../templates/projection_js.txt");
+const KAFKA_CONSUMER_JS_TEMPLATE: &str = include_str!("../templates/kafka_consumer_js.txt");
+const SERVICE_JS_TEMPLATE: &str = include_str!("../templates/service_js.txt");
+const SERVICE_CONTROLLERS_JS_TEMPLATE: &str = include_str!("../templates/service_controllers_js.txt");
+const FEDERATION_RESOLVER_JS_TEMPLATE: &str = include_str!("../templates/federation_resolver_js.txt");
+const ABSTRACT_REPOSITORY_JS_TEMPLATE: &str = include_str!("../templates/abstract_repository_js.txt");
+const ABSTRACT_USE_CASE_JS_TEMPLATE: &str = include_str!("../templates/abstract_use_case_js.txt");
+const CACHED_REPOSITORY_JS_TEMPLATE: &str = include_str!("../templates/cached_repository_js.txt");
+const EVENTS_JS_TEMPLATE: &str = include_str!("../templates/events_js.txt");
+const EVENT_BUS_JS_TEMPLATE: &str = include_str!("../templates/event_bus_js.txt");
+const ZOD_SCHEMA_JS_TEMPLATE: &str = include_str!("../templates/zod_schema_js.txt");
+const SEQUELIZE_JS_TEMPLATE: &str = include_str!("../templates/sequelize_js.txt");
+const DATABASE_CONFIG_JS_TEMPLATE: &str = include_str!("../templates/database_js.txt");
+const ENTITY_JS_TEMPLATE: &str = include_str!("../templates/entity_js.txt");
+const ENTITY_MAPPER_JS_TEMPLATE: &str = include_str!("../templates/entity_mapper_js.txt");
+const REQUEST_LOGGER_PINO_JS_TEMPLATE: &str = include_str!("../templates/request_logger_pino_js.txt");
+const REQUEST_LOGGER_MORGAN_JS_TEMPLATE: &str = include_str!("../templates/request_logger_morgan_js.txt");
+const TENANT_CONTEXT_JS_TEMPLATE: &str = include_str!("../templates/tenant_context_js.txt");
+const LANGUAGES: &[&str] = &["ts", "js"];
+const MODULE_SYSTEMS: &[&str] = &["esm", "cjs", "amd"];
+// --target-lang names the module systems by their ECMAScript target.
+const TARGET_LANGS: &[(&str, &str)] = &[("es2015", "esm"), ("commonjs", "cjs"), ("amd", "amd")];
+const INDEX_STYLES: &[&str] = &["decorator", "table"];
+// --style clean is the interface, repository and use case layers, minimal one service per model.
+const ARCHITECTURES: &[&str] = &["clean", "minimal"];
+const GLOBAL_CONFIG_FILE: &str = "config.toml";
+const CONFIG_FILE: &str = "crudify.toml";
+const POSTMAN_SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+const POSTMAN_SUFFIX: &str = ".postman_collection.json";
+const BINDINGS_SUFFIX: &str = "Bindings.ts";
+const INSOMNIA_SUFFIX: &str = ".insomnia.yaml";
+const ASYNCAPI_SUFFIX: &str = ".asyncapi.yaml";
+const ASYNCAPI_VERSION: &str = "2.6.0";
+const ORMS: &[&str] = &["sequelize", "prisma"];
+const DECORATOR_STYLES: &[&str] = &["experimental", "stage3"];
+const SCHEMA_FORMATS: &[&str] = &["json", "yaml"];
+// A schema piped in larger than this is taken for a mistake, such as the wrong file.
+const MAX_STDIN_SCHEMA: u64 = 16 * 1024 * 1024;
+// --api-versioning url puts the version in the route paths, header and query read it from the request.
+const VERSIONING_STRATEGIES: &[&str] = &["url", "header", "query"];
+const MANIFEST_FILE: &str = ".crudify/manifest.json";
+const HASH_MARKER: &str = "crudify-hash: ";
+
+#[derive(Debug, Clone, Default)]
+struct DockerSettings {
+    port: i64,
+    user: String,
+    password: String,
+    database: String
+}
+
+impl DockerSettings {
+    fn from_config(config: &toml::Table, dialect: &str) -> Self {
+        let docker = config.get("docker").and_then(toml::Value::as_table);
+        let setting = |key: &str| docker.and_then(|table| table.get(key));
+        let default_user = if dialect == "mssql" { "sa" } else { "crudify" };
+
+        Self {
+            port: setting("port").and_then(toml::Value::as_integer).unwrap_or(database_port(dialect)),
+            user: setting("user").and_then(toml::Value::as_str).unwrap_or(default_user).to_string(),
+            password: setting("password").and_then(toml::Value::as_str).unwrap_or("Crudify_password1").to_string(),
+            database: setting("database").and_then(toml::Value::as_str).unwrap_or("crudify").to_string(),
+        }
+    }
+}
+
+// Which fields the --domain-entities entities carry, set in the [entities] table of crudify.toml.
+#[derive(Debug, Clone)]
+struct EntitySettings {
+    hidden: bool,
+    computed: bool
+}
+
+impl EntitySettings {
+    fn from_config(config: &toml::Table) -> Self {
+        let entities = config.get("entities").and_then(toml::Value::as_table);
+        let setting = |key: &str| entities.and_then(|table| table.get(key)).and_then(toml::Value::as_bool);
+
+        Self {
+            hidden: setting("hidden").unwrap_or(true),
+            computed: setting("virtual").unwrap_or(true),
+        }
+    }
+
+    fn includes(&self, field: &Field) -> bool {
+        (self.hidden || !field.has_attr("@Hidden")) && (self.computed || !field.is_virtual())
+    }
+}
+
+const DEFAULT_GROUP_LAYOUT: &str = "{group}/{layer}";
+
+// Layer and directory of each model artifact other modules import.
+const MODEL_ARTIFACT_DIRS: &[(&str, &str, &str)] = &[
+    ("model", "infrastructure", "models"),
+    ("interface", "core", "interfaces"),
+    ("repository", "infrastructure", "repositories"),
+    ("cached_repository", "infrastructure", "repositories"),
+    ("routes", "infrastructure", "routes"),
+    ("controllers", "presentation", "controllers"),
+    ("events", "core", "events"),
+    ("typedefs", "presentation", "graphql"),
+    ("resolvers", "presentation", "graphql"),
+];
+
+// Layer directories of model_directories --only and --skip pick from, routes names both routes directories.
+const LAYERS: &[&str] = &[
+    "models", "repositories", "interfaces", "use_cases", "services", "utils", "events", "entities", "aggregates", "controllers",
+    "routes", "graphql", "middleware", "config", "prisma", "migrations", "mappers", "event_store", "postman", "insomnia",
+    "asyncapi", "factories",
+];
+
+// Layer directories every model shares, grouped models included.
+const SHARED_LAYER_DIRS: &[(&str, &str)] = &[
+    ("infrastructure", "config"),
+    ("infrastructure", "prisma"),
+    ("infrastructure", "migrations"),
+    ("presentation", "routes"),
+    ("presentation", "middleware"),
+];
+
+// Maps the generated layers onto workspace packages for --monorepo-packages,
+// and the models with a `group` onto their own directories.
+#[derive(Debug, Clone, Default)]
+struct OutputLayout {
+    packages: Vec<(String, PathBuf)>,
+    group_pattern: String
+}
+
+impl OutputLayout {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut packages = Vec::new();
+        for entry in spec.split(',').filter(|entry| !entry.trim().is_empty()) {
+            let (layer, package) = entry.split_once('=').ok_or(format!("--monorepo-packages expects layer=path, got {entry}"))?;
+            let layer = match layer.trim() {
+                "core" => "core",
+                "infra" | "infrastructure" => "infrastructure",
+                "api" | "presentation" => "presentation",
+                other => return Err(format!("Unknown layer: {other}, expected core, infra or api")),
+            };
+            packages.push((layer.to_string(), PathBuf::from(package.trim())));
+        }
+
+        Ok(Self { packages, ..Self::default() })
+    }
+
+    fn layer_dir(&self, root: &Path, layer: &str) -> PathBuf {
+        match self.packages.iter().find(|(name, _)| name == layer) {
+            Some((_, package)) => root.join(package),
+            None => root.join(layer),
+        }
+    }
+
+    fn group_dir(&self, group: &str, layer: &str) -> String {
+        let pattern = if self.group_pattern.is_empty() { DEFAULT_GROUP_LAYOUT } else { &self.group_pattern };
+        pattern.replace("{group}", group).replace("{layer}", layer)
+    }
+
+    // Layer directory of the model's own artifacts, nested under its group when it has one.
+    fn model_layer_dir(&self, root: &Path, layer: &str, model: &Model) -> PathBuf {
+        match &model.group {
+            Some(group) => root.join(self.group_dir(group, layer)),
+            None => self.layer_dir(root, layer),
+        }
+    }
+
+    // The layer aliases of grouped artifacts become @group/layer/, `@billing/core/*` maps to `src/billing/core/*`.
+    fn group_aliases(&self, models: &[Model]) -> Vec<(String, String)> {
+        let mut aliases = Vec::new();
+        for model in models {
+            let Some(group) = &model.group else { continue };
+            for directory in ["use_cases", "utils"] {
+                aliases.push((format!("@core/{directory}/{}/", model.name), format!("@{group}/core/{directory}/{}/", model.name)));
+            }
+            aliases.push((format!("@core/entities/{}\"", model.name), format!("@{group}/core/entities/{}\"", model.name)));
+            aliases.push((
+                format!("@core/services/{}Service\"", model.name.to_lowercase()),
+                format!("@{group}/core/services/{}Service\"", model.name.to_lowercase()),
+            ));
+            aliases.push((
+                format!("@core/interfaces/Abstract{}Repository\"", model.name),
+                format!("@{group}/core/interfaces/Abstract{}Repository\"", model.name),
+            ));
+            aliases.push((format!("@core/aggregates/{}Aggregate\"", model.name), format!("@{group}/core/aggregates/{}Aggregate\"", model.name)));
+            aliases.push((
+                format!("@infrastructure/event_store/{}EventStore\"", model.name),
+                format!("@{group}/infrastructure/event_store/{}EventStore\"", model.name),
+            ));
+            aliases.push((
+                format!("@infrastructure/event_store/{}ProjectionRepository\"", model.name),
+                format!("@{group}/infrastructure/event_store/{}ProjectionRepository\"", model.name),
+            ));
+            aliases.push((
+                format!("@infrastructure/mappers/{}Mapper\"", model.name.to_lowercase()),
+                format!("@{group}/infrastructure/mappers/{}Mapper\"", model.name.to_lowercase()),
+            ));
+            for (artifact, layer, directory) in MODEL_ARTIFACT_DIRS {
+                let module = naming().module(artifact, model);
+                aliases.push((format!("@{layer}/{directory}/{module}\""), format!("@{group}/{layer}/{directory}/{module}\"")));
+            }
+        }
+        aliases
+    }
+
+    // Package names come from each package.json, falling back to the directory name.
+    fn import_aliases(&self, root: &Path) -> Vec<(String, String)> {
+        self.packages
+            .iter()
+            .map(|(layer, package)| {
+                let manifest: Option<Value> = fs::read_to_string(root.join(package).join("package.json"))
+                    .ok()
+                    .and_then(|content| serde_json::from_str(&content).ok());
+                let name = manifest
+                    .as_ref()
+                    .and_then(|manifest| manifest["name"].as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| package.file_name().map_or_else(|| layer.clone(), |name| name.to_string_lossy().into_owned()));
+
+                (format!("@{layer}/"), format!("{name}/"))
+            })
+            .collect()
+    }
+}
+
+// Language and module system of the generated sources, set by --lang and --module.
+#[derive(Debug, Clone)]
+struct OutputStyle {
+    lang: String,
+    module: String
+}
+
+impl Default for OutputStyle {
+    fn default() -> Self {
+        Self { lang: "ts".to_string(), module: "esm".to_string() }
+    }
+}
+
+impl OutputStyle {
+    fn is_js(&self) -> bool {
+        self.lang == "js"
+    }
+
+    fn is_commonjs(&self) -> bool {
+        self.module == "cjs"
+    }
+
+    fn is_amd(&self) -> bool {
+        self.module == "amd"
+    }
+
+    // Sources are planned as .ts and land as .js for JavaScript output.
+    fn source_path(&self, path: &Path) -> PathBuf {
+        if self.is_js() && path.extension().is_some_and(|extension| extension == "ts") {
+            path.with_extension("js")
+        } else {
+            path.to_path_buf()
+        }
+    }
+}
+
+// Flags of generate, check, apply and stats, Options::from_args turns them into the options of a run.
+#[derive(Args, Debug, Clone, Default)]
+struct GenerateArgs {
+    #[arg(long = "config", visible_alias = "schema", value_name = "schema|-", conflicts_with = "schema_dir", help = "YAML or JSON schema with the models, - reads it from stdin, the example model otherwise")]
+    schema: Option<String>,
+    #[arg(long, value_name = "dir", help = "Read the models from every .yaml, .yml and .json schema of the directory in one run")]
+    schema_dir: Option<String>,
+    #[arg(long, requires = "schema_dir", help = "Read the schemas of the --schema-dir subdirectories too")]
+    recursive: bool,
+    #[arg(long, requires = "schema_dir", help = "Generate the valid --schema-dir files and list the failing ones, exiting with 1")]
+    continue_on_error: bool,
+    #[arg(long, help = "Add no id key to the schema models without a @PrimaryKey and never fall back on the example model, every model declares its key")]
+    no_id: bool,
+    #[arg(long, value_parser = PossibleValuesParser::new(SCHEMA_FORMATS), help = "Parse the schema as this format instead of telling it from the content")]
+    schema_format: Option<String>,
+    #[arg(long, value_name = "file|-", requires = "schema", help = "Write the schema the models are read from as JSON, - for stdout")]
+    emit_schema: Option<String>,
+    #[arg(long, help = "Turn schema warnings into errors")]
+    strict: bool,
+    #[arg(long, help = "Warn instead of stopping when a template lacks a placeholder an enabled feature needs")]
+    lenient: bool,
+    #[arg(long, help = "Regenerate under a changed [naming] convention")]
+    force: bool,
+    #[arg(long, help = "Print the run report as JSON instead of the summary")]
+    json: bool,
+    #[arg(long, value_name = "n", help = "Models generated at once")]
+    jobs: Option<usize>,
+    #[arg(long, value_name = "secs", help = "How long to wait for another run holding .crudify/lock")]
+    lock_timeout: Option<u64>,
+    #[arg(long, help = "Read every template from disk each time it is used")]
+    no_cache: bool,
+    #[arg(long, value_parser = PossibleValuesParser::new(LANGUAGES), help = "Output language, picks the _JS_TEMPLATE variants and .js.tpl local templates")]
+    lang: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(MODULE_SYSTEMS), conflicts_with = "target_lang", help = "Module system of JavaScript output")]
+    module: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(TARGET_LANGS.iter().map(|(name, _)| *name)), help = "Module system of JavaScript output by its ECMAScript name, es2015 is ESM")]
+    target_lang: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(ORMS), help = "ORM the repositories and models are written for")]
+    orm: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(DIALECTS), help = "Database dialect, fills {DIALECT}")]
+    dialect: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(INDEX_STYLES), help = "Declare indexes as @Index decorators or in {TABLE_INDEXES}")]
+    indexes: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(ARCHITECTURES), help = "Interface, repository and use case layers, or one {name}Service.ts per model")]
+    style: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(DI_LIBRARIES), help = "Dependency injection, fills {DI_IMPORT}, {DI_INJECTABLE}, {DI_INJECT_REPOSITORY} and {DI_CONTAINER_IMPORT}")]
+    di: Option<String>,
+    #[arg(long, value_parser = ["redis"], help = "Cached repositories, fills {REPOSITORY_IMPORT} and {REPOSITORY_INSTANCE} with them")]
+    cache: Option<String>,
+    #[arg(long, help = "Domain events, fills {DYNAMIC_EVENTS_IMPORT} and {DYNAMIC_PUBLISH_EVENT}")]
+    events: bool,
+    #[arg(long, help = "Wrap controller responses in the { data, meta, errors } helpers")]
+    envelope: bool,
+    #[arg(long, value_parser = PossibleValuesParser::new(LOGGERS), overrides_with = "no_logging", help = "Request logger middleware, fills {LOG_ERROR}")]
+    logging: Option<String>,
+    #[arg(long, overrides_with = "logging", help = "Turn off a logging set in crudify.toml")]
+    no_logging: bool,
+    #[arg(long, value_parser = PossibleValuesParser::new(VALIDATION_LIBRARIES), help = "Request validation schemas from {ZOD_SCHEMA_BODY}")]
+    validation: Option<String>,
+    #[arg(long, value_name = "prefix", help = "API version prefix, fills {VERSION_PREFIX}")]
+    version_prefix: Option<String>,
+    #[arg(long, value_name = "name", help = "Scope every model to a tenant column the controllers take from req.tenant.id, `tenant: false` opts a model out")]
+    tenant_field: Option<String>,
+    #[arg(long, value_parser = ["INTEGER", "UUID"], ignore_case = true, requires = "tenant_field", help = "Type of the --tenant-field column, INTEGER by default")]
+    tenant_type: Option<String>,
+    #[arg(long, value_parser = PossibleValuesParser::new(VERSIONING_STRATEGIES), help = "Version in the path (/v1 unless --version-prefix), the Accept-Version header or ?version=, fills {VERSION_MIDDLEWARE}")]
+    api_versioning: Option<String>,
+    #[arg(long, value_name = "prefix", help = "Mount prefix the model routers are registered under")]
+    route_prefix: Option<String>,
+    #[arg(long, value_name = "KEY=VALUE", help = "Fill {KEY} in every template, over the [placeholders] of crudify.toml")]
+    set: Vec<String>,
+    #[arg(long, help = "Liveness and readiness routes")]
+    health: bool,
+    #[arg(long, help = "GraphQL type definitions from {DYNAMIC_GRAPHQL_TYPE}")]
+    graphql: bool,
+    #[arg(long, requires = "graphql", help = "Apollo Federation @key on the --graphql types and a __resolveReference resolver")]
+    federation: bool,
+    #[arg(long, help = "Sequelize migrations from {DYNAMIC_MIGRATION_COLUMNS} and {DYNAMIC_MIGRATION_INDEXES}")]
+    migration: bool,
+    #[arg(long, help = "Postman collection per model")]
+    postman: bool,
+    #[arg(long, help = "Insomnia workspace per model")]
+    insomnia: bool,
+    #[arg(long, help = "JSDoc with the @Comment, example and nullability over model and type properties")]
+    field_docs: bool,
+    #[arg(long, help = "Dockerfile and docker-compose service")]
+    docker: bool,
+    #[arg(long, help = "Plain entities in core/entities with mappers, fills {ENTITY_TYPE}, {ENTITY_IMPORT}, {ENTITY_MAPPER_IMPORT}, {TO_ENTITY} and {TO_MODEL}")]
+    domain_entities: bool,
+    #[arg(long, value_name = "layer=path,...", help = "Write the layers to workspace packages")]
+    monorepo_packages: Option<String>,
+    #[arg(long, value_name = "path,...", help = "Register the models in these sequelize.ts files, relative to the output, instead of infrastructure/config/sequelize.ts")]
+    sequelize_files: Option<String>,
+    #[arg(long, value_name = "dir", help = "Directory the relative template paths of .env are read from, the schema's directory by default")]
+    template_root: Option<PathBuf>,
+    #[arg(long, value_name = "dir", help = "Run pre-generate.sh and post-generate.sh from the directory around the generation")]
+    hooks_dir: Option<PathBuf>,
+    #[arg(long, help = "Give files that end up as they were, after --prettier or --eslint-fix, their old mtime back")]
+    touch_changed_only: bool,
+    #[arg(long, help = "Abstract repository and use case classes the generated ones extend")]
+    abstract_base: bool,
+    #[arg(long, value_name = "field1,field2", help = "Make the named fields the primary key of every model that has them")]
+    composite_pk: Option<String>,
+    #[arg(long, help = "Leave out the __tests__/factories fixture builders")]
+    no_factories: bool,
+    #[arg(long, conflicts_with = "no_factories", help = "Jest tests of the use cases in __tests__/use_cases, built with the factories")]
+    tests: bool,
+    #[arg(long, help = "Aggregate root, Sequelize event store and read model projection per model")]
+    event_sourcing: bool,
+    #[arg(long, value_parser = ["kafka"], requires = "event_sourcing", help = "With --event-sourcing, a consumer of the {name}-events topic that feeds the projection")]
+    queue_type: Option<String>,
+    #[arg(long, help = "Generate models without fields besides the key, leaving out their Add, Update, controllers and routes")]
+    allow_empty: bool,
+    #[arg(long, help = "Wrap utils types and entities in namespace {NAME} with /// <reference> lines instead of imports")]
+    namespace_declarations: bool,
+    #[arg(long, help = "Declare the utils object types as interfaces, intersections as extends")]
+    types_as_interface: bool,
+    #[arg(long, help = "tsconfig.json in each layer root with the path aliases of the imports, from TSCONFIG_TEMPLATE")]
+    tsconfig: bool,
+    #[arg(long, help = "package.json in each layer root with the packages the ORM and flags need, from PACKAGE_JSON_TEMPLATE")]
+    package_json: bool,
+    #[arg(long, value_parser = PossibleValuesParser::new(DECORATOR_STYLES), help = "TypeScript decorators the output is written for, stage3 rules out the sequelize-typescript and DI decorators")]
+    decorator_style: Option<String>,
+    #[arg(long, help = "Format the written TypeScript and JavaScript files with npx prettier --write")]
+    prettier: bool,
+    #[arg(long, help = "Fix the written files with npx eslint --fix after --prettier, remaining problems are warnings")]
+    eslint_fix: bool,
+    #[arg(long, value_name = "layer,...", value_delimiter = ',', value_parser = PossibleValuesParser::new(LAYERS), conflicts_with = "skip", help = "Generate only these layer directories of each model, and register the model only in the shared files of those layers")]
+    only: Vec<String>,
+    #[arg(long, value_name = "layer,...", value_delimiter = ',', value_parser = PossibleValuesParser::new(LAYERS), help = "Generate every layer directory of each model but these")]
+    skip: Vec<String>
+}
+
+#[derive(Debug, Clone, Default)]
+struct Options {
+    health: bool,
+    strict: bool,
+    cache: Option<String>,
+    orm: String,
+    di: String,
+    graphql: bool,
+    federation: bool,
+    events: bool,
+    envelope: bool,
+    logging: Option<String>,
+    validation: Option<String>,
+    dialect: String,
+    migration: bool,
+    postman: bool,
+    insomnia: bool,
+    field_docs: bool,
+    version_prefix: String,
+    api_versioning: Option<String>,
+    tenant_field: Option<String>,
+    tenant_type: Option<String>,
+    route_prefix: String,
+    docker: Option<DockerSettings>,
+    entities: Option<EntitySettings>,
+    schema: Option<String>,
+    schema_dir: Option<String>,
+    recursive: bool,
+    continue_on_error: bool,
+    no_id: bool,
+    sequelize_files: Vec<String>,
+    schema_format: Option<String>,
+    emit_schema: Option<String>,
+    layout: OutputLayout,
+    naming: Naming,
+    placeholders: Vec<(String, String)>,
+    style: OutputStyle,
+    jobs: usize,
+    template_cache: bool,
+    index_style: String,
+    architecture: String,
+    hooks_dir: Option<PathBuf>,
+    template_root: Option<PathBuf>,
+    prettier: bool,
+    eslint_fix: bool,
+    touch_changed_only: bool,
+    abstract_base: bool,
+    composite_pk: Vec<String>,
+    factories: bool,
+    tests: bool,
+    event_sourcing: bool,
+    queue_type: Option<String>,
+    namespace_declarations: bool,
+    types_as_interface: bool,
+    decorator_style: Option<String>,
+    tsconfig: bool,
+    package_json: bool,
+    allow_empty: bool,
+    lenient: bool,
+    lock_timeout: Duration,
+    force: bool,
+    only: Vec<String>,
+    skip: Vec<String>
+}
+
+impl Options {
+    fn from_args(args: &GenerateArgs, config: &toml::Table) -> Result<Self, String> {
+        let dialect = config.get("dialect").and_then(toml::Value::as_str).unwrap_or("postgres");
+        let route_prefix = config.get("route_prefix").and_then(toml::Value::as_str).unwrap_or_default();
+        let index_style = config.get("indexes").and_then(toml::Value::as_str).unwrap_or("decorator");
+        let architecture = config.get("style").and_then(toml::Value::as_str).unwrap_or("clean");
+        let group_layout = config.get("group_layout").and_then(toml::Value::as_str).unwrap_or(DEFAULT_GROUP_LAYOUT);
+        let logging = config.get("logging").and_then(toml::Value::as_str).map(str::to_string);
+        let lock_timeout = config.get("lock_timeout").and_then(toml::Value::as_integer).unwrap_or(DEFAULT_LOCK_TIMEOUT);
+        let mut options = Self {
+            orm: args.orm.clone().unwrap_or_else(|| "sequelize".to_string()),
+            di: args.di.clone().unwrap_or_else(|| "none".to_string()),
+            dialect: args.dialect.clone().unwrap_or_else(|| dialect.to_string()),
+            route_prefix: normalize_prefix(args.route_prefix.as_deref().unwrap_or(route_prefix)),
+            naming: Naming::from_config(config)?,
+            placeholders: config_placeholders(config)?,
+            jobs: std::thread::available_parallelism().map_or(1, usize::from),
+            template_cache: !args.no_cache,
+            factories: !args.no_factories,
+            tests: args.tests,
+            index_style: args.indexes.clone().unwrap_or_else(|| index_style.to_string()),
+            architecture: args.style.clone().unwrap_or_else(|| architecture.to_string()),
+            logging: if args.no_logging { None } else { args.logging.clone().or(logging) },
+            types_as_interface: args.types_as_interface || config.get("use_interface").and_then(toml::Value::as_bool).unwrap_or_default(),
+            decorator_style: args.decorator_style.clone().or_else(|| config.get("decorator_style").and_then(toml::Value::as_str).map(str::to_string)),
+            lock_timeout: Duration::from_secs(args.lock_timeout.unwrap_or(lock_timeout.max(0) as u64)),
+            health: args.health,
+            strict: args.strict,
+            lenient: args.lenient,
+            force: args.force,
+            graphql: args.graphql,
+            federation: args.federation,
+            events: args.events,
+            envelope: args.envelope,
+            migration: args.migration,
+            postman: args.postman,
+            insomnia: args.insomnia,
+            field_docs: args.field_docs,
+            docker: args.docker.then(DockerSettings::default),
+            entities: args.domain_entities.then(|| EntitySettings::from_config(config)),
+            schema: args.schema.clone(),
+            schema_dir: args.schema_dir.clone(),
+            recursive: args.recursive,
+            continue_on_error: args.continue_on_error,
+            no_id: args.no_id,
+            schema_format: args.schema_format.clone(),
+            emit_schema: args.emit_schema.clone(),
+            hooks_dir: args.hooks_dir.clone(),
+            prettier: args.prettier,
+            eslint_fix: args.eslint_fix,
+            touch_changed_only: args.touch_changed_only,
+            abstract_base: args.abstract_base,
+            event_sourcing: args.event_sourcing,
+            queue_type: args.queue_type.clone(),
+            namespace_declarations: args.namespace_declarations,
+            tsconfig: args.tsconfig,
+            package_json: args.package_json,
+            allow_empty: args.allow_empty,
+            api_versioning: args.api_versioning.clone(),
+            cache: args.cache.clone(),
+            validation: args.validation.clone(),
+            tenant_type: args.tenant_type.as_deref().map(str::to_uppercase),
+            only: args.only.clone(),
+            skip: args.skip.clone(),
+            ..Self::default()
+        };
+
+        if let Some(packages) = &args.monorepo_packages {
+            options.layout = OutputLayout::parse(packages)?;
+        }
+        if let Some(files) = &args.sequelize_files {
+            options.sequelize_files = files.split(',').map(str::trim).filter(|file| !file.is_empty()).map(str::to_string).collect();
+            if options.sequelize_files.is_empty() {
+                return Err("--sequelize-files expects a comma separated list of sequelize.ts paths".to_string());
+            }
+        }
+        if let Some(root) = &args.template_root {
+            let root = fs::canonicalize(root).map_err(|error| format!("--template-root {}: {error}", root.display()))?;
+            if !root.is_dir() {
+                return Err(format!("--template-root {} is not a directory", root.display()));
+            }
+            options.template_root = Some(root);
+        }
+        if let Some(fields) = &args.composite_pk {
+            let fields: Vec<String> = fields.split(',').map(str::trim).filter(|field| !field.is_empty()).map(str::to_string).collect();
+            if fields.len() < 2 || fields.iter().enumerate().any(|(position, field)| fields[..position].contains(field)) {
+                return Err("--composite-pk expects two or more distinct fields, like field1,field2".to_string());
+            }
+            options.composite_pk = fields;
+        }
+        if let Some(jobs) = args.jobs {
+            options.jobs = Some(jobs).filter(|jobs| *jobs > 0).ok_or(format!("--jobs expects a positive number, got {jobs}"))?;
+        }
+        if let Some(prefix) = &args.version_prefix {
+            let prefix = normalize_prefix(prefix);
+            if !prefix.chars().any(|character| character.is_ascii_digit()) {
+                return Err(format!("--version-prefix needs a version number: {prefix}"));
+            }
+            options.version_prefix = prefix;
+        }
+        if let Some(field) = &args.tenant_field {
+            if !identifier_regex().is_match(field) {
+                return Err(format!("--tenant-field needs a plain field name: {field}"));
+            }
+            options.tenant_field = Some(field.clone());
+        }
+        for assignment in &args.set {
+            let (key, value) = assignment.split_once('=').ok_or(format!("--set expects KEY=VALUE, got {assignment}"))?;
+            set_placeholder(&mut options.placeholders, key, value)?;
+        }
+        if let Some(lang) = &args.lang {
+            options.style.lang = lang.clone();
+        }
+        if let Some(module) = &args.module {
+            options.style.module = module.clone();
+        }
+        if let Some((_, module)) = TARGET_LANGS.iter().find(|(name, _)| Some(*name) == args.target_lang.as_deref()) {
+            options.style.module = module.to_string();
+        }
+
+        if !DIALECTS.contains(&options.dialect.as_str()) {
+            return Err(format!("Unsupported dialect: {}", options.dialect));
+        }
+        if let Some(logger) = options.logging.as_deref().filter(|logger| !LOGGERS.contains(logger)) {
+            return Err(format!("Unsupported logger: {logger}"));
+        }
+        if !INDEX_STYLES.contains(&options.index_style.as_str()) {
+            return Err(format!("Unsupported index style: {}", options.index_style));
+        }
+        if !ARCHITECTURES.contains(&options.architecture.as_str()) {
+            return Err(format!("Unsupported style: {}, expected clean or minimal", options.architecture));
+        }
+        if options.is_minimal() {
+            let layered = [
+                ("--orm prisma", options.orm != "sequelize"),
+                ("--di", options.di != "none"),
+                ("--cache", options.cache.is_some()),
+                ("--domain-entities", options.entities.is_some()),
+                ("--abstract-base", options.abstract_base),
+                ("--tests", options.tests),
+            ];
+            if let Some((flag, _)) = layered.iter().find(|(_, set)| *set) {
+                return Err(format!("{flag} works on the repository and use case layers, which --style minimal leaves out"));
+            }
+        }
+        if !group_layout.contains("{group}") || !group_layout.contains("{layer}") {
+            return Err(format!("group_layout needs both {{group}} and {{layer}}: {group_layout}"));
+        }
+        options.layout.group_pattern = group_layout.to_string();
+        if options.docker.is_some() {
+            options.docker = Some(DockerSettings::from_config(config, &options.dialect));
+        }
+        if options.entities.is_some() && options.orm != "sequelize" {
+            return Err("--domain-entities maps Sequelize models, it needs --orm sequelize".to_string());
+        }
+        if options.style.is_js() && options.di != "none" {
+            return Err(format!("--di {} injects through decorators, which need --lang ts", options.di));
+        }
+        if !options.composite_pk.is_empty() {
+            let single_keyed = [
+                ("--style minimal", options.is_minimal()),
+                ("--orm prisma", options.orm != "sequelize"),
+                ("--cache", options.cache.is_some()),
+                ("--events", options.events),
+                ("--federation", options.federation),
+                ("--event-sourcing", options.event_sourcing),
+            ];
+            if let Some((flag, _)) = single_keyed.iter().find(|(_, set)| *set) {
+                return Err(format!("{flag} looks records up by a single primary key and cannot be combined with --composite-pk"));
+            }
+        }
+        if options.event_sourcing && options.orm != "sequelize" {
+            return Err("--event-sourcing keeps the events in a Sequelize table, it needs --orm sequelize".to_string());
+        }
+        if options.api_versioning.is_some() && options.version_prefix.is_empty() {
+            options.version_prefix = "/v1".to_string();
+        }
+        if !options.sequelize_files.is_empty() && options.orm != "sequelize" {
+            return Err("--sequelize-files registers Sequelize models, it does not apply to --orm prisma".to_string());
+        }
+        if options.no_id && options.schema.is_none() && options.schema_dir.is_none() {
+            return Err("--no-id leaves out the id of the example model, pass --config or --schema-dir with a @PrimaryKey field on every model".to_string());
+        }
+        if options.emit_schema.as_deref() == Some("-") && args.json {
+            return Err("--emit-schema - and --json both write to stdout, send one of them to a file".to_string());
+        }
+        if options.tenant_field.is_some() {
+            let unscoped = [
+                ("--style minimal", options.is_minimal()),
+                ("--orm prisma", options.orm != "sequelize"),
+                ("--cache", options.cache.is_some()),
+                ("--graphql", options.graphql),
+                ("--event-sourcing", options.event_sourcing),
+                ("--composite-pk", !options.composite_pk.is_empty()),
+            ];
+            if let Some((flag, _)) = unscoped.iter().find(|(_, set)| *set) {
+                return Err(format!("{flag} reads records outside the tenant scoped repository and cannot be combined with --tenant-field"));
+            }
+        }
+        if options.namespace_declarations && options.style.is_js() {
+            return Err("--namespace-declarations wraps TypeScript types, it needs --lang ts".to_string());
+        }
+        if options.types_as_interface && options.style.is_js() {
+            return Err("--types-as-interface declares TypeScript interfaces, it needs --lang ts".to_string());
+        }
+        if options.tsconfig && options.style.is_js() {
+            return Err("--tsconfig configures the TypeScript compiler, it needs --lang ts".to_string());
+        }
+        if let Some(style) = &options.decorator_style {
+            if !DECORATOR_STYLES.contains(&style.as_str()) {
+                return Err(format!("Unsupported decorator style: {style}"));
+            }
+            if options.style.is_js() {
+                return Err("--decorator-style picks the TypeScript decorators, it needs --lang ts".to_string());
+            }
+        }
+        // sequelize-typescript and the DI containers read the metadata only the experimental decorators emit,
+        // and inject through constructor parameter decorators, which Stage 3 has no form of.
+        if options.decorator_style.as_deref() == Some("stage3") {
+            let legacy = [
+                ("--orm sequelize", options.orm == "sequelize"),
+                ("--di", options.di != "none"),
+                ("--event-sourcing", options.event_sourcing),
+            ];
+            if let Some((flag, _)) = legacy.iter().find(|(_, set)| *set) {
+                return Err(format!("{flag} needs the experimental decorators, use --decorator-style experimental or --orm prisma without --di"));
+            }
+        }
+        if options.style.module != "esm" && !options.style.is_js() {
+            return Err(format!("--module {} needs --lang js, TypeScript output stays ESM", options.style.module));
+        }
+        // Relative template paths of .env follow the schema, wherever crudify runs from.
+        if options.template_root.is_none() {
+            options.template_root = options
+                .schema
+                .as_deref()
+                .or(options.schema_dir.as_deref())
+                .and_then(|schema| Path::new(schema).parent())
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .and_then(|parent| fs::canonicalize(parent).ok());
+        }
+
+        Ok(options)
+    }
+
+    fn is_minimal(&self) -> bool {
+        self.architecture == "minimal"
+    }
+
+    // Schema models without a @PrimaryKey are keyed by an id, unless --no-id asks for declared keys.
+    // --composite-pk replaces the key of the models it applies to, so they keep declaring theirs.
+    fn implicit_id(&self) -> bool {
+        !self.no_id && self.composite_pk.is_empty()
+    }
+
+    // Whether --only and --skip leave the layer directory in.
+    fn generates(&self, layer: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|only| only == layer)) && !self.skip.iter().any(|skip| skip == layer)
+    }
+
+    // Environment variables read by the artifacts generated with these options.
+    fn env_variables(&self) -> Vec<&'static str> {
+        let mut variables = vec!["DB_HOST", "DB_PORT", "DB_NAME", "DB_USER", "DB_PASSWORD"];
+        if self.orm == "prisma" {
+            variables.push("DATABASE_URL");
+        }
+        if self.cache.is_some() {
+            variables.push("REDIS_URL");
+        }
+        if self.logging.as_deref() == Some("pino") {
+            variables.push("LOG_LEVEL");
+        }
+        if SCHEMA_MODELS.get().into_iter().flatten().any(Model::has_encrypted) {
+            variables.push("ENCRYPTION_KEY");
+        }
+
+        variables
+    }
+
+    // Numeric portion of the version prefix, "/api/v1" gives "1".
+    fn version(&self) -> Option<&str> {
+        let version_regex = Regex::new(r"\d+(\.\d+)?").unwrap();
+        version_regex.find(&self.version_prefix).map(|found| found.as_str())
+    }
+
+    // The version prefix as it appears in the route paths, header and query versioning leave it out.
+    fn path_version_prefix(&self) -> &str {
+        match self.api_versioning.as_deref() {
+            Some("header") | Some("query") => "",
+            _ => &self.version_prefix,
+        }
+    }
+}
+
+// "api/v1/", "/api//v1" and "/api/v1" all become "/api/v1", and "/" becomes "".
+fn normalize_prefix(prefix: &str) -> String {
+    prefix
+        .split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{segment}"))
+        .collect()
+}
+
+// Mount prefix of a model's router, the schema can override the global one per model.
+fn route_prefix<'a>(model: &'a Model, options: &'a Options) -> &'a str {
+    model.route_prefix.as_deref().unwrap_or(&options.route_prefix)
+}
+
+// Full request path of a model's collection, as the clients see it.
+fn resource_path(model: &Model, options: &Options) -> String {
+    format!("{}{}/{}", route_prefix(model, options), options.path_version_prefix(), model.name_plural.to_lowercase())
+}
+
+// Template files read during a run, so generating many models opens each one once.
+#[derive(Debug, Default)]
+struct TemplateCache {
+    enabled: bool,
+    templates: Mutex<HashMap<String, String>>
+}
+
+impl TemplateCache {
+    fn new(enabled: bool) -> Self {
+        Self { enabled, ..Self::default() }
+    }
+
+    fn read(&self, template_path: &str) -> io::Result<String> {
+        if !self.enabled {
+            return fs::read_to_string(template_path);
+        }
+        if let Some(content) = self.templates.lock().unwrap().get(template_path) {
+            return Ok(content.clone());
+        }
+
+        let content = fs::read_to_string(template_path)?;
+        self.templates.lock().unwrap().insert(template_path.to_string(), content.clone());
+        Ok(content)
+    }
+}
+
+const LOCAL_TEMPLATES_DIR: &str = "crudify-templates";
+
+// Set when stdout carries the --json report or the --emit-schema - schema of a pipe.
+static STDOUT_RESERVED: OnceLock<bool> = OnceLock::new();
+
+// Progress and diagnostics, moved to stderr while stdout is reserved for the data.
+fn status(message: &str) {
+    match STDOUT_RESERVED.get().copied().unwrap_or(false) {
+        true => eprintln!("{message}"),
+        false => println!("{message}"),
+    }
+}
+
+fn warn(message: String) {
+    status(&format!("Warning: {message}"));
+    RunReport::record(|report| report.warnings.push(message));
+}
+
+fn notice(message: &str) {
+    status(&format!("Notice: {message}"));
+}
+
+// Prints when .env sets CRUDIFY_LOG=debug.
+fn debug(message: &str) {
+    if env::var("CRUDIFY_LOG").is_ok_and(|level| level.eq_ignore_ascii_case("debug")) {
+        status(&format!("Debug: {message}"));
+    }
+}
+
+// Project-local template, ADD_USE_CASE_TEMPLATE is looked up as crudify-templates/add_use_case.ts.tpl.
+fn local_template_path(env_key: &str) -> PathBuf {
+    let name = env_key.trim_end_matches("_TEMPLATE").trim_end_matches("_JS").to_lowercase();
+    Path::new(LOCAL_TEMPLATES_DIR).join(format!("{name}.{}.tpl", output_style().lang))
+}
+
+const MAX_TEMPLATE_DEPTH: usize = 10;
+
+fn load_template(env_key: &str, builtin: Option<&str>, templates: &TemplateCache) -> io::Result<String> {
+    let content = find_template(env_key, builtin, templates)?;
+    let mut content = resolve_template(content, templates, 0)?;
+    // The values go in first, so the built-ins they mention are filled in with the rest of the template.
+    for (key, value) in PLACEHOLDERS.get().into_iter().flatten() {
+        content = content.replace(&format!("{{{key}}}"), value);
+    }
+    check_contract(env_key, content)
+}
+
+// Placeholders Crudify fills in outside of TEMPLATE_CONTRACTS, along with the {FIELD_*} and {COUNTER*} of loops.
+const RENDERED_PLACEHOLDERS: &[&str] = &[
+    "NAME", "NAME_PLURAL", "NAME_UPPER", "NAME_UPPER_PLURAL", "NAME_LOWER", "NAME_LOWER_PLURAL", "NAME_KEBAB", "NAME_SNAKE", "NAME_CAMEL",
+    "TRANSACTION_IMPORT", "TRANSACTION_PARAM", "IS_FIRST", "IS_LAST", "CHILD_CONTENT", "DIALECT",
+    "DI_IMPORT", "DI_CONTAINER_IMPORT", "DI_INJECTABLE", "DI_INJECT_REPOSITORY",
+    "ENTITY_TYPE", "ENTITY_IMPORT", "ENTITY_MAPPER_IMPORT", "TO_ENTITY", "TO_MODEL",
+    "RESOLVE_ADD_USE_CASE", "RESOLVE_GETS_USE_CASE", "RESOLVE_UPDATE_USE_CASE", "RESOLVE_DELETE_USE_CASE",
+];
+
+fn is_builtin_placeholder(key: &str) -> bool {
+    RENDERED_PLACEHOLDERS.contains(&key)
+        || key.starts_with("FIELD_")
+        || key.starts_with("COUNTER")
+        || TEMPLATE_CONTRACTS.iter().any(|(_, required, optional)| required.contains(&key) || optional.contains(&key))
+}
+
+// Project values the templates receive as {KEY}, from the [placeholders] of crudify.toml.
+fn config_placeholders(config: &toml::Table) -> Result<Vec<(String, String)>, String> {
+    let mut placeholders = Vec::new();
+    for (key, value) in config.get("placeholders").and_then(toml::Value::as_table).into_iter().flatten() {
+        let value = value.as_str().ok_or(format!("[placeholders] {key} must be a string"))?;
+        set_placeholder(&mut placeholders, key, value)?;
+    }
+    Ok(placeholders)
+}
+
+// --set replaces what crudify.toml gave the same key.
+fn set_placeholder(placeholders: &mut Vec<(String, String)>, key: &str, value: &str) -> Result<(), String> {
+    if !Regex::new(r"^[A-Z][A-Z0-9_]*$").unwrap().is_match(key) {
+        return Err(format!("Placeholder {key} must be UPPER_SNAKE_CASE, like SERVICE_SLUG"));
+    }
+    if is_builtin_placeholder(key) {
+        return Err(format!("Placeholder {{{key}}} is filled in by Crudify, pick another name"));
+    }
+    match placeholders.iter_mut().find(|(name, _)| name == key) {
+        Some(entry) => entry.1 = value.to_string(),
+        None => placeholders.push((key.to_string(), value.to_string())),
+    }
+    Ok(())
+}
+
+// Placeholders each generator fills in. A template has to keep the required ones or the artifact
+// comes out without them, and none of them may be left once the generator is done.
+// `/* crudify:ignore NAME */` in a template drops NAME from its required placeholders.
+const TEMPLATE_CONTRACTS: &[(&str, &[&str], &[&str])] = &[
+    (
+        "ADD_USE_CASE",
+        &["DYNAMIC_ADD_PROPERTIES"],
+        &["DYNAMIC_TRANSACTION_IMPORT", "DYNAMIC_TRANSACTION_BEGIN", "DYNAMIC_TRANSACTION_END", "DYNAMIC_TRANSACTION_ARGUMENT", "DYNAMIC_EVENTS_IMPORT", "DYNAMIC_PUBLISH_EVENT"],
+    ),
+    ("UPDATE_USE_CASE", &["DYNAMIC_UPDATE_PROPERTIES"], &["DYNAMIC_EVENTS_IMPORT", "DYNAMIC_PUBLISH_EVENT"]),
+    ("DELETE_USE_CASE", &[], &["DYNAMIC_EVENTS_IMPORT", "DYNAMIC_PUBLISH_EVENT"]),
+    ("CONTROLLERS", &["DYNAMIC_PROPERTIES_DETAILS"], &["REPOSITORY_IMPORT", "REPOSITORY_INSTANCE", "LOG_ERROR", "PRIMARY_KEY", "KEY_PARSER"]),
+    ("SERVICE_CONTROLLERS", &["DYNAMIC_PROPERTIES_DETAILS"], &["REPOSITORY_IMPORT", "REPOSITORY_INSTANCE", "LOG_ERROR", "PRIMARY_KEY", "KEY_PARSER"]),
+    (
+        "SERVICE",
+        &["DYNAMIC_ADD_PROPERTIES", "DYNAMIC_UPDATE_PROPERTIES"],
+        &[
+            "DYNAMIC_TRANSACTION_IMPORT", "DYNAMIC_TRANSACTION_BEGIN", "DYNAMIC_TRANSACTION_END", "DYNAMIC_TRANSACTION_ARGUMENT",
+            "DYNAMIC_EVENTS_IMPORT", "DYNAMIC_PUBLISH_CREATED", "DYNAMIC_PUBLISH_UPDATED", "DYNAMIC_PUBLISH_DELETED",
+        ],
+    ),
+    ("ROUTES", &[], &["REPOSITORY_IMPORT", "REPOSITORY_INSTANCE", "DYNAMIC_UPLOAD_IMPORT", "DYNAMIC_UPLOAD_MIDDLEWARE", "VERSION_PREFIX", "VERSION_MIDDLEWARE", "KEY_PARAMS"]),
+    ("REQUEST_UTILS", &[], &["PRIMARY_KEY", "PRIMARY_KEY_TYPE", "KEY_TYPE"]),
+    ("TYPES_UTILS", &["DYNAMIC_PROPERTIES_ATTRIBUTES", "DYNAMIC_PROPERTIES_DETAILS"], &["DYNAMIC_SHAPE_INTERFACES", "DYNAMIC_QUERY_OPTIONS"]),
+    ("MODEL", &["DYNAMIC_PROPERTIES"], &["TABLE_INDEXES", "TABLE_OPTIONS", "DYNAMIC_IMPORTS"]),
+    ("MIGRATION", &["TABLE_NAME", "DYNAMIC_MIGRATION_COLUMNS"], &["DYNAMIC_MIGRATION_INDEXES"]),
+    ("ENUM_MIGRATION", &["DYNAMIC_ENUM_TYPES", "DYNAMIC_ENUM_DROPS"], &[]),
+    ("EXTENSION_MIGRATION", &["DYNAMIC_EXTENSIONS"], &[]),
+    ("SEEDER", &["TABLE_NAME", "DYNAMIC_SEED_ROWS"], &[]),
+    ("EVENTS", &["DYNAMIC_EVENT_PAYLOAD", "DYNAMIC_EVENT_MAPPING"], &["PRIMARY_KEY", "PRIMARY_KEY_TYPE"]),
+    ("GRAPHQL_TYPEDEFS", &["DYNAMIC_GRAPHQL_TYPE"], &[]),
+    ("FEDERATION_ENTITY", &[], &["PRIMARY_KEY"]),
+    ("FEDERATION_RESOLVER", &["REPOSITORY_INSTANCE"], &["REPOSITORY_IMPORT", "PRIMARY_KEY", "PRIMARY_KEY_TYPE"]),
+    ("ZOD_SCHEMA", &["ZOD_SCHEMA_BODY"], &[]),
+    ("CACHED_REPOSITORY", &[], &["PRIMARY_KEY", "ENTITY_IMPORT", "CACHED_ITEM", "CACHED_LIST"]),
+    ("ENTITY", &["DYNAMIC_ENTITY_PROPERTIES"], &[]),
+    ("ENTITY_MAPPER", &["DYNAMIC_ENTITY_MAPPING", "DYNAMIC_MODEL_MAPPING"], &[]),
+    ("INVERSIFY_BINDINGS", &["BINDINGS"], &["BINDING_IMPORTS"]),
+    ("TSYRINGE_DI", &["DI_REGISTRATIONS"], &["DI_IMPORTS"]),
+    ("FACTORY", &["DYNAMIC_FACTORY_DEFAULTS"], &["FACTORY_IMPORT", "FACTORY_CREATE"]),
+    ("AGGREGATE", &[], &["PRIMARY_KEY", "PRIMARY_KEY_TYPE"]),
+    ("EVENT_STORE", &[], &["PRIMARY_KEY", "PRIMARY_KEY_TYPE"]),
+    ("PROJECTION", &[], &["PRIMARY_KEY", "PRIMARY_KEY_TYPE"]),
+    ("TENANT_CONTEXT", &[], &["TENANT_ID_TYPE", "TENANT_ID_PARSE", "TENANT_ID_VALID"]),
+    ("KAFKA_CONSUMER", &[], &["PRIMARY_KEY", "PRIMARY_KEY_TYPE", "KEY_FROM_MESSAGE"]),
+    ("TSCONFIG", &["DYNAMIC_TSCONFIG_PATHS"], &["BASE_URL", "OUT_DIR", "EXPERIMENTAL_DECORATORS"]),
+    ("PACKAGE_JSON", &["DEPENDENCIES", "DEV_DEPENDENCIES"], &["PACKAGE_NAME", "MODULE_TYPE", "BUILD_SCRIPT", "TEST_SCRIPT"]),
+];
+
+// ADD_USE_CASE_TEMPLATE and ADD_USE_CASE_JS_TEMPLATE share the contract of ADD_USE_CASE.
+fn template_contract(env_key: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    let artifact = env_key.trim_end_matches("_TEMPLATE").trim_end_matches("_JS");
+    TEMPLATE_CONTRACTS
+        .iter()
+        .find(|(name, _, _)| *name == artifact)
+        .map(|(_, required, optional)| (*required, *optional))
+}
+
+// Where a template is read from, for the errors about it.
+fn template_source(env_key: &str) -> String {
+    if let Ok(template_path) = env::var(env_key) {
+        return template_path;
+    }
+    let local_path = local_template_path(env_key);
+    if local_path.is_file() { local_path.display().to_string() } else { format!("built-in {env_key}") }
+}
+
+// A placeholder an enabled feature only works through, the generator has no fallback for templates without it.
+// It holds when one of the templates has every placeholder, either the controllers or the routes can build the repository.
+#[derive(Debug)]
+struct FeaturePlaceholders {
+    feature: &'static str,
+    enabled: fn(&[Model], &Options) -> bool,
+    templates: &'static [&'static str],
+    placeholders: &'static [&'static str]
+}
+
+const EVENT_PLACEHOLDERS: &[&str] = &["DYNAMIC_EVENTS_IMPORT", "DYNAMIC_PUBLISH_EVENT"];
+const INVERSIFY_PLACEHOLDERS: &[&str] = &["DI_IMPORT", "DI_INJECTABLE", "DI_INJECT_REPOSITORY"];
+
+const FEATURE_PLACEHOLDERS: &[FeaturePlaceholders] = &[
+    FeaturePlaceholders {
+        feature: "--events",
+        enabled: |models, options| options.events && !options.is_minimal() && models.iter().any(|model| !model.is_empty()),
+        templates: &["ADD_USE_CASE"],
+        placeholders: EVENT_PLACEHOLDERS,
+    },
+    FeaturePlaceholders {
+        feature: "--events",
+        enabled: |models, options| options.events && !options.is_minimal() && models.iter().any(|model| !model.is_empty()),
+        templates: &["UPDATE_USE_CASE"],
+        placeholders: EVENT_PLACEHOLDERS,
+    },
+    FeaturePlaceholders { feature: "--events", enabled: |_, options| options.events && !options.is_minimal(), templates: &["DELETE_USE_CASE"], placeholders: EVENT_PLACEHOLDERS },
+    FeaturePlaceholders {
+        feature: "--events",
+        enabled: |_, options| options.events && options.is_minimal(),
+        templates: &["SERVICE"],
+        placeholders: &["DYNAMIC_EVENTS_IMPORT", "DYNAMIC_PUBLISH_CREATED", "DYNAMIC_PUBLISH_UPDATED", "DYNAMIC_PUBLISH_DELETED"],
+    },
+    FeaturePlaceholders {
+        feature: "--cache",
+        enabled: |_, options| options.cache.is_some() && options.di == "none" && !options.is_minimal(),
+        templates: &["CONTROLLERS", "ROUTES"],
+        placeholders: &["REPOSITORY_IMPORT", "REPOSITORY_INSTANCE"],
+    },
+    FeaturePlaceholders {
+        feature: "--di inversify",
+        enabled: |models, options| options.di == "inversify" && !options.is_minimal() && models.iter().any(|model| !model.is_empty()),
+        templates: &["ADD_USE_CASE"],
+        placeholders: INVERSIFY_PLACEHOLDERS,
+    },
+    FeaturePlaceholders {
+        feature: "--di inversify",
+        enabled: |models, options| options.di == "inversify" && !options.is_minimal() && models.iter().any(|model| !model.is_empty()),
+        templates: &["UPDATE_USE_CASE"],
+        placeholders: INVERSIFY_PLACEHOLDERS,
+    },
+    FeaturePlaceholders { feature: "--di inversify", enabled: |_, options| options.di == "inversify" && !options.is_minimal(), templates: &["GETS_USE_CASE"], placeholders: INVERSIFY_PLACEHOLDERS },
+    FeaturePlaceholders { feature: "--di inversify", enabled: |_, options| options.di == "inversify" && !options.is_minimal(), templates: &["DELETE_USE_CASE"], placeholders: INVERSIFY_PLACEHOLDERS },
+    FeaturePlaceholders {
+        feature: "a FILE field",
+        enabled: |models, _| models.iter().any(|model| !model.file_fields().is_empty() && !model.is_empty()),
+        templates: &["ROUTES"],
+        placeholders: &["DYNAMIC_UPLOAD_IMPORT", "DYNAMIC_UPLOAD_MIDDLEWARE"],
+    },
+];
+
+// The built-in a template falls back on when neither .env nor crudify-templates has it.
+fn builtin_template(artifact: &str) -> Option<&'static str> {
+    match artifact {
+        "SERVICE" => Some(builtin(SERVICE_TEMPLATE, SERVICE_JS_TEMPLATE)),
+        "SERVICE_CONTROLLERS" => Some(builtin(SERVICE_CONTROLLERS_TEMPLATE, SERVICE_CONTROLLERS_JS_TEMPLATE)),
+        _ => None,
+    }
+}
+
+// What the enabled features are missing from the templates the run resolves, a template that cannot be
+// found is left to the generation to report.
+fn missing_feature_placeholders(models: &[Model], options: &Options) -> Vec<String> {
+    let templates = TemplateCache::new(options.template_cache);
+    let mut missing = Vec::new();
+    for requirement in FEATURE_PLACEHOLDERS.iter().filter(|requirement| (requirement.enabled)(models, options)) {
+        let resolved: Vec<(String, String)> = requirement
+            .templates
+            .iter()
+            .filter_map(|artifact| {
+                let env_key = template_key(&format!("{artifact}_TEMPLATE"));
+                let content = find_template(&env_key, builtin_template(artifact), &templates).and_then(|content| resolve_template(content, &templates, 0)).ok()?;
+                Some((template_source(&env_key), content))
+            })
+            .collect();
+        if resolved.is_empty() {
+            continue;
+        }
+        let absent: Vec<String> = requirement
+            .placeholders
+            .iter()
+            .map(|placeholder| format!("{{{placeholder}}}"))
+            .filter(|placeholder| !resolved.iter().any(|(_, content)| content.contains(placeholder)))
+            .collect();
+        if !absent.is_empty() {
+            let sources: Vec<&str> = resolved.iter().map(|(source, _)| source.as_str()).collect();
+            missing.push(format!("{} needs {} in {}", requirement.feature, absent.join(", "), sources.join(" or ")));
+        }
+    }
+    missing
+}
+
+// Fails on a template missing required placeholders, and strips its crudify:ignore comments.
+fn check_contract(env_key: &str, content: String) -> io::Result<String> {
+    let Some((required, _)) = template_contract(env_key) else {
+        return Ok(content);
+    };
+
+    let ignore = Regex::new(r"[ \t]*/\*\s*crudify:ignore\s+([^*]*?)\s*\*/[ \t]*\n?").unwrap();
+    let ignored: Vec<String> = ignore
+        .captures_iter(&content)
+        .flat_map(|captures| {
+            captures[1]
+                .split(|character: char| character == ',' || character.is_whitespace())
+                .map(|name| name.trim_matches(['{', '}']).to_string())
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|placeholder| !ignored.iter().any(|name| name == *placeholder))
+        .filter(|placeholder| !content.contains(&format!("{{{placeholder}}}")))
+        .copied()
+        .collect();
+    if !missing.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} is missing the required placeholders {}, add them or opt out with /* crudify:ignore NAME */",
+                template_source(env_key),
+                missing.iter().map(|placeholder| format!("{{{placeholder}}}")).collect::<Vec<_>>().join(", ")
+            ),
+        ));
+    }
+
+    Ok(ignore.replace_all(&content, "").into_owned())
+}
+
+// Fails when the generator left one of the placeholders of the template unsubstituted.
+fn check_rendered(env_key: &str, content: &str) -> io::Result<()> {
+    let Some((required, optional)) = template_contract(env_key) else {
+        return Ok(());
+    };
+
+    let left: Vec<String> = required
+        .iter()
+        .chain(optional)
+        .map(|placeholder| format!("{{{placeholder}}}"))
+        .filter(|placeholder| content.contains(placeholder))
+        .collect();
+    if !left.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} still has {} after rendering", template_source(env_key), left.join(", ")),
+        ));
+    }
+
+    // Any other {UPPER_SNAKE} is a placeholder nothing fills, a typo or a [placeholders] key that is not set.
+    // A ${NAME} is left alone, it interpolates a JavaScript template literal.
+    let placeholder = Regex::new(r"(^|[^$])\{([A-Z][A-Z0-9_]*)\}").unwrap();
+    let mut unknown: Vec<String> = Vec::new();
+    for captures in placeholder.captures_iter(content) {
+        let name = format!("{{{}}}", &captures[2]);
+        if !unknown.contains(&name) {
+            unknown.push(name);
+        }
+    }
+    if !unknown.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} has the unknown placeholders {}, which no generator or [placeholders] entry fills",
+                template_source(env_key),
+                unknown.join(", ")
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+// A template starting with {EXTENDS name} is placed in the {CHILD_CONTENT} of the base template,
+// which is looked up like any other under NAME_TEMPLATE or crudify-templates/name.ts.tpl.
+fn resolve_template(content: String, templates: &TemplateCache, depth: usize) -> io::Result<String> {
+    let Some(directive) = content.trim_start().strip_prefix("{EXTENDS ") else {
+        return Ok(content);
+    };
+    let (name, child) = directive
+        .split_once('}')
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "{EXTENDS} directive is missing its closing brace"))?;
+    let name = name.trim();
+    if depth >= MAX_TEMPLATE_DEPTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Templates extend more than {MAX_TEMPLATE_DEPTH} levels deep at {name}, check for a cycle"),
+        ));
+    }
+
+    let base = find_template(&template_key(&format!("{}_TEMPLATE", name.to_uppercase())), None, templates)?;
+    let base = resolve_template(base, templates, depth + 1)?;
+    if !base.contains("{CHILD_CONTENT}") {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Base template {name} has no {{CHILD_CONTENT}} placeholder")));
+    }
+    Ok(base.replace("{CHILD_CONTENT}", child.strip_prefix('\n').unwrap_or(child)))
+}
+
+// Tries the path in the env var, then the project-local template, then the embedded one when there is one.
+fn find_template(env_key: &str, builtin: Option<&str>, templates: &TemplateCache) -> io::Result<String> {
+    if let Ok(template_path) = env::var(env_key) {
+        let template_path = template_root_path(&template_path);
+        if !template_path.is_file() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("{env_key} points to {}, which does not exist", template_path.display())));
+        }
+        debug(&format!("{env_key} read from {}", template_path.display()));
+        return read_template_file(&template_path, templates);
+    }
+
+    let local_path = local_template_path(env_key);
+    if local_path.is_file() {
+        debug(&format!("{env_key} read from {}", local_path.display()));
+        return read_template_file(&local_path, templates);
+    }
+
+    match builtin {
+        Some(content) => {
+            debug(&format!("{env_key} uses the built-in template"));
+            include_templates(content.to_string(), None, templates, &mut Vec::new())
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{env_key} not set in .env file and {} does not exist", local_path.display()),
+        )),
+    }
+}
+
+const MAX_INCLUDE_DEPTH: usize = 5;
+
+// Partials the built-in templates include, also the fallback for local templates copied by upgrade-templates.
+const BUILTIN_PARTIALS: &[(&str, &str)] = &[
+    ("partials/correlation_header", include_str!("../templates/partials/correlation_header.txt")),
+    ("partials/model_import", include_str!("../templates/partials/model_import.txt")),
+];
+
+fn read_template_file(template_path: &Path, templates: &TemplateCache) -> io::Result<String> {
+    let content = templates.read(&template_path.to_string_lossy())?;
+    include_templates(content, Some(template_path), templates, &mut vec![canonical_path(template_path)])
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+// Replaces every {INCLUDE partial.tpl} or {{> partials/name}} with that file, resolved next to the template
+// including it and tried with a .tpl extension too. Built-in templates, which have no directory, only include built-in partials.
+// The stack holds the templates being included, from the outermost one.
+fn include_templates(content: String, template_path: Option<&Path>, templates: &TemplateCache, stack: &mut Vec<PathBuf>) -> io::Result<String> {
+    let include = Regex::new(r"\{\{>\s*([^}]+?)\s*\}\}|\{INCLUDE\s+([^}]+?)\s*\}").unwrap();
+    if !include.is_match(&content) {
+        return Ok(content);
+    }
+
+    let including = template_path.map_or_else(|| "built-in template".to_string(), |template_path| template_path.display().to_string());
+    let mut result = String::new();
+    let mut last = 0;
+    for captures in include.captures_iter(&content) {
+        let directive = captures.get(0).unwrap();
+        result.push_str(&content[last..directive.start()]);
+        last = directive.end();
+
+        let name = captures.get(1).or_else(|| captures.get(2)).unwrap().as_str();
+        let line = content[..directive.start()].matches('\n').count() + 1;
+        let partial_path = template_path.and_then(|template_path| {
+            let directory = template_path.parent().unwrap_or(Path::new("."));
+            [directory.join(name), directory.join(format!("{name}.tpl"))].into_iter().find(|partial_path| partial_path.is_file())
+        });
+        let builtin = BUILTIN_PARTIALS.iter().find(|(partial, _)| *partial == name).map(|(_, content)| *content);
+
+        let (key, partial) = match (&partial_path, builtin) {
+            (Some(partial_path), _) => (canonical_path(partial_path), None),
+            (None, Some(builtin)) => (PathBuf::from(format!("built-in {name}")), Some(builtin)),
+            (None, None) => {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!("Partial {name} included at {including}:{line} does not exist")));
+            }
+        };
+        if stack.contains(&key) {
+            let chain = stack.iter().chain([&key]).map(|path| path.display().to_string()).collect::<Vec<_>>().join(" -> ");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Circular template include: {chain}")));
+        }
+        if stack.len() > MAX_INCLUDE_DEPTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Template includes nest more than {MAX_INCLUDE_DEPTH} levels deep at {including}:{line}"),
+            ));
+        }
+
+        let partial = match (partial, &partial_path) {
+            (Some(builtin), _) => builtin.to_string(),
+            (None, Some(partial_path)) => templates
+                .read(&partial_path.to_string_lossy())
+                .map_err(|error| io::Error::new(error.kind(), format!("{} included at {including}:{line}: {error}", partial_path.display())))?,
+            (None, None) => unreachable!(),
+        };
+        stack.push(key);
+        let partial = include_templates(partial, partial_path.as_deref(), templates, stack)?;
+        stack.pop();
+        result.push_str(&partial);
+    }
+    result.push_str(&content[last..]);
+
+    Ok(result)
+}
+
+fn copy_template(env_key: &str, builtin: Option<&str>, model: &Model, templates: &TemplateCache) -> io::Result<String>{
+    let content = load_template(env_key, builtin, templates)?;
+
+    Ok(render_template(&content, model))
+}
+
+// Keeps `{IF @Attr}...{/IF}` blocks when a field of the model has the attribute and drops them otherwise,
+// `{IF !@Attr}` the other way around. `{IF true}` and `{IF false}` are what `{IF IS_FIRST}` and `{IF IS_LAST}` become inside loops.
+// Nested blocks are resolved from the innermost one out.
+fn process_conditionals(content: &str, model: &Model) -> String {
+    let mut content = content.to_string();
+    while let Some(start) = content.rfind("{IF ") {
+        let Some(header_end) = content[start..].find('}').map(|offset| start + offset) else { break };
+        let Some(end) = content[header_end..].find("{/IF}").map(|offset| header_end + offset) else {
+            warn("template has an {IF} block without {/IF}".to_string());
+            break;
+        };
+
+        let condition = match content[start + 4..header_end].trim() {
+            "true" => true,
+            "false" => false,
+            attribute => match attribute.strip_prefix('!') {
+                Some(attribute) => !model.fields.iter().any(|field| field.has_attr(attribute.trim())),
+                None => model.fields.iter().any(|field| field.has_attr(attribute)),
+            },
+        };
+        let body = if condition { &content[header_end + 1..end] } else { "" };
+        content = format!("{}{body}{}", &content[..start], &content[end + 5..]);
+    }
+
+    content
+}
+
+// Repeats `{FOREACH field}...{/FOREACH}` bodies once per field with the {FIELD_*} placeholders filled in,
+// along with {COUNTER} from 0, {COUNTER_1} from 1 and {IS_FIRST}/{IS_LAST} as true or false, also in `{IF !IS_LAST}`.
+// `{FOREACH field WHERE @Attr}` only repeats it for the fields with the attribute, `WHERE !@Attr` for the ones without.
+// Nested loops are expanded from the innermost one out, like the conditionals.
+fn process_loops(content: &str, fields: &[Field]) -> String {
+    let mut content = content.to_string();
+    while let Some(start) = content.rfind("{FOREACH field") {
+        let Some(body_start) = content[start..].find('}').map(|offset| start + offset + 1) else { break };
+        let Some(end) = content[body_start..].find("{/FOREACH}").map(|offset| body_start + offset) else {
+            warn("template has a {FOREACH field} loop without {/FOREACH}".to_string());
+            break;
+        };
+
+        let clause = content[start + "{FOREACH field".len()..body_start - 1].trim();
+        let filter = match clause.strip_prefix("WHERE") {
+            Some(attribute) => {
+                let attribute = attribute.trim();
+                Some(attribute.strip_prefix('!').map_or((attribute, true), |attribute| (attribute.trim(), false)))
+            }
+            None if clause.is_empty() => None,
+            None => {
+                warn(format!("ignoring unknown clause {clause} of a {{FOREACH field}} loop"));
+                None
+            }
+        };
+
+        let body = &content[body_start..end];
+        let selected: Vec<&Field> = fields
+            .iter()
+            .filter(|field| filter.is_none_or(|(attribute, present)| field.has_attr(attribute) == present))
+            .collect();
+        let repeated: String = selected
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                body.replace("{IF IS_FIRST}", &format!("{{IF {}}}", index == 0))
+                    .replace("{IF IS_LAST}", &format!("{{IF {}}}", index + 1 == selected.len()))
+                    .replace("{IF !IS_FIRST}", &format!("{{IF {}}}", index != 0))
+                    .replace("{IF !IS_LAST}", &format!("{{IF {}}}", index + 1 != selected.len()))
+                    .replace("{IS_FIRST}", &(index == 0).to_string())
+                    .replace("{IS_LAST}", &(index + 1 == selected.len()).to_string())
+                    .replace("{COUNTER}", &index.to_string())
+                    .replace("{COUNTER_1}", &(index + 1).to_string())
+                    .replace("{FIELD_NAME}", &field.name)
+                    .replace("{FIELD_JS_TYPE}", field.property_type())
+                    .replace("{FIELD_DB_TYPE}", &field.db_type)
+            })
+            .collect();
+        content = format!("{}{repeated}{}", &content[..start], &content[end + "{/FOREACH}".len()..]);
+    }
+
+    content
+}
+
+fn render_template(content: &str, model: &Model) -> String {
+    let content = &process_conditionals(&process_loops(content, &model.fields), model);
+    let (transaction_import, transaction_param) = if output_style().is_js() {
+        ("", "transaction")
+    } else {
+        ("import { Transaction } from \"sequelize\";", "transaction?: Transaction")
+    };
+    let mut content = content
+        .replace("{NAME_UPPER}", &model.name)
+        .replace("{NAME_UPPER_PLURAL}", &model.name_plural)
+        .replace("{NAME_LOWER}", &model.name.to_lowercase())
+        .replace("{NAME_LOWER_PLURAL}", &model.name_plural.to_lowercase())
+        .replace("{TRANSACTION_IMPORT}", transaction_import)
+        .replace("{TRANSACTION_PARAM}", transaction_param);
+    for (casing, value) in name_casings(&model.name).into_iter().skip(1) {
+        content = content.replace(&format!("{{NAME_{}}}", casing.to_uppercase()), &value);
+    }
+
+    naming().rewrite_imports(&content, model)
+}
+
+const ARTIFACTS: &[(&str, &str)] = &[
+    ("model", "{name_lower}Model.ts"),
+    ("interface", "I{NAME}Repository.ts"),
+    ("repository", "{name_lower}Repository.ts"),
+    ("cached_repository", "Cached{NAME}Repository.ts"),
+    ("routes", "{name_lower}Routes.ts"),
+    ("controllers", "{name_lower}Controllers.ts"),
+    ("add_use_case", "Add{NAME}.ts"),
+    ("gets_use_case", "Get{NAME_PLURAL}.ts"),
+    ("update_use_case", "Update{NAME}.ts"),
+    ("delete_use_case", "Delete{NAME}.ts"),
+    ("events", "{NAME}Events.ts"),
+    ("typedefs", "{name_lower}TypeDefs.ts"),
+    ("resolvers", "{name_lower}Resolvers.ts"),
+];
+
+// Splits "BlogPost", "blog_post" or "blog-post" into lowercase words.
+fn name_words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous_lower = false;
+    for character in name.chars() {
+        if character == '_' || character == '-' || character == ' ' {
+            previous_lower = false;
+            words.push(String::new());
+            continue;
+        }
+        if character.is_uppercase() && previous_lower || words.is_empty() {
+            words.push(String::new());
+        }
+        previous_lower = character.is_lowercase() || character.is_ascii_digit();
+        words.last_mut().unwrap().extend(character.to_lowercase());
+    }
+
+    words.into_iter().filter(|word| !word.is_empty()).collect()
+}
+
+fn name_casings(name: &str) -> [(&'static str, String); 4] {
+    let words = name_words(name);
+    let camel: String = words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| if index == 0 { word.clone() } else { word[..1].to_uppercase() + &word[1..] })
+        .collect();
+
+    [
+        ("lower", name.to_lowercase()),
+        ("kebab", words.join("-")),
+        ("snake", words.join("_")),
+        ("camel", camel),
+    ]
+}
+
+// File name patterns of the generated artifacts, overridable in the [naming]
+// section of crudify.toml.
+#[derive(Debug, Clone, PartialEq)]
+struct Naming {
+    patterns: Vec<(String, String)>
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Self { patterns: ARTIFACTS.iter().map(|(artifact, pattern)| (artifact.to_string(), pattern.to_string())).collect() }
+    }
+}
+
+impl Naming {
+    fn from_config(config: &toml::Table) -> Result<Self, String> {
+        let mut naming = Self::default();
+        for (artifact, pattern) in config.get("naming").and_then(toml::Value::as_table).into_iter().flatten() {
+            let pattern = pattern.as_str().ok_or(format!("[naming] {artifact} must be a string"))?;
+            let entry = naming
+                .patterns
+                .iter_mut()
+                .find(|(name, _)| name == artifact)
+                .ok_or(format!("Unknown artifact in [naming]: {artifact}"))?;
+            entry.1 = pattern.to_string();
+        }
+
+        Ok(naming)
+    }
+
+    fn render(pattern: &str, model: &Model) -> String {
+        let mut file_name = pattern.replace("{NAME}", &model.name).replace("{NAME_PLURAL}", &model.name_plural);
+        for (casing, value) in name_casings(&model.name) {
+            file_name = file_name.replace(&format!("{{name_{casing}}}"), &value);
+        }
+        for (casing, value) in name_casings(&model.name_plural) {
+            file_name = file_name.replace(&format!("{{name_{casing}_plural}}"), &value);
+        }
+
+        file_name
+    }
+
+    fn file_name(&self, artifact: &str, model: &Model) -> String {
+        let pattern = self.patterns.iter().find(|(name, _)| name == artifact).map_or("", |(_, pattern)| pattern.as_str());
+        let file_name = Self::render(pattern, model);
+        match file_name.strip_suffix(".ts") {
+            Some(stem) if output_style().is_js() => format!("{stem}.js"),
+            _ => file_name,
+        }
+    }
+
+    // Import specifier of an artifact, its file name without the extension.
+    fn module(&self, artifact: &str, model: &Model) -> String {
+        let file_name = self.file_name(artifact, model);
+        match file_name.rsplit_once('.') {
+            Some((module, "ts" | "js")) => module.to_string(),
+            _ => file_name,
+        }
+    }
+
+    // Templates written against the default names keep resolving under a custom convention.
+    fn rewrite_imports(&self, content: &str, model: &Model) -> String {
+        let mut content = content.to_string();
+        for (artifact, default) in ARTIFACTS {
+            let default_module = Self::render(default, model).trim_end_matches(".ts").to_string();
+            let module = self.module(artifact, model);
+            if module != default_module {
+                for quote in ['"', '\''] {
+                    content = content.replace(&format!("/{default_module}{quote}"), &format!("/{module}{quote}"));
+                }
+            }
+        }
+
+        content
+    }
+}
+
+static NAMING: OnceLock<Naming> = OnceLock::new();
+static PLACEHOLDERS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+fn naming() -> &'static Naming {
+    NAMING.get_or_init(Naming::default)
+}
+
+static TEMPLATE_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+// A relative template path of .env anchored to --template-root, with its symlinks resolved.
+fn template_root_path(template_path: &str) -> PathBuf {
+    let template_path = Path::new(template_path);
+    match TEMPLATE_ROOT.get() {
+        Some(root) if template_path.is_relative() => {
+            let anchored = root.join(template_path);
+            fs::canonicalize(&anchored).unwrap_or(anchored)
+        }
+        _ => template_path.to_path_buf(),
+    }
+}
+
+static OUTPUT_STYLE: OnceLock<OutputStyle> = OnceLock::new();
+
+fn output_style() -> &'static OutputStyle {
+    OUTPUT_STYLE.get_or_init(OutputStyle::default)
+}
+
+// JavaScript output reads its templates from X_JS_TEMPLATE, the TypeScript
+// ones would not parse as plain JavaScript.
+fn template_key(env_key: &str) -> String {
+    if output_style().is_js() {
+        env_key.replace("_TEMPLATE", "_JS_TEMPLATE")
+    } else {
+        env_key.to_string()
+    }
+}
+
+fn builtin(typescript: &'static str, javascript: &'static str) -> &'static str {
+    if output_style().is_js() { javascript } else { typescript }
+}
+
+fn template_or_default(env_key: &str, default: &str, templates: &TemplateCache) -> io::Result<String> {
+    load_template(env_key, Some(default), templates)
+}
+
+fn content_hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+static PACKAGE_IMPORTS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+static GROUP_IMPORTS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+static SCHEMA_MODELS: OnceLock<Vec<Model>> = OnceLock::new();
+
+// Points the layer aliases (@core/, @infrastructure/, @presentation/) at the
+// workspace packages when generating into a monorepo, and at the group of grouped models.
+fn package_imports(content: &str) -> String {
+    let mut content = content.to_string();
+    for (alias, group_alias) in GROUP_IMPORTS.get().into_iter().flatten() {
+        content = content.replace(&format!("\"{alias}"), &format!("\"{group_alias}"));
+    }
+    for (alias, package) in PACKAGE_IMPORTS.get().into_iter().flatten() {
+        for quote in ['"', '\'', '`'] {
+            content = content.replace(&format!("{quote}{alias}"), &format!("{quote}{package}"));
+        }
+    }
+
+    content
+}
+
+// Rewrites ESM imports and exports as require and module.exports for --module cjs.
+// Content that already is CommonJS passes through unchanged.
+fn to_commonjs(content: &str) -> String {
+    let named_import = Regex::new(r#"(?m)^import\s+(?:(\w+)\s*,\s*)?\{([^}]*)\}\s*from\s*("[^"]*"|'[^']*');?"#).unwrap();
+    let default_import = Regex::new(r#"(?m)^import\s+(?:\*\s+as\s+)?(\w+)\s+from\s*("[^"]*"|'[^']*');?"#).unwrap();
+    let bare_import = Regex::new(r#"(?m)^import\s*("[^"]*"|'[^']*');?"#).unwrap();
+    let declaration_export = Regex::new(r"(?m)^export\s+((?:async\s+)?function\*?|class|const|let|var)\s+(\w+)").unwrap();
+    let list_export = Regex::new(r"(?m)^export\s*\{([^}]*)\};?[ \t]*\n?").unwrap();
+    let list_reexport = Regex::new(r#"(?m)^export\s*\{([^}]*)\}\s*from\s*("[^"]*"|'[^']*');?"#).unwrap();
+    let namespace_reexport = Regex::new(r#"(?m)^export\s*\*\s*as\s+(\w+)\s+from\s*("[^"]*"|'[^']*');?"#).unwrap();
+    let star_reexport = Regex::new(r#"(?m)^export\s*\*\s*from\s*("[^"]*"|'[^']*');?[ \t]*\n?"#).unwrap();
+    let default_export = Regex::new(r"(?m)^export\s+default\s+").unwrap();
+
+    let mut content = named_import.replace_all(content, |captures: &regex::Captures| {
+        let bindings = captures[2]
+            .split(',')
+            .map(str::trim)
+            .filter(|binding| !binding.is_empty())
+            .map(|binding| binding.replacen(" as ", ": ", 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match captures.get(1) {
+            Some(default) => format!("const {0} = require({1});\nconst {{ {bindings} }} = {0};", default.as_str(), &captures[3]),
+            None => format!("const {{ {bindings} }} = require({});", &captures[3]),
+        }
+    }).into_owned();
+    content = default_import.replace_all(&content, "const $1 = require($2);").into_owned();
+    content = bare_import.replace_all(&content, "require($1);").into_owned();
+
+    // Re-exported bindings are required under their exported name, `export *` is merged into the exports last.
+    let mut exports: Vec<(String, String)> = Vec::new();
+    content = list_reexport.replace_all(&content, |captures: &regex::Captures| {
+        let mut bindings = Vec::new();
+        for binding in captures[1].split(',').map(str::trim).filter(|binding| !binding.is_empty()) {
+            let (imported, exported) = binding.split_once(" as ").map_or((binding, binding), |(imported, exported)| (imported.trim(), exported.trim()));
+            exports.push((exported.to_string(), exported.to_string()));
+            bindings.push(if imported == exported { exported.to_string() } else { format!("{imported}: {exported}") });
+        }
+        format!("const {{ {} }} = require({});", bindings.join(", "), &captures[2])
+    }).into_owned();
+    content = namespace_reexport.replace_all(&content, |captures: &regex::Captures| {
+        exports.push((captures[1].to_string(), captures[1].to_string()));
+        format!("const {} = require({});", &captures[1], &captures[2])
+    }).into_owned();
+    let mut merged: Vec<String> = Vec::new();
+    content = star_reexport.replace_all(&content, |captures: &regex::Captures| {
+        merged.push(format!("Object.assign(module.exports, require({}));", &captures[1]));
+        String::new()
+    }).into_owned();
+    content = declaration_export.replace_all(&content, |captures: &regex::Captures| {
+        exports.push((captures[2].to_string(), captures[2].to_string()));
+        format!("{} {}", &captures[1], &captures[2])
+    }).into_owned();
+    content = list_export.replace_all(&content, |captures: &regex::Captures| {
+        for binding in captures[1].split(',').map(str::trim).filter(|binding| !binding.is_empty()) {
+            let (local, exported) = binding.split_once(" as ").unwrap_or((binding, binding));
+            exports.push((exported.trim().to_string(), local.trim().to_string()));
+        }
+        String::new()
+    }).into_owned();
+    content = default_export.replace_all(&content, "module.exports = ").into_owned();
+
+    if exports.is_empty() && merged.is_empty() {
+        return content;
+    }
+
+    // Named exports hang off the default export when the module has one.
+    let mut lines: Vec<String> = Vec::new();
+    if content.contains("module.exports =") {
+        lines.extend(exports.iter().map(|(exported, local)| format!("module.exports.{exported} = {local};")));
+    } else if !exports.is_empty() {
+        let bindings = exports
+            .iter()
+            .map(|(exported, local)| if exported == local { exported.clone() } else { format!("{exported}: {local}") })
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("module.exports = {{ {bindings} }};"));
+    }
+    lines.extend(merged);
+
+    match content.trim_end() {
+        "" => format!("{}\n", lines.join("\n")),
+        body => format!("{body}\n\n{}\n", lines.join("\n")),
+    }
+}
+
+const AMD_HEADER: &str = "define(function (require, exports, module) {\n";
+
+// AMD modules are the CommonJS source in the simplified CommonJS wrapper. A module that is already wrapped
+// is unwrapped first, with the lines an updater inserted above the wrapper joining its body.
+// Leading comment lines, the crudify-hash header among them, stay above the wrapper.
+fn to_amd(content: &str) -> String {
+    let content = match content.split_once(AMD_HEADER) {
+        Some((prefix, body)) => {
+            let body = body.trim_end();
+            format!("{prefix}{}", body.strip_suffix("});").unwrap_or(body))
+        }
+        None => content.to_string(),
+    };
+    let content = to_commonjs(&content);
+
+    let lines: Vec<&str> = content.lines().collect();
+    let comments = lines.iter().take_while(|line| line.starts_with("//")).count();
+    let header: String = lines[..comments].iter().map(|line| format!("{line}\n")).collect();
+    format!("{header}{AMD_HEADER}{}\n}});\n", lines[comments..].join("\n").trim_matches('\n'))
+}
+
+fn source_content(file_path: &Path, content: &str) -> String {
+    match file_path.extension().and_then(|extension| extension.to_str()) {
+        Some("ts") | Some("js") if output_style().is_amd() => to_amd(&package_imports(content)),
+        Some("ts") | Some("js") if output_style().is_commonjs() => to_commonjs(&package_imports(content)),
+        Some("ts") | Some("js") => package_imports(content),
+        _ => content.to_string(),
+    }
+}
+
+// An import line in the module syntax of the generated sources, so updaters
+// can look for it before inserting.
+fn module_import(import: &str) -> String {
+    if output_style().is_commonjs() || output_style().is_amd() {
+        to_commonjs(&package_imports(import))
+    } else {
+        package_imports(import)
+    }
+}
+
+fn write_file(file_path: &Path, content: &str) -> io::Result<()> {
+    let file_path = output_style().source_path(file_path);
+    let content = source_content(&file_path, content);
+
+    match TRANSACTION.lock().unwrap().as_mut() {
+        Some(transaction) => {
+            transaction.stage(&file_path, content);
+            Ok(())
+        }
+        None => atomic_write(&file_path, content.as_bytes()),
+    }
+}
+
+// Reads a file as the current run sees it, including writes that are staged but not committed.
+fn read_file(file_path: &Path) -> io::Result<String> {
+    let file_path = output_style().source_path(file_path);
+    if let Some(content) = TRANSACTION.lock().unwrap().as_ref().and_then(|transaction| transaction.staged(&file_path)) {
+        return Ok(content.to_string());
+    }
+
+    fs::read_to_string(file_path)
+}
+
+fn file_exists(file_path: &Path) -> bool {
+    let file_path = output_style().source_path(file_path);
+    let staged = TRANSACTION.lock().unwrap().as_ref().is_some_and(|transaction| transaction.staged(&file_path).is_some());
+    staged || file_path.exists()
+}
+
+static TRANSACTION: Mutex<Option<GenerationTransaction>> = Mutex::new(None);
+
+thread_local! {
+    // The model whose files the thread is writing, the --schema-dir summary groups the files by it.
+    static STAGING_MODEL: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn planning_only() -> bool {
+    TRANSACTION.lock().unwrap().as_ref().is_some_and(|transaction| transaction.plan_only)
+}
+
+fn create_output_dir(directory: &Path) -> io::Result<()> {
+    if planning_only() {
+        return Ok(());
+    }
+    fs::create_dir_all(directory)
+}
+
+// Runs `run` with the files it writes staged as files of the model.
+fn staging_for<T>(model: &Model, run: impl FnOnce() -> T) -> T {
+    let previous = STAGING_MODEL.with(|staging| staging.replace(Some(model.name.to_string())));
+    let result = run();
+    STAGING_MODEL.with(|staging| staging.replace(previous));
+    result
+}
+
+// Collects every write of a generation run and only touches the disk once
+// planning succeeded, rolling back the files already written if one fails.
+#[derive(Debug, Default)]
+struct GenerationTransaction {
+    writes: Vec<(PathBuf, String)>,
+    // The models that wrote each file.
+    owners: Vec<(PathBuf, Vec<String>)>,
+    // Set for the plans that are never committed, the layer directories are then left uncreated too.
+    plan_only: bool
+}
+
+impl GenerationTransaction {
+    fn begin() {
+        *TRANSACTION.lock().unwrap() = Some(Self::default());
+    }
+
+    fn begin_plan() {
+        *TRANSACTION.lock().unwrap() = Some(Self { plan_only: true, ..Self::default() });
+    }
+
+    fn stage(&mut self, file_path: &Path, content: String) {
+        match self.writes.iter_mut().find(|(path, _)| path == file_path) {
+            Some((_, staged)) => *staged = content,
+            None => self.writes.push((file_path.to_path_buf(), content)),
+        }
+        let Some(model) = STAGING_MODEL.with(|staging| staging.borrow().clone()) else {
+            return;
+        };
+        match self.owners.iter_mut().find(|(path, _)| path == file_path) {
+            Some((_, models)) if !models.contains(&model) => models.push(model),
+            Some(_) => {}
+            None => self.owners.push((file_path.to_path_buf(), vec![model])),
+        }
+    }
+
+    fn staged(&self, file_path: &Path) -> Option<&str> {
+        self.writes.iter().find(|(path, _)| path == file_path).map(|(_, content)| content.as_str())
+    }
+
+    // Ends the transaction without writing anything, for the commands that only look at the plan.
+    fn take() -> Self {
+        TRANSACTION.lock().unwrap().take().unwrap_or_default()
+    }
+
+    // The staged files the commit is about to modify with their content and mtime, for --touch-changed-only.
+    fn snapshot() -> Vec<(PathBuf, Vec<u8>, SystemTime)> {
+        let transaction = TRANSACTION.lock().unwrap();
+        transaction.iter().flat_map(|transaction| &transaction.writes).filter_map(|(file_path, content)| {
+            let previous = fs::read(file_path).ok().filter(|previous| previous != content.as_bytes())?;
+            let modified = fs::metadata(file_path).and_then(|metadata| metadata.modified()).ok()?;
+            Some((file_path.clone(), previous, modified))
+        }).collect()
+    }
+
+    // Takes the planned writes without committing them and lists the files whose disk content differs.
+    fn stale() -> Vec<PathBuf> {
+        let Some(transaction) = TRANSACTION.lock().unwrap().take() else {
+            return Vec::new();
+        };
+
+        transaction
+            .writes
+            .into_iter()
+            .filter(|(file_path, content)| fs::read(file_path).map_or(true, |existing| existing != content.as_bytes()))
+            .map(|(file_path, _)| file_path)
+            .collect()
+    }
+
+    fn commit() -> io::Result<()> {
+        let Some(transaction) = TRANSACTION.lock().unwrap().take() else {
+            return Ok(());
+        };
+
+        RunReport::record(|report| report.owners = transaction.owners);
+        let mut completed: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for (file_path, content) in transaction.writes {
+            let previous = fs::read(&file_path).ok();
+            let action = match &previous {
+                None => "created",
+                Some(previous) if previous == content.as_bytes() => "skipped",
+                Some(_) => "modified",
+            };
+            RunReport::record(|report| report.file(&file_path, action, &content));
+            if action == "skipped" {
+                continue;
+            }
+            // A shared file can be staged for a layer directory --only or --skip left uncreated.
+            let written = match file_path.parent() {
+                Some(parent) => fs::create_dir_all(parent).and_then(|_| atomic_write(&file_path, content.as_bytes())),
+                None => atomic_write(&file_path, content.as_bytes()),
+            };
+            if let Err(error) = written {
+                status(&format!("Error writing {}: {error}, rolling back {} files", file_path.display(), completed.len()));
+                for (written, previous) in completed.into_iter().rev() {
+                    let restored = match previous {
+                        Some(previous) => atomic_write(&written, &previous),
+                        None => fs::remove_file(&written),
+                    };
+                    if let Err(error) = restored {
+                        warn(format!("could not roll back {}: {error}", written.display()));
+                    }
+                }
+                return Err(error);
+            }
+            completed.push((file_path, previous));
+        }
+
+        Ok(())
+    }
+}
+
+const REPORT_FILE: &str = ".crudify/last-run.json";
+const REPORT_VERSION: u32 = 1;
+
+static RUN_REPORT: Mutex<Option<RunReport>> = Mutex::new(None);
+
+// What a generation run did, for the tooling that drives Crudify. The JSON keeps this shape,
+// report_version is bumped whenever a key changes meaning or goes away:
+// {
+//   "report_version": 1,
+//   "command": "generate",
+//   "args": ["--health", "--config", "schema.yaml"],
+//   "schema_hash": "9f2c6a1e0b7d4c3a" or null,
+//   "status": "succeeded" or "failed",
+//   "error": "…" or null,
+//   "duration_ms": 42,
+//   "steps": [{ "name": "generate", "duration_ms": 30 }],
+//   "files": [{ "path": "core/utils/ApiResponse.ts", "action": "created", "bytes": 812, "hash": "…" }],
+//   "writes_avoided": 12,
+//   "warnings": ["…"],
+//   "schema_files": [{ "path": "schemas/user.yaml", "models": ["User"], "error": null, "files": ["core/interfaces/User.ts"] }]
+// }
+// A file is created, modified, skipped when its content did not change, or preserved when it was customized.
+// Skipped files are never opened for writing, writes_avoided counts them. schema_files lists the files of
+// --schema-dir, empty for other runs, each with the files only its models wrote.
+#[derive(Debug)]
+struct RunReport {
+    command: String,
+    args: Vec<String>,
+    schema_hash: Option<String>,
+    json: bool,
+    started: Instant,
+    steps: Vec<(String, u128)>,
+    files: Vec<(PathBuf, &'static str, usize, String)>,
+    warnings: Vec<String>,
+    owners: Vec<(PathBuf, Vec<String>)>,
+    schema_files: Vec<(PathBuf, Vec<String>, Option<String>)>,
+    sequelize_files: Vec<PathBuf>
+}
+
+impl RunReport {
+    fn begin(command: &str, args: &[String], json: bool) {
+        *RUN_REPORT.lock().unwrap() = Some(Self {
+            command: command.to_string(),
+            args: args.to_vec(),
+            schema_hash: None,
+            json,
+            started: Instant::now(),
+            steps: Vec::new(),
+            files: Vec::new(),
+            warnings: Vec::new(),
+            owners: Vec::new(),
+            schema_files: Vec::new(),
+            sequelize_files: Vec::new(),
+        });
+    }
+
+    fn record(update: impl FnOnce(&mut Self)) {
+        if let Some(report) = RUN_REPORT.lock().unwrap().as_mut() {
+            update(report);
+        }
+    }
+
+    fn file(&mut self, file_path: &Path, action: &'static str, content: &str) {
+        self.files.push((file_path.to_path_buf(), action, content.len(), content_hash(content)));
+    }
+
+    // Files the run wrote, the ones created or modified.
+    fn written_files() -> Vec<PathBuf> {
+        RUN_REPORT.lock().unwrap().as_ref().map_or_else(Vec::new, |report| {
+            report.files
+                .iter()
+                .filter(|(_, action, _, _)| matches!(*action, "created" | "modified"))
+                .map(|(file_path, _, _, _)| file_path.clone())
+                .collect()
+        })
+    }
+
+    fn count(&self, action: &str) -> usize {
+        self.files.iter().filter(|(_, file_action, _, _)| *file_action == action).count()
+    }
+
+    // The --schema-dir file whose models alone wrote the file, None for files written for several files or none.
+    fn schema_file_of(&self, file_path: &Path) -> Option<usize> {
+        let (_, models) = self.owners.iter().find(|(path, _)| path == file_path)?;
+        let mut sources = models.iter().filter_map(|model| self.schema_files.iter().position(|(_, names, _)| names.contains(model)));
+        let first = sources.next()?;
+        sources.all(|source| source == first).then_some(first)
+    }
+
+    // The written files grouped by the --schema-dir file that produced them, the shared ones last.
+    fn print_schema_files(&self, root: &Path) {
+        let groups = self.schema_files.iter().enumerate().map(|(index, file)| (Some(index), Some(file))).chain([(None, None)]);
+        for (index, schema_file) in groups {
+            let files: Vec<&(PathBuf, &'static str, usize, String)> = self.files.iter().filter(|(file_path, _, _, _)| self.schema_file_of(file_path) == index).collect();
+            let heading = match schema_file {
+                Some((schema_path, _, Some(error))) => {
+                    status(&format!("{}: left out, {error}", schema_path.display()));
+                    continue;
+                }
+                Some((schema_path, models, None)) => format!("{} ({})", schema_path.display(), models.join(", ")),
+                None if files.is_empty() => continue,
+                None => "shared".to_string(),
+            };
+            let count = |action: &str| files.iter().filter(|(_, file_action, _, _)| *file_action == action).count();
+            status(&format!("{heading}: {} created, {} modified, {} unchanged, {} preserved", count("created"), count("modified"), count("skipped"), count("preserved")));
+            for (file_path, action, _, _) in files.iter().filter(|(_, action, _, _)| *action != "skipped") {
+                status(&format!("  {action:<9} {}", file_path.strip_prefix(root).unwrap_or(file_path).display()));
+            }
+        }
+    }
+
+    // What the run did to each of --sequelize-files, the last write of the file counts.
+    fn sequelize_file_actions(&self) -> Vec<(&Path, &'static str)> {
+        self.sequelize_files
+            .iter()
+            .map(|sequelize_path| {
+                let action = self.files.iter().rev().find(|(file_path, _, _, _)| file_path == sequelize_path).map_or("untouched", |(_, action, _, _)| *action);
+                (sequelize_path.as_path(), if action == "skipped" { "unchanged" } else { action })
+            })
+            .collect()
+    }
+
+    fn to_json(&self, root: &Path, error: Option<&str>) -> Value {
+        let files: Vec<Value> = self.files
+            .iter()
+            .map(|(file_path, action, bytes, hash)| {
+                let path = file_path.strip_prefix(root).unwrap_or(file_path).to_string_lossy().replace('\\', "/");
+                json!({ "path": path, "action": action, "bytes": bytes, "hash": hash })
+            })
+            .collect();
+        let schema_files: Vec<Value> = self.schema_files
+            .iter()
+            .enumerate()
+            .map(|(index, (schema_path, models, error))| {
+                let files: Vec<String> = self.files
+                    .iter()
+                    .filter(|(file_path, _, _, _)| self.schema_file_of(file_path) == Some(index))
+                    .map(|(file_path, _, _, _)| file_path.strip_prefix(root).unwrap_or(file_path).to_string_lossy().replace('\\', "/"))
+                    .collect();
+                json!({ "path": schema_path.to_string_lossy().replace('\\', "/"), "models": models, "error": error, "files": files })
+            })
+            .collect();
+
+        json!({
+            "report_version": REPORT_VERSION,
+            "command": self.command,
+            "args": self.args,
+            "schema_hash": self.schema_hash,
+            "status": if error.is_some() { "failed" } else { "succeeded" },
+            "error": error,
+            "duration_ms": self.started.elapsed().as_millis(),
+            "steps": self.steps.iter().map(|(name, duration)| json!({ "name": name, "duration_ms": duration })).collect::<Vec<_>>(),
+            "files": files,
+            "writes_avoided": self.count("skipped"),
+            "warnings": self.warnings,
+            "schema_files": schema_files,
+            "sequelize_files": self
+                .sequelize_file_actions()
+                .iter()
+                .map(|(file_path, action)| json!({ "path": file_path.strip_prefix(root).unwrap_or(file_path).to_string_lossy().replace('\\', "/"), "action": action }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    // Writes the report even for a run that failed partway, with the failure recorded.
+    fn finish(root: &Path, error: Option<&str>) {
+        let Some(report) = RUN_REPORT.lock().unwrap().take() else {
+            return;
+        };
+
+        let content = report.to_json(root, error);
+        let report_path = root.join(REPORT_FILE);
+        let written = fs::create_dir_all(report_path.parent().unwrap_or(root))
+            .and_then(|_| atomic_write(&report_path, format!("{content:#}\n").as_bytes()));
+        if let Err(error) = written {
+            status(&format!("Warning: could not write {}: {error}", report_path.display()));
+        }
+
+        if report.json {
+            println!("{content}");
+            return;
+        }
+        if !report.schema_files.is_empty() {
+            report.print_schema_files(root);
+        }
+        for (file_path, action) in report.sequelize_file_actions() {
+            status(&format!("{action:<9} {}", file_path.strip_prefix(root).unwrap_or(file_path).display()));
+        }
+        let outcome = if error.is_some() { "failed" } else { "done" };
+        status(&format!(
+            "Crudify {} {outcome} in {}ms: {} created, {} modified, {} unchanged, {} preserved, {} warnings",
+            report.command,
+            report.started.elapsed().as_millis(),
+            report.count("created"),
+            report.count("modified"),
+            report.count("skipped"),
+            report.count("preserved"),
+            report.warnings.len()
+        ));
+    }
+}
+
+// Runs one step of the run and records how long it took.
+fn step<T>(name: &str, run: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = run();
+    RunReport::record(|report| report.steps.push((name.to_string(), started.elapsed().as_millis())));
+    result
+}
+
+// Reports why the run stopped before exiting.
+fn fail(context: &str, error: impl std::fmt::Display, root: &Path) -> ! {
+    let message = format!("Error in {context}: {error}");
+    status(&message);
+    RunReport::finish(root, Some(&message));
+    RunLock::release();
+    process::exit(1);
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+// Writes a .tmp sibling and renames it over the target, so a crash never
+// leaves a half-written file behind.
+fn atomic_write(path: &Path, content: &[u8]) -> io::Result<()> {
+    let temporary = temporary_path(path);
+    let result = File::create(&temporary).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    let result = result.and_then(|_| fs::rename(&temporary, path));
+    if result.is_err() {
+        fs::remove_file(&temporary).ok();
+    }
+    result
+}
+
+const LOCK_FILE: &str = ".crudify/lock";
+const DEFAULT_LOCK_TIMEOUT: i64 = 30;
+
+static HELD_LOCK: OnceLock<PathBuf> = OnceLock::new();
+
+// Keeps two runs from reading and rewriting the shared files (sequelize.ts, the routes index) at once.
+// The lock holds "pid timestamp", one left by a process that is gone is taken over.
+struct RunLock;
+
+impl RunLock {
+    fn acquire(root: &Path, timeout: Duration) -> Result<Self, String> {
+        let lock_path = root.join(LOCK_FILE);
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent).map_err(|error| format!("{}: {error}", parent.display()))?;
+        }
+
+        let started = Instant::now();
+        loop {
+            match File::options().write(true).create_new(true).open(&lock_path) {
+                Ok(mut file) => {
+                    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+                    file.write_all(format!("{} {timestamp}\n", process::id()).as_bytes()).map_err(|error| format!("{}: {error}", lock_path.display()))?;
+                    HELD_LOCK.set(lock_path.clone()).ok();
+                    release_on_interrupt(&lock_path);
+                    return Ok(Self);
+                }
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(format!("{}: {error}", lock_path.display())),
+            }
+
+            let holder = fs::read_to_string(&lock_path).unwrap_or_default();
+            let pid = holder.split_whitespace().next().and_then(|pid| pid.parse::<u32>().ok());
+            if let Some(pid) = pid.filter(|pid| !process_alive(*pid)) {
+                warn(format!("removing the stale {} left by pid {pid}", lock_path.display()));
+                fs::remove_file(&lock_path).ok();
+                continue;
+            }
+            if started.elapsed() >= timeout {
+                let holder = pid.map_or_else(|| "an unknown process".to_string(), |pid| format!("pid {pid}"));
+                return Err(format!(
+                    "{holder} still holds {} after {}s, remove it if no generation is running",
+                    lock_path.display(),
+                    timeout.as_secs()
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    // process::exit skips the drop, so fail releases the lock through here too.
+    fn release() {
+        let Some(lock_path) = HELD_LOCK.get() else {
+            return;
+        };
+        let owned = fs::read_to_string(lock_path)
+            .is_ok_and(|holder| holder.split_whitespace().next() == Some(process::id().to_string().as_str()));
+        if owned {
+            fs::remove_file(lock_path).ok();
+        }
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        Self::release();
+    }
+}
+
+// Asks the system whether the pid still runs, an unanswered question counts as alive.
+fn process_alive(pid: u32) -> bool {
+    let pid = pid.to_string();
+    let output = if cfg!(windows) {
+        process::Command::new("tasklist").args(["/FI", &format!("PID eq {pid}"), "/NH"]).output()
+    } else {
+        process::Command::new("kill").args(["-0", &pid]).output()
+    };
+    output.map_or(true, |output| {
+        if cfg!(windows) {
+            String::from_utf8_lossy(&output.stdout).split_whitespace().any(|word| word == pid)
+        } else {
+            output.status.success()
+        }
+    })
+}
+
+// Ctrl-C and SIGTERM never unwind to the drop, so a handler unlinks the lock before exiting.
+#[cfg(unix)]
+fn release_on_interrupt(lock_path: &Path) {
+    use std::ffi::{c_char, c_int, CString};
+    use std::os::unix::ffi::OsStrExt;
+
+    static INTERRUPT_LOCK: OnceLock<CString> = OnceLock::new();
+
+    extern "C" {
+        fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+        fn unlink(path: *const c_char) -> c_int;
+        fn _exit(status: c_int) -> !;
+    }
+
+    extern "C" fn interrupted(signum: c_int) {
+        if let Some(lock_path) = INTERRUPT_LOCK.get() {
+            unsafe { unlink(lock_path.as_ptr()) };
+        }
+        unsafe { _exit(128 + signum) }
+    }
+
+    const SIGINT: c_int = 2;
+    const SIGTERM: c_int = 15;
+    let Ok(lock_path) = CString::new(lock_path.as_os_str().as_bytes()) else {
+        return;
+    };
+    if INTERRUPT_LOCK.set(lock_path).is_ok() {
+        unsafe {
+            signal(SIGINT, interrupted);
+            signal(SIGTERM, interrupted);
+        }
+    }
+}
+
+// Without a handler here an interrupted run leaves the lock, which the next run finds stale.
+#[cfg(not(unix))]
+fn release_on_interrupt(_lock_path: &Path) {}
+
+fn comment_prefix(file_path: &Path) -> &'static str {
+    match file_path.extension().and_then(|extension| extension.to_str()) {
+        // tsconfig.json, the only JSON project file, is read as JSON with comments.
+        Some("ts") | Some("js") | Some("prisma") | Some("json") => "// ",
+        _ => "# ",
+    }
+}
+
+// Once-per-project files carry a hash of their generated body, so a rerun only
+// overwrites them while they are still untouched.
+fn write_project_file(file_path: &Path, content: &str) -> io::Result<()> {
+    let marker = format!("{}{HASH_MARKER}", comment_prefix(file_path));
+    if file_exists(file_path) {
+        let existing = read_file(file_path)?;
+        let untouched = existing
+            .split_once('\n')
+            .and_then(|(header, body)| header.strip_prefix(marker.as_str()).map(|hash| hash == content_hash(body)))
+            .unwrap_or(false);
+
+        if !untouched {
+            status(&format!("Keeping customized {}", file_path.display()));
+            RunReport::record(|report| report.file(file_path, "preserved", &existing));
+            return Ok(());
+        }
+    }
+
+    let content = source_content(&output_style().source_path(file_path), content);
+    write_file(file_path, &format!("{marker}{}\n{content}", content_hash(&content)))
+}
+
+fn implement_interface(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_name = naming().file_name("interface", model);
+    let file_path = path.join(file_name);
+
+    let content: String = copy_template(&template_key("INTERFACE_REPOSITORY_TEMPLATE"), None, model, templates)?;
+    if options.entities.is_some() && !content.contains("{ENTITY_TYPE}") {
+        warn(format!("interface template of {} has no {{ENTITY_TYPE}}, its methods stay typed against the model", model.name));
+    }
+    let mut content = apply_tenant(&apply_query_options(&apply_key_signatures(&apply_entities(&content, model, options), model), model), model, "interface");
+    // The interface is gone at runtime, inversify binds and injects the repository by a symbol of the same name.
+    let token = format!("export const I{}Repository = ", model.name);
+    if options.di == "inversify" && !content.contains(&token) {
+        content = format!("{}
+
+{token}Symbol.for(\"I{}Repository\");
+", content.trim_end(), model.name);
+    }
+    if !content.contains(&format!("{}QueryOptions", model.name)) && !output_style().is_js() {
+        warn(format!("interface template of {} has no findAll(), {}QueryOptions is left out of it", model.name, model.name));
+    }
+
+    write_file(&file_path, &content)?;
+
+    if options.abstract_base {
+        let abstract_path = path.join(format!("Abstract{}Repository.ts", model.name));
+        let content = copy_template(
+            &template_key("ABSTRACT_REPOSITORY_TEMPLATE"),
+            Some(builtin(ABSTRACT_REPOSITORY_TEMPLATE, ABSTRACT_REPOSITORY_JS_TEMPLATE)),
+            model,
+            templates,
+        )?;
+        write_file(&abstract_path, &apply_entities(&content, model, options))?;
+    }
+
+    Ok(())
+}
+
+// Replaces the placeholder with a multi-line value, each line after the first indented like the placeholder.
+fn replace_indented(content: &str, placeholder: &str, value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = content;
+    while let Some(index) = rest.find(placeholder) {
+        let line_start = rest[..index].rfind('\n').map_or(0, |newline| newline + 1);
+        let indent = &rest[line_start..index];
+        let indent = if indent.trim().is_empty() { indent } else { "" };
+        result.push_str(&rest[..index]);
+        result.push_str(&value.replace('\n', &format!("\n{indent}")));
+        rest = &rest[index + placeholder.len()..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+// {DYNAMIC_ADD_PROPERTIES}, the stored fields copied from the request, shared by the add use case and the service.
+fn dynamic_add_properties(model: &Model) -> String {
+    let stored: Vec<Field> = model.fields.iter().filter(|field| !field.is_virtual() && model.is_supplied(field)).cloned().collect();
+    process_loops("{FOREACH field}{FIELD_NAME}: request.{FIELD_NAME},\n{/FOREACH}", &stored)
+        .trim_end()
+        .to_string()
+}
+
+// {DYNAMIC_UPDATE_PROPERTIES}, the stored fields assigned from the request.
+fn dynamic_update_properties(model: &Model) -> String {
+    let stored: Vec<Field> = model.fields.iter().filter(|field| !field.is_virtual() && !model.is_tenant(field)).cloned().collect();
+    let update_loop = format!("{{FOREACH field WHERE !@PrimaryKey}}{}.{{FIELD_NAME}} = request.{{FIELD_NAME}};\n{{/FOREACH}}", model.name.to_lowercase());
+    process_loops(&update_loop, &stored).trim_end().to_string()
+}
+
+// The {DYNAMIC_TRANSACTION_*} placeholders, a withTransaction block for models with relations,
+// `argument` being how the transaction is handed to the write.
+fn transaction_placeholders(model: &Model, argument: &'static str) -> [(&'static str, &'static str); 4] {
+    let relations = model.has_relations();
+    let value = |value: &'static str| if relations { value } else { "" };
+    [
+        ("{DYNAMIC_TRANSACTION_IMPORT}", value("import { withTransaction } from \"@infrastructure/config/unitOfWork\";")),
+        ("{DYNAMIC_TRANSACTION_BEGIN}", value("return withTransaction(async (transaction) => {")),
+        ("{DYNAMIC_TRANSACTION_END}", value("});")),
+        ("{DYNAMIC_TRANSACTION_ARGUMENT}", value(argument)),
+    ]
+}
+
+// The eventBus and the types of `events` with the payload mapper, JavaScript events are untyped.
+fn events_import(model: &Model, events: &[&str]) -> String {
+    let mut imported: Vec<String> = match output_style().is_js() {
+        true => Vec::new(),
+        false => events.iter().map(|event| format!("{}{event}", model.name)).collect(),
+    };
+    imported.push(format!("to{}Payload", model.name));
+    format!("{}\nimport {{ {} }} from \"@core/events/{}\";", event_bus_import(), imported.join(", "), naming().module("events", model))
+}
+
+fn publish_event(model: &Model, event: &str) -> String {
+    let event_type = format!("{}{event}", model.name);
+    let payload = if event == "Deleted" {
+        format!("{{ {} }}", model.primary_key().name)
+    } else {
+        format!("to{}Payload({})", model.name, model.name.to_lowercase())
+    };
+    let type_argument = if output_style().is_js() { String::new() } else { format!("<{event_type}>") };
+    format!("await eventBus.publish{type_argument}(\"{event_type}\", {payload});")
+}
+
+// The CRUD operations of --style minimal in one class, built from the same blocks as the use cases.
+fn implement_service(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(format!("{}Service.ts", model.name.to_lowercase()));
+
+    let service_template_key = template_key("SERVICE_TEMPLATE");
+    let content = copy_template(&service_template_key, Some(builtin(SERVICE_TEMPLATE, SERVICE_JS_TEMPLATE)), model, templates)?;
+    let content = replace_indented(&content, "{DYNAMIC_ADD_PROPERTIES}", &dynamic_add_properties(model));
+    let mut content = replace_indented(&content, "{DYNAMIC_UPDATE_PROPERTIES}", &dynamic_update_properties(model))
+        .replace("{PRIMARY_KEY_TYPE}", &model.primary_key().js_type)
+        .replace("{PRIMARY_KEY}", &model.primary_key().name);
+    for (placeholder, value) in transaction_placeholders(model, ", { transaction }") {
+        content = content.replace(placeholder, value);
+    }
+
+    let events = ["Created", "Updated", "Deleted"];
+    content = content.replace("{DYNAMIC_EVENTS_IMPORT}", &if options.events { events_import(model, &events) } else { String::new() });
+    for event in events {
+        let publish = if options.events { publish_event(model, event) } else { String::new() };
+        content = content.replace(&format!("{{DYNAMIC_PUBLISH_{}}}", event.to_uppercase()), &publish);
+    }
+    check_rendered(&service_template_key, &content)?;
+
+    write_file(&file_path, &content)
+}
+
+fn implement_use_case(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let new_path = path.join(model.name.as_str());
+
+    create_output_dir(&new_path).expect("Problem creating folder for use_case");
+
+    let add_path = new_path.join(naming().file_name("add_use_case", model));
+    let gets_path = new_path.join(naming().file_name("gets_use_case", model));
+    let delete_path = new_path.join(naming().file_name("delete_use_case", model));
+    let update_path = new_path.join(naming().file_name("update_use_case", model));
+
+    let [add_template_key, gets_template_key, delete_template_key, update_template_key] =
+        ["ADD_USE_CASE_TEMPLATE", "GETS_USE_CASE_TEMPLATE", "DELETE_USE_CASE_TEMPLATE", "UPDATE_USE_CASE_TEMPLATE"].map(template_key);
+
+    let mut add_content = apply_entities(&apply_di(&copy_template(&add_template_key, None, model, templates)?, model, options), model, options);
+    let mut gets_content = apply_entities(&apply_di(&copy_template(&gets_template_key, None, model, templates)?, model, options), model, options);
+    gets_content = apply_query_options(&gets_content, model);
+    let mut delete_content = apply_entities(&apply_di(&copy_template(&delete_template_key, None, model, templates)?, model, options), model, options);
+    let mut update_content = apply_entities(&apply_di(&copy_template(&update_template_key, None, model, templates)?, model, options), model, options);
+
+    add_content = replace_indented(&add_content, "{DYNAMIC_ADD_PROPERTIES}", &dynamic_add_properties(model));
+    for (placeholder, value) in transaction_placeholders(model, ", transaction") {
+        add_content = add_content.replace(placeholder, value);
+    }
+    update_content = replace_indented(&update_content, "{DYNAMIC_UPDATE_PROPERTIES}", &dynamic_update_properties(model));
+    delete_content = apply_key_signatures(&delete_content, model);
+    update_content = apply_key_signatures(&update_content, model);
+
+    let events = ["Created", "Updated", "Deleted"].map(|event| match options.events {
+        true => (events_import(model, &[event]), publish_event(model, event)),
+        false => (String::new(), String::new()),
+    });
+    let [(add_events_import, add_publish), (update_events_import, update_publish), (delete_events_import, delete_publish)] = events;
+    add_content = add_content
+        .replace("{DYNAMIC_EVENTS_IMPORT}", &add_events_import)
+        .replace("{DYNAMIC_PUBLISH_EVENT}", &add_publish);
+    update_content = update_content
+        .replace("{DYNAMIC_EVENTS_IMPORT}", &update_events_import)
+        .replace("{DYNAMIC_PUBLISH_EVENT}", &update_publish);
+    delete_content = delete_content
+        .replace("{DYNAMIC_EVENTS_IMPORT}", &delete_events_import)
+        .replace("{DYNAMIC_PUBLISH_EVENT}", &delete_publish);
+
+    for content in [&mut add_content, &mut gets_content, &mut delete_content, &mut update_content] {
+        *content = apply_tenant(content, model, "use_case");
+    }
+
+    check_rendered(&add_template_key, &add_content)?;
+    check_rendered(&delete_template_key, &delete_content)?;
+    check_rendered(&update_template_key, &update_content)?;
+
+    if options.di == "tsyringe" {
+        for content in [&mut add_content, &mut gets_content, &mut delete_content, &mut update_content] {
+            *content = apply_tsyringe_decorators(content, model);
+        }
+    }
+    if options.abstract_base {
+        let base = format!("Abstract{}UseCase", model.name);
+        let content = copy_template(
+            &template_key("ABSTRACT_USE_CASE_TEMPLATE"),
+            Some(builtin(ABSTRACT_USE_CASE_TEMPLATE, ABSTRACT_USE_CASE_JS_TEMPLATE)),
+            model,
+            templates,
+        )?;
+        write_file(&new_path.join(format!("{base}.ts")), &content)?;
+
+        let import = format!("import {{ {base} }} from \"@core/use_cases/{}/{base}\";", model.name);
+        for (content, key) in [
+            (&mut add_content, &add_template_key),
+            (&mut gets_content, &gets_template_key),
+            (&mut delete_content, &delete_template_key),
+            (&mut update_content, &update_template_key),
+        ] {
+            *content = apply_abstract_base(content, &base, &import, key);
+        }
+    }
+
+    if !model.is_empty() {
+        write_file(&add_path, &add_content)?;
+        write_file(&update_path, &update_content)?;
+    }
+    write_file(&gets_path, &gets_content)?;
+    write_file(&delete_path, &delete_content)?;
+
+    Ok(())
+}
+
+fn abstract_repository_import(model: &Model) -> String {
+    format!("import {{ Abstract{0}Repository }} from \"@core/interfaces/Abstract{0}Repository\";", model.name)
+}
+
+// Makes the first exported class extend the --abstract-base class, its constructor calls super() first.
+// Classes that extend something already are left alone with a warning.
+fn apply_abstract_base(content: &str, base: &str, import: &str, env_key: &str) -> String {
+    let class = Regex::new(r"(?m)^(?:export\s+)?class\s+\w+(\s+extends\b)?").unwrap();
+    let Some(captures) = class.captures(content) else {
+        warn(format!("{} has no class to extend {base}", template_source(env_key)));
+        return content.to_string();
+    };
+    if captures.get(1).is_some() {
+        warn(format!("{} has a class that extends another one already, {base} is left out", template_source(env_key)));
+        return content.to_string();
+    }
+    let declaration = captures.get(0).unwrap().end();
+    let mut content = format!("{} extends {base}{}", &content[..declaration], &content[declaration..]);
+
+    // The parameters are skipped by their parentheses, decorators such as @inject(...) included.
+    if let Some(start) = content.find("constructor(") {
+        let mut depth = 0;
+        let close = content[start..].char_indices().find_map(|(offset, character)| {
+            match character {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            (character == ')' && depth == 0).then_some(start + offset)
+        });
+        let body = close.and_then(|close| {
+            let after = &content[close + 1..];
+            after.trim_start().starts_with('{').then(|| close + 1 + after.len() - after.trim_start().len())
+        });
+        if let Some(open) = body {
+            let line_start = content[..start].rfind('\n').map_or(0, |index| index + 1);
+            let indent = content[line_start..start].to_string();
+            let rest = content[open + 1..].trim_start_matches([' ', '\t']);
+            content = match rest.strip_prefix('}') {
+                Some(rest) => format!("{}{{\n{indent}{indent}super();\n{indent}}}{rest}", &content[..open]),
+                None => format!("{}{{\n{indent}{indent}super();{}", &content[..open], &content[open + 1..]),
+            };
+        }
+    }
+
+    insert_import(&content, import)
+}
+
+fn event_bus_import() -> String {
+    let module = env::var("EVENT_BUS_MODULE").unwrap_or_else(|_| "@core/events/EventBus".to_string());
+    format!("import {{ eventBus }} from \"{module}\";")
+}
+
+fn implement_events(path: PathBuf, model: &Model, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(naming().file_name("events", model));
+
+    let visible_fields: Vec<&Field> = model.fields.iter().filter(|field| !field.has_attr("@Hidden")).collect();
+    let dynamic_event_payload = visible_fields
+        .iter()
+        .map(|field| if output_style().is_js() {
+            format!(" * @property {{{}}} {}", jsdoc_type(&field.js_type), field.name)
+        } else {
+            format!("\t{}: {};", field.name, field.js_type)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let dynamic_event_mapping = visible_fields
+        .iter()
+        .map(|field| format!("\t{}: source.{},", field.name, field.name))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let events_template_key = template_key("EVENTS_TEMPLATE");
+    let content = copy_template(&events_template_key, Some(builtin(EVENTS_TEMPLATE, EVENTS_JS_TEMPLATE)), model, templates)?
+    .replace("{DYNAMIC_EVENT_PAYLOAD}", &dynamic_event_payload)
+    .replace("{DYNAMIC_EVENT_MAPPING}", &dynamic_event_mapping)
+    .replace("{PRIMARY_KEY}", &model.primary_key().name)
+    .replace("{PRIMARY_KEY_TYPE}", &model.primary_key().js_type);
+    check_rendered(&events_template_key, &content)?;
+
+    write_file(&file_path, &content)?;
+
+    Ok(())
+}
+
+fn implement_event_bus(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    // Projects that bring their own bus point EVENT_BUS_MODULE at it instead.
+    if env::var("EVENT_BUS_MODULE").is_err() {
+        let bus = template_or_default(&template_key("EVENT_BUS_TEMPLATE"), builtin(EVENT_BUS_TEMPLATE, EVENT_BUS_JS_TEMPLATE), templates)?;
+        write_project_file(&path.join("EventBus.ts"), &bus)?;
+    }
+
+    Ok(())
+}
+
+fn implement_utils(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let properties = &model.fields;
+    let new_path = path.join(model.name.as_str());
+
+    create_output_dir(&new_path).expect("Problem creating folder for use_case");
+
+    let request_content = apply_key_requests(&copy_template(&template_key("REQUEST_UTILS_TEMPLATE"), None, model, templates)?, model);
+    check_rendered(&template_key("REQUEST_UTILS_TEMPLATE"), &request_content)?;
+    let mut types_content = copy_template(&template_key("TYPES_UTILS_TEMPLATE"), None, model, templates)?;
+
+    let doc = |property: &Field| if options.field_docs { format!("{}\t", property.to_jsdoc("\t").trim_start()) } else { String::new() };
+    let mut dynamic_properties_attributes = match options.field_docs {
+        true => properties.iter().map(|property| format!("{}{}: {};", doc(property), property.name, property.property_type())).collect::<Vec<_>>().join("\n\t"),
+        false => process_loops("{FOREACH field}{FIELD_NAME}: {FIELD_JS_TYPE};\n\t{/FOREACH}", properties),
+    }
+    .trim()
+    .to_string();
+
+    // Details are the request payload, VIRTUAL fields are computed and only appear in the attributes.
+    let mut dynamic_properties_details = properties
+        .iter()
+        .filter_map(|property| match property.db_type.as_str() {
+            "FILE" => Some(format!("{}{}?: {}; // populated by the server from the uploaded file", doc(property), property.name, property.js_type)),
+            "VIRTUAL" => None,
+            _ if !model.is_supplied(property) => None,
+            _ => Some(format!("{}{}: {};", doc(property), property.name, property.property_type())),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\t");
+
+    // JavaScript templates describe the shapes as JSDoc typedefs.
+    // --field-docs puts the description after the typedef property.
+    if output_style().is_js() {
+        let described = |property: &Field, line: String| match property.description().filter(|_| options.field_docs) {
+            Some(description) => format!("{line} {}", jsdoc_text(&description).replace('\n', " ")),
+            None => line,
+        };
+        dynamic_properties_attributes = properties
+            .iter()
+            .map(|property| described(property, property.to_jsdoc_property()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        dynamic_properties_details = properties
+            .iter()
+            .filter_map(|property| match property.db_type.as_str() {
+                "FILE" => Some(format!(" * @property {{string}} [{}] populated by the server from the uploaded file", property.name)),
+                "VIRTUAL" => None,
+                _ if !model.is_supplied(property) => None,
+                _ => Some(described(property, property.to_jsdoc_property())),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    // Templates without the placeholder get the shape interfaces at the top.
+    let shape_declarations = model.to_shape_declarations();
+    if types_content.contains("{DYNAMIC_SHAPE_INTERFACES}") {
+        types_content = types_content.replace("{DYNAMIC_SHAPE_INTERFACES}", &shape_declarations);
+    } else if !shape_declarations.is_empty() {
+        types_content = format!("{shape_declarations}\n\n{types_content}");
+    }
+
+    // JavaScript types spell the enum values out instead.
+    if !model.enum_names().is_empty() && !output_style().is_js() {
+        types_content = insert_import(&types_content, &enum_import(&model.enum_names()));
+    }
+
+    types_content = types_content.replace("{DYNAMIC_PROPERTIES_ATTRIBUTES}", &dynamic_properties_attributes);
+    types_content = types_content.replace("{DYNAMIC_PROPERTIES_DETAILS}", &dynamic_properties_details);
+
+    // A composite key is looked up with an object of all its fields.
+    if model.has_composite_key() {
+        let key_type = match output_style().is_js() {
+            true => {
+                let fields = model.primary_keys().iter().map(|field| format!("{}: {}", field.name, field.js_type)).collect::<Vec<_>>();
+                format!("/** @typedef {{{{ {} }}}} {}Key */", fields.join(", "), model.name)
+            }
+            false => {
+                let fields = model.primary_keys().iter().map(|field| format!("{}: {};", field.name, field.js_type)).collect::<Vec<_>>();
+                format!("export type {}Key = {{ {} }};", model.name, fields.join(" "))
+            }
+        };
+        types_content = format!("{}\n\n{key_type}\n", types_content.trim_end());
+    }
+
+    // Templates without the placeholder get the query options at the end.
+    let query_options = model.to_query_options();
+    if types_content.contains("{DYNAMIC_QUERY_OPTIONS}") {
+        types_content = types_content.replace("{DYNAMIC_QUERY_OPTIONS}", &query_options);
+    } else {
+        types_content = format!("{}\n\n{query_options}\n", types_content.trim_end());
+    }
+
+    check_rendered(&template_key("TYPES_UTILS_TEMPLATE"), &types_content)?;
+
+    let (request_content, types_content) = match options.types_as_interface {
+        true => (to_interfaces(&request_content), to_interfaces(&types_content)),
+        false => (request_content, types_content),
+    };
+    let (request_path, types_path) = (new_path.join("Request.ts"), new_path.join("types.ts"));
+    let (request_content, types_content) = match options.namespace_declarations {
+        true => (to_namespace(&request_content, &request_path, &model.name), to_namespace(&types_content, &types_path, &model.name)),
+        false => (request_content, types_content),
+    };
+    // The requests are only used by the Add and Update use cases.
+    if !model.is_empty() {
+        write_file(&request_path, &request_content)?;
+    }
+    write_file(&types_path, &types_content)?;
+
+    Ok(())
+}
+
+fn implement_zod_schema(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let new_path = path.join(model.name.as_str());
+
+    create_output_dir(&new_path).expect("Problem creating folder for validation");
+
+    let mut content = copy_template(&template_key("ZOD_SCHEMA_TEMPLATE"), Some(builtin(ZOD_SCHEMA_TEMPLATE, ZOD_SCHEMA_JS_TEMPLATE)), model, templates)?
+    .replace("{ZOD_SCHEMA_BODY}", &model.to_zod_schema(&options.dialect));
+    if !model.enum_names().is_empty() {
+        content = insert_import(&content, &enum_import(&model.enum_names()));
+    }
+
+    check_rendered(&template_key("ZOD_SCHEMA_TEMPLATE"), &content)?;
+
+    write_file(&new_path.join("validation.ts"), &content)?;
+
+    Ok(())
+}
+
+fn implement_repository(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(naming().file_name("repository", model));
+
+    let repository_template_key = template_key("REPOSITORY_TEMPLATE");
+    let mut content = apply_entities(&apply_di(&copy_template(&repository_template_key, None, model, templates)?, model, options), model, options);
+    if options.di == "tsyringe" {
+        content = apply_tsyringe_decorators(&content, model);
+    }
+    if options.abstract_base {
+        content = apply_abstract_base(&content, &format!("Abstract{}Repository", model.name), &abstract_repository_import(model), &repository_template_key);
+    }
+    content = apply_tenant(&apply_query_options(&apply_key_signatures(&content, model), model), model, "repository");
+
+    write_file(&file_path, &content)?;
+
+    Ok(())
+}
+
+fn table_option_regex() -> &'static Regex {
+    static TABLE_OPTION: OnceLock<Regex> = OnceLock::new();
+    TABLE_OPTION.get_or_init(|| Regex::new(r#"tableName:\s*"[^"]*""#).unwrap())
+}
+
+// Fills {TABLE_OPTIONS}, or puts the options next to the template's tableName. Either way the table is
+// the one the migration creates, so a template's own tableName gives way to Model::table_name.
+fn apply_table_options(content: &str, model: &Model, table_options: Vec<String>) -> String {
+    let entries: Vec<String> = std::iter::once(format!("tableName: \"{}\"", model.table_name())).chain(table_options).collect();
+    match content.contains("{TABLE_OPTIONS}") {
+        true => content.replace("{TABLE_OPTIONS}", &format!("{{ {} }}", entries.join(", "))),
+        false => table_option_regex().replace(content, regex::NoExpand(&entries.join(", "))).into_owned(),
+    }
+}
+
+// fn implement_controllers(path: PathBuf);
+fn implement_model(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_name = naming().file_name("model", model);
+    let file_path = path.join(file_name);
+
+    // Plain JavaScript has no decorators, its models default to Model.init.
+    let default = output_style().is_js().then_some(MODEL_JS_TEMPLATE);
+    let mut content: String = copy_template(&template_key("MODEL_TEMPLATE"), default, model, templates)?;
+
+    // JavaScript models have no decorators, their indexes always go in the model options.
+    // Templates without {TABLE_OPTIONS} get the options next to their tableName.
+    let has_table_options = content.contains("{TABLE_OPTIONS}") || table_option_regex().is_match(&content);
+    let mut table_indexes = !model.indexes().is_empty() && (output_style().is_js() || options.index_style == "table");
+    if table_indexes && !content.contains("{TABLE_INDEXES}") && !has_table_options {
+        warn(format!("model template of {} has no tableName option or {{TABLE_INDEXES}} to declare the indexes in", model.name));
+        table_indexes = false;
+    }
+    if !has_table_options && !model.table_options().is_empty() {
+        warn(format!("model template of {} has no tableName option or {{TABLE_OPTIONS}}, its table options are left out", model.name));
+    }
+
+    let mut table_options = model.table_options();
+    if table_indexes {
+        match content.contains("{TABLE_INDEXES}") {
+            true => content = content.replace("{TABLE_INDEXES}", &model.to_table_indexes()),
+            false => table_options.push(model.to_table_indexes()),
+        }
+    }
+    content = content.replace("{TABLE_INDEXES}", "");
+
+    content = apply_table_options(&content, model, table_options);
+
+    let properties = match output_style().is_js() {
+        true => model.to_sequelize_attributes(&options.dialect, options.field_docs),
+        false => model.to_sequelize_class(table_indexes, options.field_docs),
+    };
+    let indent = if output_style().is_js() { "\t\t\t" } else { "\t" };
+    content = content.replace("{DYNAMIC_PROPERTIES}", &format!("{indent}// {FIELDS_START}\n{properties}\n{indent}// {FIELDS_END}"));
+    if content.contains("{DYNAMIC_IMPORTS}") {
+        content = content.replace("{DYNAMIC_IMPORTS}", &model_imports(&content, model));
+    }
+
+    let shapes = interface_names(&model.fields.iter().collect::<Vec<_>>());
+    if !shapes.is_empty() && !output_style().is_js() {
+        content = insert_import(&content, &format!("import {{ {} }} from \"@core/utils/{}/types\";", shapes.join(", "), model.name));
+    }
+    if !model.enum_names().is_empty() {
+        content = insert_import(&content, &enum_import(&model.enum_names()));
+    }
+    if model.has_encrypted() {
+        content = insert_import(&content, "import { encrypt, decrypt } from \"@infrastructure/config/crypto\";");
+    }
+    if !output_style().is_js() {
+        for related in model.related_models() {
+            let import = format!("import {{ {} }} from \"@infrastructure/models/{}\";", related.name, naming().module("model", related));
+            if !content.contains(&import) {
+                content = insert_import(&content, &import);
+            }
+        }
+    }
+
+    check_rendered(&template_key("MODEL_TEMPLATE"), &content)?;
+    if file_exists(&file_path) {
+        let existing = read_file(&file_path)?;
+        match merge_model_fields(&existing, &content, model) {
+            Some(merged) => content = merged,
+            None => {
+                let source_path = output_style().source_path(&file_path);
+                let backup = backup_path(&source_path);
+                fs::create_dir_all(backup.parent().unwrap_or(Path::new(".")))?;
+                atomic_write(&backup, existing.as_bytes())?;
+                notice(&format!("the generated fields of {} could not be told apart, it is overwritten and the previous version kept in {}", source_path.display(), backup.display()));
+            }
+        }
+        content = preserve_keep_blocks(&existing, &content);
+    }
+
+    write_file(&file_path, &content)?;
+
+    Ok(())
+}
+
+// The import lines of {DYNAMIC_IMPORTS}, the ORM names the rendered model uses, sorted, and the models it
+// relates to. Templates without the placeholder keep their own import line.
+fn model_imports(content: &str, model: &Model) -> String {
+    let body: String = content
+        .replace("{DYNAMIC_IMPORTS}", "")
+        .lines()
+        .filter(|line| !line.starts_with("import "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (package, candidates): (&str, &[&str]) = match output_style().is_js() {
+        true => ("sequelize", &["DataTypes", "Model", "Op", "Sequelize"]),
+        false => ("sequelize-typescript", &["DataType", "Model"]),
+    };
+
+    let mut names: Vec<String> = candidates
+        .iter()
+        .filter(|name| Regex::new(&format!(r"\b{name}\b")).unwrap().is_match(&body))
+        .map(|name| name.to_string())
+        .collect();
+    // Decorators and validators alike come from sequelize-typescript.
+    if !output_style().is_js() {
+        names.extend(Regex::new(r"@([A-Z]\w*)").unwrap().captures_iter(&body).map(|captures| captures[1].to_string()));
+    }
+    names.sort();
+    names.dedup();
+
+    let mut imports = vec![format!("import {{ {} }} from \"{package}\";", names.join(", "))];
+    if !output_style().is_js() {
+        let mut related = model.related_models();
+        related.sort_by(|left, right| left.name.cmp(&right.name));
+        imports.extend(related.iter().map(|related| format!("import {{ {} }} from \"@infrastructure/models/{}\";", related.name, naming().module("model", related))));
+    }
+    imports.join("\n")
+}
+
+// Under --style minimal the service takes the place of the repository.
+fn repository_wiring(model: &Model, options: &Options) -> (String, String) {
+    if options.is_minimal() {
+        let service = format!("{}Service", model.name);
+        return (format!("import {{ {service} }} from \"@core/services/{}Service\";", model.name.to_lowercase()), format!("new {service}()"));
+    }
+    let repository = format!("{}Repository", model.name);
+    let import = format!("import {{ {repository} }} from \"@infrastructure/repositories/{}\";", naming().module("repository", model));
+
+    match options.cache {
+        Some(_) => (
+            format!(
+                "{import}\nimport {{ Cached{repository} }} from \"@infrastructure/repositories/{}\";",
+                naming().module("cached_repository", model)
+            ),
+            format!("new Cached{repository}(new {repository}())"),
+        ),
+        None => (import, format!("new {repository}()")),
+    }
+}
+
+// The use cases written for the model with their naming artifacts, empty models only get Gets and Delete.
+fn generated_use_cases(model: &Model) -> Vec<(String, &'static str)> {
+    use_case_names(model)
+        .into_iter()
+        .zip(["add_use_case", "gets_use_case", "delete_use_case", "update_use_case"])
+        .filter(|(_, artifact)| !model.is_empty() || ["gets_use_case", "delete_use_case"].contains(artifact))
+        .collect()
+}
+
+fn use_case_names(model: &Model) -> [String; 4] {
+    [
+        format!("Add{}", model.name),
+        format!("Get{}", model.name_plural),
+        format!("Delete{}", model.name),
+        format!("Update{}", model.name),
+    ]
+}
+
+// tsyringe registers the repository under its class name, inversify under the symbol the interface file exports.
+fn repository_token(model: &Model, di: &str) -> String {
+    match di {
+        "tsyringe" => format!("\"{}Repository\"", model.name),
+        _ => format!("I{}Repository", model.name),
+    }
+}
+
+fn repository_interface_import(model: &Model) -> String {
+    format!("import {{ I{}Repository }} from \"@core/interfaces/{}\";", model.name, naming().module("interface", model))
+}
+
+fn apply_di(content: &str, model: &Model, options: &Options) -> String {
+    let (di_import, injectable, inject, container_import) = match options.di.as_str() {
+        "none" => (String::new(), String::new(), String::new(), String::new()),
+        library => (
+            format!("import {{ injectable, inject }} from \"{library}\";"),
+            "@injectable()".to_string(),
+            format!("@inject({}) ", repository_token(model, library)),
+            "import { container } from \"@infrastructure/config/container\";".to_string(),
+        ),
+    };
+
+    let mut content = content
+        .replace("{DI_IMPORT}", &di_import)
+        .replace("{DI_INJECTABLE}", &injectable)
+        .replace("{DI_INJECT_REPOSITORY}", &inject)
+        .replace("{DI_CONTAINER_IMPORT}", &container_import);
+    if options.di == "inversify" && !inject.is_empty() && content.contains(&inject) {
+        content = merge_import(&content, &repository_interface_import(model));
+    }
+
+    let (_, repository_instance) = repository_wiring(model, options);
+    let placeholders = ["{RESOLVE_ADD_USE_CASE}", "{RESOLVE_GETS_USE_CASE}", "{RESOLVE_DELETE_USE_CASE}", "{RESOLVE_UPDATE_USE_CASE}"];
+    for (placeholder, use_case) in placeholders.iter().zip(use_case_names(model)) {
+        let resolved = match options.di.as_str() {
+            "tsyringe" => format!("container.resolve({use_case})"),
+            "inversify" => format!("container.get({use_case})"),
+            _ => format!("new {use_case}({repository_instance})"),
+        };
+        content = content.replace(placeholder, &resolved);
+    }
+
+    content
+}
+
+// Templates without the DI placeholders still get their decorators under --di tsyringe,
+// @injectable() on the class and @inject on a constructor parameter typed as the repository interface.
+fn apply_tsyringe_decorators(content: &str, model: &Model) -> String {
+    let class = Regex::new(r"(?m)^export\s+class\s").unwrap();
+    let Some(declaration) = class.find(content).filter(|_| !content.contains("@injectable(")) else {
+        return content.to_string();
+    };
+    let content = format!("{}@injectable()\n{}", &content[..declaration.start()], &content[declaration.start()..]);
+
+    let parameter = Regex::new(&format!(r"(constructor\(\s*)((?:private|public|protected|readonly)\b[^:)]*:\s*I{}Repository\b)", model.name)).unwrap();
+    let (content, names) = match parameter.is_match(&content) {
+        true => (parameter.replace(&content, format!("${{1}}@inject({}) ${{2}}", repository_token(model, "tsyringe"))).into_owned(), "injectable, inject"),
+        false => (content, "injectable"),
+    };
+
+    insert_import(&content, &format!("import {{ {names} }} from \"tsyringe\";"))
+}
+
+// findAll() of the repository and execute() of the Gets use case take the {NAME}QueryOptions of the utils types,
+// the findAll() calls inside them pass the options on, which are Sequelize find options as they are.
+fn apply_query_options(content: &str, model: &Model) -> String {
+    let options_type = format!("{}QueryOptions", model.name);
+    let declaration = Regex::new(r"(?m)^(\s*(?:async\s+)?(?:findAll|execute))\(\s*\)").unwrap();
+    if !declaration.is_match(content) {
+        return content.to_string();
+    }
+    let parameter = if output_style().is_js() { "options".to_string() } else { format!("options?: {options_type}") };
+    let content = declaration.replace_all(content, format!("${{1}}({parameter})"));
+    let content = content.replace(".findAll()", ".findAll(options)");
+
+    match output_style().is_js() {
+        true => content,
+        false => merge_import(&content, &format!("import {{ {options_type} }} from \"@core/utils/{}/types\";", model.name)),
+    }
+}
+
+// --tenant-field makes the tenant the first parameter of the repository methods and the use cases, the controllers
+// pass req.tenant.id. The create, find, update and destroy queries of the repository are scoped to it.
+fn apply_tenant(content: &str, model: &Model, layer: &str) -> String {
+    let Some(tenant) = model.tenant() else {
+        return content.to_string();
+    };
+    let tenant_name = &tenant.name;
+    let parameter = if output_style().is_js() { tenant_name.to_string() } else { format!("{tenant_name}: {}", tenant.js_type) };
+    let has_tenant = Regex::new(&format!(r"\b{tenant_name}\b")).unwrap();
+    let prepend = |content: &str, declaration: &Regex| {
+        declaration.replace_all(content, |captures: &regex::Captures| {
+            let parameters = captures[3].trim();
+            if ["constructor", "if", "for", "while", "switch", "catch", "function"].contains(&&captures[2]) || has_tenant.is_match(parameters) {
+                return captures[0].to_string();
+            }
+            let parameters = if parameters.is_empty() { parameter.clone() } else { format!("{parameter}, {parameters}") };
+            format!("{}{}({parameters}){}", &captures[1], &captures[2], &captures[4])
+        }).into_owned()
+    };
+    let pass = |content: &str, call: &Regex, argument: &str| {
+        call.replace_all(content, |captures: &regex::Captures| match captures.get(2) {
+            Some(_) => format!("{}({argument})", &captures[1]),
+            None => format!("{}({argument}, ", &captures[1]),
+        }).into_owned()
+    };
+
+    match layer {
+        "interface" => prepend(content, &Regex::new(r"(?m)^(\s+)(\w+)\(([^)]*)\)(\s*:)").unwrap()),
+        "use_case" => {
+            let content = prepend(content, &Regex::new(r"(?m)^(\s+(?:async\s+)?)(execute)\(([^)]*)\)()").unwrap());
+            pass(&content, &Regex::new(r"(this\.\w+Repository\.\w+)\((\s*\))?").unwrap(), tenant_name)
+        }
+        "controllers" => pass(content, &Regex::new(r"(\.execute)\((\s*\))?").unwrap(), "req.tenant.id"),
+        _ => {
+            let content = prepend(content, &Regex::new(r"(?m)^(\s+(?:async\s+)?)(\w+)\(([^)]*)\)(\s*(?::[^{;]*)?\{)").unwrap());
+            let name = regex::escape(&model.name);
+            let key = &model.primary_key().name;
+            let content = Regex::new(&format!(r"\b{name}\.create\((\w+)"))
+                .unwrap()
+                .replace_all(&content, format!("{name}.create({{ ...${{1}}, {tenant_name} }}"));
+            let content = Regex::new(&format!(r"\b{name}\.findAll\(\)")).unwrap().replace_all(&content, format!("{name}.findAll({{ where: {{ {tenant_name} }} }})"));
+            let content = Regex::new(&format!(r"\b{name}\.findAll\((\w+)\)"))
+                .unwrap()
+                .replace_all(&content, format!("{name}.findAll({{ ...${{1}}, where: {{ ...${{1}}?.where, {tenant_name} }} }})"));
+            let content = Regex::new(&format!(r"\b{name}\.findByPk\((\w+)\)")).unwrap().replace_all(&content, |captures: &regex::Captures| {
+                let lookup = if &captures[1] == key { key.to_string() } else { format!("{key}: {}", &captures[1]) };
+                format!("{name}.findOne({{ where: {{ {lookup}, {tenant_name} }} }})")
+            });
+            let content = Regex::new(r"where: \{\s*([^{}]*?)\s*\}").unwrap().replace_all(&content, |captures: &regex::Captures| match has_tenant.is_match(&captures[1]) {
+                true => captures[0].to_string(),
+                false => format!("where: {{ {}, {tenant_name} }}", &captures[1]),
+            });
+            // An instance loaded for another tenant is never saved.
+            Regex::new(r"(^|[^.\w])(\w+)\.save\(\)")
+                .unwrap()
+                .replace_all(&content, format!("${{1}}(${{2}}.{tenant_name} === {tenant_name} ? ${{2}}.save() : Promise.reject(new Error(\"{} belongs to another tenant\")))", model.name))
+                .into_owned()
+        }
+    }
+}
+
+// Repository types under --domain-entities are the plain entity, otherwise the model class as before.
+// JavaScript entities are JSDoc typedefs, so there is nothing to import.
+fn apply_entities(content: &str, model: &Model, options: &Options) -> String {
+    let model_import = format!("import {{ {} }} from \"@infrastructure/models/{}\";", model.name, naming().module("model", model));
+    let (entity_type, entity_import, mapper_import, to_entity, to_model) = match options.entities {
+        Some(_) => (
+            format!("{}Entity", model.name),
+            if output_style().is_js() { String::new() } else { format!("import {{ {0}Entity }} from \"@core/entities/{0}\";", model.name) },
+            format!("import {{ to{0}Entity, to{0}Model }} from \"@infrastructure/mappers/{1}Mapper\";", model.name, model.name.to_lowercase()),
+            format!("to{}Entity", model.name),
+            format!("to{}Model", model.name),
+        ),
+        None => (model.name.to_string(), model_import, String::new(), String::new(), String::new()),
+    };
+
+    content
+        .replace("{ENTITY_TYPE}", &entity_type)
+        .replace("{ENTITY_IMPORT}", &entity_import)
+        .replace("{ENTITY_MAPPER_IMPORT}", &mapper_import)
+        .replace("{TO_ENTITY}", &to_entity)
+        .replace("{TO_MODEL}", &to_model)
+}
+
+fn implement_entity(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let Some(settings) = &options.entities else {
+        return Ok(());
+    };
+    let fields: Vec<&Field> = model.fields.iter().filter(|field| settings.includes(field)).collect();
+
+    // Computed fields are read only, the entity cannot set them.
+    let properties = fields
+        .iter()
+        .map(|field| match (output_style().is_js(), field.is_virtual()) {
+            (true, _) => field.to_jsdoc_property(),
+            (false, true) => format!("\treadonly {}: {};", field.name, field.property_type()),
+            (false, false) => format!("\t{}: {};", field.name, field.property_type()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let entity_template_key = template_key("ENTITY_TEMPLATE");
+    let mut content = copy_template(&entity_template_key, Some(builtin(ENTITY_TEMPLATE, ENTITY_JS_TEMPLATE)), model, templates)?
+        .replace("{DYNAMIC_ENTITY_PROPERTIES}", &properties);
+    check_rendered(&entity_template_key, &content)?;
+
+    if !output_style().is_js() {
+        let shapes = interface_names(&fields);
+        if !shapes.is_empty() {
+            content = insert_import(&content, &format!("import {{ {} }} from \"@core/utils/{}/types\";", shapes.join(", "), model.name));
+        }
+        let mut enum_names: Vec<&str> = Vec::new();
+        for enum_name in fields.iter().filter_map(|field| field.enum_name()) {
+            if !enum_names.contains(&enum_name) {
+                enum_names.push(enum_name);
+            }
+        }
+        if !enum_names.is_empty() {
+            content = insert_import(&content, &enum_import(&enum_names));
+        }
+    }
+
+    let file_path = path.join(format!("{}.ts", model.name));
+    if options.namespace_declarations {
+        content = to_namespace(&content, &file_path, &model.name);
+    }
+    write_file(&file_path, &content)
+}
+
+fn implement_entity_mapper(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let Some(settings) = &options.entities else {
+        return Ok(());
+    };
+    let fields: Vec<&Field> = model.fields.iter().filter(|field| settings.includes(field)).collect();
+
+    let entity_mapping = fields.iter().map(|field| format!("\t{0}: model.{0},", field.name)).collect::<Vec<_>>().join("\n");
+    let model_mapping = fields
+        .iter()
+        .filter(|field| !field.is_virtual())
+        .map(|field| format!("\t{0}: entity.{0},", field.name))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mapper_template_key = template_key("ENTITY_MAPPER_TEMPLATE");
+    let content = copy_template(&mapper_template_key, Some(builtin(ENTITY_MAPPER_TEMPLATE, ENTITY_MAPPER_JS_TEMPLATE)), model, templates)?
+        .replace("{DYNAMIC_ENTITY_MAPPING}", &entity_mapping)
+        .replace("{DYNAMIC_MODEL_MAPPING}", &model_mapping);
+    check_rendered(&mapper_template_key, &content)?;
+
+    write_file(&path.join(format!("{}Mapper.ts", model.name.to_lowercase())), &content)
+}
+
+fn insert_import(content: &str, import: &str) -> String {
+    let mut lines: Vec<&str> = content.lines().collect();
+    match lines.iter().rposition(|line| line.starts_with("import ")) {
+        Some(index) => lines.insert(index + 1, import),
+        None => {
+            lines.insert(0, "");
+            lines.insert(0, import);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+// Adds the names of a named import to an import or require of the same module when there is one,
+// other imports are inserted unless the content has them already.
+fn merge_import(content: &str, import: &str) -> String {
+    let named = Regex::new(r#"^import \{([^}]*)\} from "([^"]+)";?$"#).unwrap();
+    let Some(captures) = named.captures(import) else {
+        if content.contains(import) || content.contains(module_import(import).trim_end()) {
+            return content.to_string();
+        }
+        return insert_import(content, module_import(import).trim_end());
+    };
+    let module = regex::escape(&captures[2]);
+    let existing_import = Regex::new(&format!(r#"(?m)^((?:import|const) \{{)([^}}]*)(\}} (?:from "{module}"|= require\("{module}"\)))"#)).unwrap();
+    let Some(existing_captures) = existing_import.captures(content) else {
+        return insert_import(content, module_import(import).trim_end());
+    };
+    let mut names: Vec<String> = existing_captures[2].split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect();
+    let missing: Vec<String> = captures[1].split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty() && !names.contains(name)).collect();
+    if missing.is_empty() {
+        return content.to_string();
+    }
+    names.extend(missing);
+    let replacement = format!("{} {} {}", &existing_captures[1], names.join(", "), &existing_captures[3]);
+    existing_import.replace(content, regex::NoExpand(&replacement)).into_owned()
+}
+
+// --namespace-declarations puts a type file in `namespace {NAME}` and turns its imports of generated files
+// into triple-slash references. `@layer/...` resolves next to the layer directory the file itself is in,
+// package imports stay as they are.
+fn to_namespace(content: &str, file_path: &Path, namespace: &str) -> String {
+    let import = Regex::new(r#"^import\s+(?:type\s+)?(?:\{[^}]*\}\s+from\s+)?"([^"]+)";?\s*$"#).unwrap();
+    let directory = file_path.parent().unwrap_or(Path::new(""));
+    let base = file_path
+        .ancestors()
+        .find(|ancestor| ancestor.file_name().is_some_and(|name| ["core", "infrastructure", "presentation"].contains(&name.to_string_lossy().as_ref())))
+        .and_then(Path::parent);
+
+    let mut references = Vec::new();
+    let mut body = Vec::new();
+    for line in content.lines() {
+        let target = import.captures(line).and_then(|captures| {
+            let specifier = &captures[1];
+            match specifier.strip_prefix('@') {
+                Some(aliased) => base.map(|base| base.join(format!("{aliased}.ts"))),
+                None if specifier.starts_with('.') => Some(directory.join(format!("{specifier}.ts"))),
+                None => None,
+            }
+        });
+        match target {
+            Some(target) => references.push(format!("/// <reference path=\"{}\" />", relative_path(directory, &target))),
+            None if line.starts_with("import ") => references.push(line.to_string()),
+            None => body.push(line),
+        }
+    }
+
+    while body.first().is_some_and(|line| line.trim().is_empty()) {
+        body.remove(0);
+    }
+    let body = body
+        .iter()
+        .map(|line| if line.trim().is_empty() { String::new() } else { format!("    {line}") })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let header = if references.is_empty() { String::new() } else { format!("{}\n\n", references.join("\n")) };
+    format!("{header}namespace {namespace} {{\n{}\n}}\n", body.trim_end())
+}
+
+// Object type aliases become interfaces, an intersection with other types becomes `extends`.
+// Unions, mapped and other aliases an interface cannot declare stay as they are.
+fn to_interfaces(content: &str) -> String {
+    let alias = Regex::new(r"^(\s*)export type (\w+(?:<[^=]*>)?) = ((?:[\w.]+(?:<[^=&{]*>)?\s*&\s*)*)\{(.*)$").unwrap();
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    for index in 0..lines.len() {
+        let Some(captures) = alias.captures(&lines[index]) else { continue };
+        let (indent, name, rest) = (captures[1].to_string(), captures[2].to_string(), captures[4].trim_end().to_string());
+        let bases: Vec<&str> = captures[3].split('&').map(str::trim).filter(|base| !base.is_empty()).collect();
+        let extends = if bases.is_empty() { String::new() } else { format!(" extends {}", bases.join(", ")) };
+
+        if rest.is_empty() {
+            // The body closes on the first line at the alias indentation, unless more type follows it.
+            let Some(end) = lines[index + 1..].iter().position(|line| line.strip_prefix(indent.as_str()).is_some_and(|line| line.starts_with('}'))) else { continue };
+            let end = index + 1 + end;
+            if lines[end] != format!("{indent}}};") {
+                continue;
+            }
+            lines[end] = format!("{indent}}}");
+            lines[index] = format!("{indent}export interface {name}{extends} {{");
+        } else if let Some(body) = rest.strip_suffix("};").filter(|body| closes_at_end(body)) {
+            lines[index] = format!("{indent}export interface {name}{extends} {{{body}}}");
+        }
+    }
+    let converted = lines.join("\n");
+    if content.ends_with('\n') { format!("{converted}\n") } else { converted }
+}
+
+// Whether every brace of a one line body closes inside it, so its `}` ends the alias.
+fn closes_at_end(body: &str) -> bool {
+    let mut depth = 0i32;
+    for character in body.chars() {
+        match character {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return false;
+        }
+    }
+    depth == 0
+}
+
+// Path of `target` as written from a file in `directory`, with forward slashes.
+fn relative_path(directory: &Path, target: &Path) -> String {
+    let from: Vec<_> = directory.components().collect();
+    let to: Vec<_> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(from, to)| from == to).count();
+
+    let mut parts = vec!["..".to_string(); from.len() - common];
+    parts.extend(to[common..].iter().map(|component| component.as_os_str().to_string_lossy().into_owned()));
+    let path = parts.join("/");
+    if path.starts_with("..") { path } else { format!("./{path}") }
+}
+
+// A regenerated model only replaces the field block of the existing file, found between its markers or,
+// in files written before them, as the properties that open the class. Hooks, methods, comments and
+// imports stay, the imports the fields need are merged in. None when the fields cannot be told apart.
+fn merge_model_fields(existing: &str, generated: &str, model: &Model) -> Option<String> {
+    let region = Regex::new(&format!(r"(?s)[ \t]*// {FIELDS_START}\n.*?// {FIELDS_END}")).unwrap();
+    let fields = region.find(generated)?.as_str();
+
+    let mut merged = match region.find(existing) {
+        Some(existing_fields) => format!("{}{fields}{}", &existing[..existing_fields.start()], &existing[existing_fields.end()..]),
+        None => {
+            let (start, end) = legacy_field_lines(existing, model)?;
+            let lines: Vec<&str> = existing.lines().collect();
+            let merged_lines: Vec<&str> = lines[..start].iter().copied().chain([fields]).chain(lines[end..].iter().copied()).collect();
+            merged_lines.join("\n") + if existing.ends_with('\n') { "\n" } else { "" }
+        }
+    };
+
+    for import in generated.lines().filter(|line| line.starts_with("import ")) {
+        merged = merge_import(&merged, import);
+    }
+    Some(merged)
+}
+
+// Lines of the properties that open the class of a model file without field markers. Only taken when
+// every property there is a current field, anything else may be a custom member or a removed field.
+fn legacy_field_lines(existing: &str, model: &Model) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = existing.lines().collect();
+    let header = Regex::new(&format!(r"^export class {} extends Model\b.*\{{\s*$", regex::escape(&model.name))).unwrap();
+    let property = Regex::new(r"^\s*(?:declare\s+)?(\w+)[!?]?\s*:\s*[^;]+;\s*$").unwrap();
+    let getter = Regex::new(r"^\s*get (\w+)\(").unwrap();
+    let balance = |line: &str| line.matches('(').count() as i32 - line.matches(')').count() as i32;
+    let start = lines.iter().position(|line| header.is_match(line))? + 1;
+
+    let (mut index, mut end, mut names) = (start, None, Vec::new());
+    while index < lines.len() {
+        let trimmed = lines[index].trim();
+        if let Some(captures) = property.captures(lines[index]) {
+            names.push(captures[1].to_string());
+            end = Some(index + 1);
+        } else if let Some(captures) = getter.captures(lines[index]) {
+            index += lines[index..].iter().position(|line| *line == "\t}")?;
+            names.push(captures[1].to_string());
+            end = Some(index + 1);
+        } else if trimmed.starts_with('@') {
+            let mut depth = balance(trimmed);
+            while depth > 0 && index + 1 < lines.len() {
+                index += 1;
+                depth += balance(lines[index]);
+            }
+        } else if !(trimmed.is_empty() || trimmed.starts_with("/**") || trimmed.starts_with('*')) {
+            break;
+        }
+        index += 1;
+    }
+
+    let end = end?;
+    names.iter().all(|name| model.fields.iter().any(|field| &field.name == name)).then_some((start, end))
+}
+
+// Carries the body of every keep block in the existing file over into the regenerated content.
+fn preserve_keep_blocks(existing: &str, generated: &str) -> String {
+    let block = Regex::new(&format!(r"(?s)(// {KEEP_START}(\w+)\n)(.*?)([ \t]*// {KEEP_END}\w+)")).unwrap();
+    let bodies: HashMap<String, String> = block
+        .captures_iter(existing)
+        .map(|captures| (captures[2].to_string(), captures[3].to_string()))
+        .collect();
+
+    block.replace_all(generated, |captures: &regex::Captures| {
+        let body = bodies.get(&captures[2]).map_or(&captures[3], String::as_str);
+        format!("{}{body}{}", &captures[1], &captures[4])
+    }).into_owned()
+}
+
+fn update_container(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let container_path = path.join("container.ts");
+    let mut file_content = if file_exists(&container_path) {
+        read_file(&container_path)?
+    } else if options.di == "tsyringe" {
+        template_or_default("CONTAINER_TEMPLATE", CONTAINER_TSYRINGE_TEMPLATE, templates)?
+    } else {
+        template_or_default("CONTAINER_TEMPLATE", CONTAINER_INVERSIFY_TEMPLATE, templates)?
+    };
+
+    // A container merged by merge-inversify loads the bindings module of the model instead.
+    if file_content.contains(&format!("{}Bindings", model.name.to_lowercase())) {
+        return Ok(());
+    }
+
+    let (imports, registrations) = if options.di == "tsyringe" {
+        tsyringe_registrations(model, options)
+    } else {
+        inversify_bindings(model, options, "container.bind")
+    };
+
+    for import in imports.iter().map(|import| package_imports(import)) {
+        if !file_content.contains(&import) {
+            file_content = insert_import(&file_content, &import);
+        }
+    }
+    for registration in registrations {
+        if !file_content.contains(&registration) {
+            file_content = format!("{}\n{registration}\n", file_content.trim_end());
+        }
+    }
+
+    write_file(&container_path, &file_content)?;
+
+    Ok(())
+}
+
+// Imports and container.register call of the repository of a model.
+fn tsyringe_registrations(model: &Model, options: &Options) -> (Vec<String>, Vec<String>) {
+    let token = repository_token(model, "tsyringe");
+    let (repository_import, repository_instance) = repository_wiring(model, options);
+    let registration = match options.cache {
+        Some(_) => format!("container.register({token}, {{ useFactory: () => {repository_instance} }});"),
+        None => format!("container.register({token}, {{ useClass: {}Repository }});", model.name),
+    };
+
+    (repository_import.lines().map(str::to_string).collect(), vec![registration])
+}
+
+fn implement_tsyringe_registration(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(format!("{}DI.ts", model.name.to_lowercase()));
+
+    let (imports, registrations) = tsyringe_registrations(model, options);
+    let imports = imports.iter().map(|import| package_imports(import)).collect::<Vec<_>>().join("\n");
+    let content = copy_template("TSYRINGE_DI_TEMPLATE", Some(TSYRINGE_DI_TEMPLATE), model, templates)?
+        .replace("{DI_IMPORTS}", &imports)
+        .replace("{DI_REGISTRATIONS}", &registrations.join("\n"));
+    check_rendered("TSYRINGE_DI_TEMPLATE", &content)?;
+
+    write_file(&file_path, &content)
+}
+
+// Imports and bind calls of the repository and use cases of a model, `bind` being what they are bound with.
+fn inversify_bindings(model: &Model, options: &Options, bind: &str) -> (Vec<String>, Vec<String>) {
+    let token = repository_token(model, "inversify");
+    let (repository_import, repository_instance) = repository_wiring(model, options);
+    let mut imports: Vec<String> = repository_import.lines().map(str::to_string).collect();
+    imports.push(repository_interface_import(model));
+    let mut bindings = vec![match options.cache {
+        Some(_) => format!("{bind}({token}).toDynamicValue(() => {repository_instance});"),
+        None => format!("{bind}({token}).to({}Repository);", model.name),
+    }];
+    for (use_case, artifact) in generated_use_cases(model) {
+        imports.push(format!("import {{ {use_case} }} from \"@core/use_cases/{}/{}\";", model.name, naming().module(artifact, model)));
+        bindings.push(format!("{bind}({use_case}).toSelf();"));
+    }
+
+    (imports, bindings)
+}
+
+fn implement_inversify_binding(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(format!("{}{BINDINGS_SUFFIX}", model.name.to_lowercase()));
+
+    let (imports, bindings) = inversify_bindings(model, options, "bind");
+    let binding_imports = imports.iter().map(|import| package_imports(import)).collect::<Vec<_>>().join("\n");
+    let bindings = bindings.iter().map(|binding| format!("\t{binding}")).collect::<Vec<_>>().join("\n");
+    let content = copy_template("INVERSIFY_BINDINGS_TEMPLATE", Some(INVERSIFY_BINDINGS_TEMPLATE), model, templates)?
+        .replace("{BINDING_IMPORTS}", &binding_imports)
+        .replace("{BINDINGS}", &bindings);
+    check_rendered("INVERSIFY_BINDINGS_TEMPLATE", &content)?;
+
+    write_file(&file_path, &content)
+}
+
+fn implement_graphql_typedefs(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(naming().file_name("typedefs", model));
+
+    let mut graphql_type = model.to_graphql_type();
+    if options.federation {
+        let directive = copy_template("FEDERATION_ENTITY_TEMPLATE", Some(FEDERATION_ENTITY_TEMPLATE), model, templates)?
+            .replace("{PRIMARY_KEY}", &model.primary_key().name);
+        check_rendered("FEDERATION_ENTITY_TEMPLATE", &directive)?;
+        let header = format!("type {} {{", model.name);
+        graphql_type = graphql_type.replacen(&header, &format!("type {} {} {{", model.name, directive.trim()), 1);
+    }
+
+    let content = copy_template("GRAPHQL_TYPEDEFS_TEMPLATE", Some(GRAPHQL_TYPEDEFS_TEMPLATE), model, templates)?
+    .replace("{DYNAMIC_GRAPHQL_TYPE}", &graphql_type);
+    check_rendered("GRAPHQL_TYPEDEFS_TEMPLATE", &content)?;
+
+    write_file(&file_path, &content)?;
+
+    if options.federation {
+        implement_federation_resolver(&path, model, options, templates)?;
+    }
+
+    Ok(())
+}
+
+// The reference resolver the gateway calls with the @key fields of the entity.
+fn implement_federation_resolver(path: &Path, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(naming().file_name("resolvers", model));
+
+    let resolver_template_key = template_key("FEDERATION_RESOLVER_TEMPLATE");
+    let (repository_import, repository_instance) = repository_wiring(model, options);
+    let content = copy_template(&resolver_template_key, Some(builtin(FEDERATION_RESOLVER_TEMPLATE, FEDERATION_RESOLVER_JS_TEMPLATE)), model, templates)?
+        .replace("{REPOSITORY_IMPORT}", &repository_import)
+        .replace("{REPOSITORY_INSTANCE}", &repository_instance)
+        .replace("{PRIMARY_KEY_TYPE}", &model.primary_key().js_type)
+        .replace("{PRIMARY_KEY}", &model.primary_key().name);
+    check_rendered(&resolver_template_key, &content)?;
+
+    write_file(&file_path, &content)
+}
+
+// The types.ts interfaces the fields are typed as, each once.
+fn interface_names<'a>(fields: &[&'a Field]) -> Vec<&'a str> {
+    let mut names: Vec<&str> = Vec::new();
+    for name in fields.iter().filter_map(|field| field.interface_name()) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn migration_type(field: &Field, dialect: &str) -> String {
+    if field.spatial.is_some() {
+        return field.data_type("Sequelize");
+    }
+
+    let length = field.attr
+        .iter()
+        .find(|attribute| attr_name(attribute) == "@Length")
+        .and_then(|attribute| attr_args(attribute))
+        .filter(|length| !length.contains(','));
+
+    match (field.column_type(), length) {
+        ("JSON", _) if dialect == "postgres" => "Sequelize.JSONB".to_string(),
+        // Postgres columns use the type the enums migration creates once for every table.
+        ("ENUM", _) if dialect == "postgres" => format!("\"{}\"", field.enum_name().map_or_else(String::new, enum_type_name)),
+        ("ENUM", _) => format!("Sequelize.ENUM({})", field.enum_literals()),
+        ("STRING", Some(length)) => format!("Sequelize.STRING({length})"),
+        (column_type, _) => format!("Sequelize.{column_type}"),
+    }
+}
+
+fn uuid_default(dialect: &str) -> Option<&'static str> {
+    match dialect {
+        "postgres" => Some("Sequelize.literal(\"gen_random_uuid()\")"),
+        "mysql" => Some("Sequelize.literal(\"(UUID())\")"),
+        "mssql" => Some("Sequelize.literal(\"NEWID()\")"),
+        _ => None,
+    }
+}
+
+// A schema turns the quoted table name into a { tableName, schema } object.
+fn replace_table_name(content: &str, model: &Model) -> String {
+    let content = match model.table.schema {
+        Some(_) => content.replace("\"{TABLE_NAME}\"", &model.migration_table()).replace("'{TABLE_NAME}'", &model.migration_table()),
+        None => content.to_string(),
+    };
+    content.replace("{TABLE_NAME}", model.table_name())
+}
+
+fn migration_column(field: &Field, column: &str, dialect: &str) -> String {
+    let mut options = vec![format!("type: {}", migration_type(field, dialect))];
+
+    if field.has_attr("@PrimaryKey") {
+        options.push("primaryKey: true".to_string());
+        options.push("allowNull: false".to_string());
+        if field.db_type == "UUID" && !field.has_attr("@Default") {
+            if let Some(default) = uuid_default(dialect) {
+                options.push(format!("defaultValue: {default}"));
+            }
+        }
+    }
+    if field.has_attr("@AutoIncrement") {
+        options.push("autoIncrement: true".to_string());
+    }
+    if field.has_attr("@Unique") {
+        options.push("unique: true".to_string());
+    }
+    for attribute in &field.attr {
+        match (attr_name(attribute), attr_args(attribute)) {
+            ("@AllowNull", Some(allow)) => options.push(format!("allowNull: {allow}")),
+            ("@Default", Some(value)) => options.push(format!("defaultValue: {value}")),
+            ("@Comment", Some(comment)) => options.push(format!("comment: {comment}")),
+            _ => {}
+        }
+    }
+    if let Some(reference) = field.reference() {
+        options.extend(reference.options());
+    }
+
+    format!("\t\t\t{column}: {{\n\t\t\t\t{},\n\t\t\t}},", options.join(",\n\t\t\t\t"))
+}
+
+fn migration_timestamp() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs()) as i64;
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // Civil-from-days conversion, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}{month:02}{day:02}{:02}{:02}{:02}", time / 3600, time % 3600 / 60, time % 60)
+}
+
+fn implement_migration(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let suffix = format!("-create-{}.js", model.name.to_lowercase());
+
+    // Regenerating keeps the original timestamp so the migration order does not change.
+    let existing = fs::read_dir(&path)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .find(|file_name| file_name.ends_with(&suffix));
+    let file_name = existing.unwrap_or_else(|| format!("{}{suffix}", migration_timestamp()));
+
+    // A composite key is added as a constraint once the table exists.
+    let mut columns: Vec<String> = model.fields
+        .iter()
+        .filter(|field| !field.is_virtual())
+        .map(|field| migration_column(field, &model.column_name(&field.name), &options.dialect))
+        .map(|column| if model.has_composite_key() { column.replace("\n\t\t\t\tprimaryKey: true,", "") } else { column })
+        .collect();
+    if model.timestamps() && !model.fields.iter().any(|field| field.has_attr("@CreatedAt")) {
+        columns.push(format!("\t\t\t{}: {{\n\t\t\t\ttype: Sequelize.DATE,\n\t\t\t\tallowNull: false,\n\t\t\t}},", model.column_name("createdAt")));
+    }
+    if model.timestamps() && !model.fields.iter().any(|field| field.has_attr("@UpdatedAt")) {
+        columns.push(format!("\t\t\t{}: {{\n\t\t\t\ttype: Sequelize.DATE,\n\t\t\t\tallowNull: false,\n\t\t\t}},", model.column_name("updatedAt")));
+    }
+    if model.table.paranoid && !model.fields.iter().any(|field| field.has_attr("@DeletedAt")) {
+        columns.push(format!("\t\t\t{}: {{\n\t\t\t\ttype: Sequelize.DATE,\n\t\t\t\tallowNull: true,\n\t\t\t}},", model.column_name("deletedAt")));
+    }
+
+    let content = replace_table_name(&copy_template("MIGRATION_TEMPLATE", Some(MIGRATION_TEMPLATE), model, templates)?, model)
+    .replace("{DYNAMIC_MIGRATION_COLUMNS}", &columns.join("\n"));
+
+    // Templates without the placeholder get the indexes right after the createTable call.
+    let mut indexes: String = model.indexes().iter().map(|index| format!("\n{}", index.to_migration(model))).collect();
+    if model.has_composite_key() {
+        indexes = format!("\n{}{indexes}", model.composite_key_constraint());
+    }
+    for index in model.insensitive_unique_indexes() {
+        indexes.push_str(&format!("\n{index}"));
+    }
+    let content = if content.contains("{DYNAMIC_MIGRATION_INDEXES}") {
+        content.replace("{DYNAMIC_MIGRATION_INDEXES}", &indexes)
+    } else {
+        let create_table = Regex::new(r"(?s)createTable\(.*?\n\s*\}\);").unwrap();
+        create_table.replace(&content, |captures: &regex::Captures| format!("{}{indexes}", &captures[0])).into_owned()
+    };
+    check_rendered("MIGRATION_TEMPLATE", &content)?;
+
+    write_file(&path.join(file_name), &content)?;
+
+    Ok(())
+}
+
+fn enum_type_name(enum_name: &str) -> String {
+    format!("enum_{}", snake_case(enum_name))
+}
+
+// The enums of every schema model, each once in the order they are first used.
+fn schema_enums() -> Vec<(&'static str, &'static [String])> {
+    let mut enums: Vec<(&'static str, &'static [String])> = Vec::new();
+    for (enum_name, values) in SCHEMA_MODELS.get().into_iter().flatten().flat_map(|model| &model.fields).filter_map(|field| field.enumeration.as_ref()) {
+        if !enums.iter().any(|(other, _)| other == enum_name) {
+            enums.push((enum_name, values));
+        }
+    }
+    enums
+}
+
+fn enum_import(enum_names: &[&str]) -> String {
+    format!("import {{ {} }} from \"@core/utils/enums\";", enum_names.join(", "))
+}
+
+// The const array of each enum next to the union type of its values.
+fn implement_enums(path: PathBuf) -> io::Result<()>{
+    let declarations = schema_enums()
+        .iter()
+        .map(|(enum_name, values)| {
+            let values = values.iter().map(|value| format!("{value:?}")).collect::<Vec<_>>().join(", ");
+            if output_style().is_js() {
+                format!("export const {enum_name} = Object.freeze([{values}]);")
+            } else {
+                format!("export const {enum_name} = [{values}] as const;\nexport type {enum_name} = (typeof {enum_name})[number];")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    write_file(&path.join("enums.ts"), &format!("{declarations}\n"))
+}
+
+// Postgres creates an enum type for every column that uses one, so the shared types get a migration of their own.
+// Its zero timestamp runs it before the table migrations.
+fn implement_enum_migration(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let enums = schema_enums();
+    let types = enums
+        .iter()
+        .map(|(enum_name, values)| {
+            let values = values.iter().map(|value| format!("'{}'", value.replace('\'', "''"))).collect::<Vec<_>>().join(", ");
+            format!("\t\tawait queryInterface.sequelize.query({:?});", format!("CREATE TYPE \"{}\" AS ENUM ({values});", enum_type_name(enum_name)))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let drops = enums
+        .iter()
+        .rev()
+        .map(|(enum_name, _)| format!("\t\tawait queryInterface.sequelize.query({:?});", format!("DROP TYPE IF EXISTS \"{}\";", enum_type_name(enum_name))))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let content = template_or_default("ENUM_MIGRATION_TEMPLATE", ENUM_MIGRATION_TEMPLATE, templates)?
+        .replace("{DYNAMIC_ENUM_TYPES}", &types)
+        .replace("{DYNAMIC_ENUM_DROPS}", &drops);
+    check_rendered("ENUM_MIGRATION_TEMPLATE", &content)?;
+
+    write_file(&path.join("00000000000000-create-enums.js"), &content)
+}
+
+// The Postgres extensions the column types of the schema models need, postgis for GEOMETRY and GEOGRAPHY
+// and citext for CITEXT.
+fn schema_extensions() -> Vec<&'static str> {
+    let fields: Vec<&Field> = SCHEMA_MODELS.get().into_iter().flatten().flat_map(|model| &model.fields).collect();
+    let mut extensions = Vec::new();
+    if fields.iter().any(|field| field.spatial.is_some()) {
+        extensions.push("postgis");
+    }
+    if fields.iter().any(|field| field.db_type == "CITEXT") {
+        extensions.push("citext");
+    }
+    extensions
+}
+
+// The extensions are enabled before the table migrations, like the enum types.
+fn implement_extension_migration(path: PathBuf, extensions: &[&str], templates: &TemplateCache) -> io::Result<()>{
+    let statements = extensions
+        .iter()
+        .map(|extension| format!("\t\tawait queryInterface.sequelize.query(\"CREATE EXTENSION IF NOT EXISTS {extension};\");"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let content = template_or_default("EXTENSION_MIGRATION_TEMPLATE", EXTENSION_MIGRATION_TEMPLATE, templates)?
+        .replace("{DYNAMIC_EXTENSIONS}", &statements);
+    check_rendered("EXTENSION_MIGRATION_TEMPLATE", &content)?;
+
+    write_file(&path.join("00000000000000-create-extensions.js"), &content)
+}
+
+fn parse_date(value: &str, date_format: Option<&str>) -> Option<NaiveDateTime> {
+    if let Some(format) = date_format {
+        return NaiveDateTime::parse_from_str(value, format)
+            .ok()
+            .or_else(|| NaiveDate::parse_from_str(value, format).ok().and_then(|date| date.and_hms_opt(0, 0, 0)));
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.naive_utc())
+        .ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok())
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+}
+
+// Converts one CSV cell into the JSON value stored for the field.
+fn parse_cell(field: &Field, value: &str, date_format: Option<&str>) -> Result<Value, String> {
+    if value.is_empty() {
+        return if field.has_attr("@AllowNull") {
+            Ok(Value::Null)
+        } else {
+            Err("empty value for a non-nullable field".to_string())
+        };
+    }
+
+    let invalid = || format!("`{value}` is not a valid {}", field.db_type);
+    match field.db_type.as_str() {
+        "BIGINT" if field.is_bigint() || field.is_bigint_string() => value.parse::<i64>().map(|number| json!(number.to_string())).map_err(|_| invalid()),
+        "INTEGER" | "BIGINT" => value.parse::<i64>().map(|number| json!(number)).map_err(|_| invalid()),
+        "FLOAT" | "REAL" | "DOUBLE" | "DECIMAL" => value.parse::<f64>().map(|number| json!(number)).map_err(|_| invalid()),
+        "BOOLEAN" => match value.to_lowercase().as_str() {
+            "true" | "1" => Ok(json!(true)),
+            "false" | "0" => Ok(json!(false)),
+            _ => Err(invalid()),
+        },
+        "DATE" => parse_date(value, date_format)
+            .map(|date| json!(date.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()))
+            .ok_or_else(invalid),
+        "DATEONLY" => parse_date(value, date_format)
+            .map(|date| json!(date.format("%Y-%m-%d").to_string()))
+            .ok_or_else(invalid),
+        "TIME" => NaiveTime::parse_from_str(value, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+            .map(|time| json!(time.format("%H:%M:%S").to_string()))
+            .map_err(|_| invalid()),
+        "UUID" => {
+            let uuid_regex = Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$").unwrap();
+            if uuid_regex.is_match(value) { Ok(json!(value)) } else { Err(invalid()) }
+        }
+        "JSON" => serde_json::from_str(value).map_err(|_| invalid()),
+        // Points come as GeoJSON or `longitude,latitude` and are inserted as EWKT, which PostGIS reads as text.
+        "GEOMETRY" | "GEOGRAPHY" => {
+            let coordinates = match serde_json::from_str::<Value>(value) {
+                Ok(geojson) if geojson["type"] == "Point" => geojson["coordinates"].as_array().map(|pair| pair.iter().filter_map(Value::as_f64).collect()),
+                Ok(_) => return Err(format!("`{value}` is not a GeoJSON Point, only points can be seeded")),
+                Err(_) => value.split(',').map(|number| number.trim().parse::<f64>()).collect::<Result<Vec<f64>, _>>().ok(),
+            };
+            match coordinates.as_deref() {
+                Some(&[longitude, latitude]) if (-180.0..=180.0).contains(&longitude) && (-90.0..=90.0).contains(&latitude) => {
+                    let srid = field.spatial.as_ref().and_then(|spatial| spatial.srid).unwrap_or(4326);
+                    Ok(json!(format!("SRID={srid};POINT({longitude} {latitude})")))
+                }
+                _ => Err(format!("`{value}` is not a point, expected GeoJSON or longitude,latitude within range")),
+            }
+        }
+        "ENUM" => match field.enumeration.as_ref().is_some_and(|(_, values)| values.iter().any(|allowed| allowed == value)) {
+            true => Ok(json!(value)),
+            false => Err(format!("`{value}` is not one of {}", field.enum_literals())),
+        },
+        _ => Ok(json!(value)),
+    }
+}
+
+// A column of an existing table as crudify introspect reads it.
+struct IntrospectedColumn {
+    table: String,
+    name: String,
+    sql_type: String,
+    nullable: bool,
+    primary: bool,
+    auto_increment: bool,
+    enum_values: Option<(String, Vec<String>)>,
+}
+
+// Tables the migration tools keep for themselves.
+const MIGRATION_TABLES: &[&str] = &["SequelizeMeta", "SequelizeData", "_prisma_migrations"];
+
+#[cfg(feature = "postgres")]
+fn introspect_postgres(url: &str) -> Result<Vec<IntrospectedColumn>, String> {
+    let mut client = postgres::Client::connect(url, postgres::NoTls).map_err(|error| {
+        let cause = std::error::Error::source(&error).map(|cause| format!(", {cause}")).unwrap_or_default();
+        format!("cannot connect to the Postgres database: {error}{cause}")
+    })?;
+    let rows = client
+        .query(
+            "SELECT c.table_name::text, c.column_name::text, c.data_type::text, c.udt_name::text, c.is_nullable = 'YES',
+                COALESCE(c.column_default, '') LIKE 'nextval(%' OR c.is_identity = 'YES',
+                EXISTS (
+                    SELECT 1 FROM information_schema.table_constraints t
+                    JOIN information_schema.key_column_usage k
+                        ON k.constraint_name = t.constraint_name AND k.table_schema = t.table_schema AND k.table_name = t.table_name
+                    WHERE t.constraint_type = 'PRIMARY KEY' AND t.table_schema = c.table_schema AND t.table_name = c.table_name AND k.column_name = c.column_name
+                )
+            FROM information_schema.columns c
+            JOIN information_schema.tables tb ON tb.table_schema = c.table_schema AND tb.table_name = c.table_name AND tb.table_type = 'BASE TABLE'
+            WHERE c.table_schema = current_schema()
+            ORDER BY c.table_name, c.ordinal_position",
+            &[],
+        )
+        .map_err(|error| format!("information_schema.columns: {error}"))?;
+
+    let mut columns = Vec::new();
+    for row in rows {
+        let (data_type, udt_name): (String, String) = (row.get(2), row.get(3));
+        // Enum columns are USER-DEFINED, their values are the labels of the type.
+        let enum_values = match data_type.as_str() {
+            "USER-DEFINED" => {
+                let labels = client
+                    .query(
+                        "SELECT e.enumlabel::text FROM pg_type t JOIN pg_enum e ON e.enumtypid = t.oid WHERE t.typname = $1 ORDER BY e.enumsortorder",
+                        &[&udt_name],
+                    )
+                    .map_err(|error| format!("pg_enum: {error}"))?;
+                let values: Vec<String> = labels.iter().map(|label| label.get(0)).collect();
+                (!values.is_empty()).then_some((udt_name.clone(), values))
+            }
+            _ => None,
+        };
+        columns.push(IntrospectedColumn {
+            table: row.get(0),
+            name: row.get(1),
+            sql_type: if data_type == "USER-DEFINED" { udt_name } else { data_type },
+            nullable: row.get(4),
+            auto_increment: row.get(5),
+            primary: row.get(6),
+            enum_values,
+        });
+    }
+
+    Ok(columns)
+}
+
+#[cfg(not(feature = "postgres"))]
+fn introspect_postgres(_url: &str) -> Result<Vec<IntrospectedColumn>, String> {
+    Err("this crudify is built without the postgres feature, rebuild it with cargo build --features postgres".to_string())
+}
+
+#[cfg(feature = "mysql")]
+fn introspect_mysql(url: &str) -> Result<Vec<IntrospectedColumn>, String> {
+    use mysql::prelude::Queryable;
+
+    let options = mysql::Opts::from_url(url).map_err(|error| format!("invalid MySQL URL: {error}"))?;
+    let mut connection = mysql::Conn::new(options).map_err(|error| format!("cannot connect to the MySQL database: {error}"))?;
+    let tables: Vec<String> = connection.query("SHOW FULL TABLES WHERE Table_type = 'BASE TABLE'").map_err(|error| format!("SHOW TABLES: {error}"))?;
+
+    let enum_regex = Regex::new(r"'((?:[^']|'')*)'").unwrap();
+    let mut columns = Vec::new();
+    for table in tables {
+        let rows: Vec<(String, String, String, String, Option<String>, String)> = connection
+            .query(format!("SHOW COLUMNS FROM `{}`", table.replace('`', "``")))
+            .map_err(|error| format!("SHOW COLUMNS FROM {table}: {error}"))?;
+        for (name, column_type, null, key, _default, extra) in rows {
+            let enum_values = column_type
+                .starts_with("enum(")
+                .then(|| enum_regex.captures_iter(&column_type).map(|captures| captures[1].replace("''", "'")).collect::<Vec<_>>())
+                .map(|values| (format!("{}{}", capitalize(&table), capitalize(&name)), values));
+            columns.push(IntrospectedColumn {
+                table: table.clone(),
+                name,
+                sql_type: column_type,
+                nullable: null == "YES",
+                primary: key == "PRI",
+                auto_increment: extra.contains("auto_increment"),
+                enum_values,
+            });
+        }
+    }
+
+    Ok(columns)
+}
+
+#[cfg(not(feature = "mysql"))]
+fn introspect_mysql(_url: &str) -> Result<Vec<IntrospectedColumn>, String> {
+    Err("this crudify is built without the mysql feature, rebuild it with cargo build --features mysql".to_string())
+}
+
+// The db_type and js_type of a column, by the name of its SQL type without the length or precision.
+// The statements of a SQL dump, split on the semicolons outside of quotes, with the comments left out.
+fn sql_statements(sql: &str) -> Vec<String> {
+    let mut statements = vec![String::new()];
+    let mut characters = sql.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(character) = characters.next() {
+        let statement = statements.last_mut().unwrap();
+        match (quote, character) {
+            (Some(open), _) => {
+                statement.push(character);
+                if character == open {
+                    quote = None;
+                }
+            }
+            (None, '\'' | '"' | '`') => {
+                quote = Some(character);
+                statement.push(character);
+            }
+            (None, '[') => {
+                quote = Some(']');
+                statement.push(character);
+            }
+            (None, '-') if characters.peek() == Some(&'-') => {
+                characters.by_ref().find(|character| *character == '\n');
+                statement.push('\n');
+            }
+            (None, '/') if characters.peek() == Some(&'*') => {
+                characters.next();
+                let mut previous = ' ';
+                characters.by_ref().find(|character| std::mem::replace(&mut previous, *character) == '*' && *character == '/');
+                statement.push(' ');
+            }
+            (None, ';') => statements.push(String::new()),
+            (None, _) => statement.push(character),
+        }
+    }
+    statements.into_iter().map(|statement| statement.trim().to_string()).filter(|statement| !statement.is_empty()).collect()
+}
+
+// Splits on the commas outside of parentheses and quotes, the columns of a table or the values of an enum.
+fn sql_list(list: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for character in list.chars() {
+        match (quote, character) {
+            (Some(open), _) if character == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(character),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                items.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        items.last_mut().unwrap().push(character);
+    }
+    items.into_iter().map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect()
+}
+
+// public."blog_posts" is blog_posts, the schema is left out along with the quotes.
+fn sql_name(name: &str) -> String {
+    let name = name.rsplit('.').next().unwrap_or(name).trim();
+    name.trim_matches(['"', '`', '[', ']']).to_string()
+}
+
+// The string literals of ENUM('draft', 'published') or CREATE TYPE ... AS ENUM (...).
+fn sql_enum_values(list: &str) -> Vec<String> {
+    sql_list(list).iter().filter_map(|value| Some(value.strip_prefix('\'')?.strip_suffix('\'')?.replace("''", "'"))).collect()
+}
+
+// Reads the CREATE TABLE statements of a SQL dump into the columns introspect reads from a database. The primary
+// keys pg_dump adds with ALTER TABLE afterwards are read as well, along with the enum types of CREATE TYPE.
+fn sql_columns(sql: &str) -> Result<Vec<IntrospectedColumn>, String> {
+    let name = r#"((?:[\w$]+|"[^"]+"|`[^`]+`|\[[^\]]+\])(?:\s*\.\s*(?:[\w$]+|"[^"]+"|`[^`]+`|\[[^\]]+\]))?)"#;
+    let create_table = Regex::new(&format!(r"(?is)^create\s+(?:(?:global|local)\s+)?(?:temp(?:orary)?\s+)?table\s+(?:if\s+not\s+exists\s+)?{name}\s*\((.*)\)[^)]*$")).unwrap();
+    let create_type = Regex::new(&format!(r"(?is)^create\s+type\s+{name}\s+as\s+enum\s*\((.*)\)\s*$")).unwrap();
+    let alter_primary = Regex::new(&format!(r"(?is)^alter\s+table\s+(?:only\s+)?{name}\s+add\s+(?:constraint\s+\S+\s+)?primary\s+key\s*\(([^)]*)\)")).unwrap();
+    let alter_identity = Regex::new(&format!(r"(?is)^alter\s+table\s+(?:only\s+)?{name}\s+alter\s+column\s+{name}\s+add\s+generated\b")).unwrap();
+    let table_primary = Regex::new(r"(?is)^(?:constraint\s+\S+\s+)?primary\s+key\s*\(([^)]*)\)").unwrap();
+    let table_constraint = Regex::new(r"(?i)^(?:constraint|foreign|unique|key|index|check|fulltext|spatial|exclude)\b").unwrap();
+    let column_regex = Regex::new(&format!(r"(?is)^{name}\s+(.*)$")).unwrap();
+    // The words that end the type of a column and start its constraints.
+    let constraint = Regex::new(r"(?i)\s(?:not|null|primary|default|references|unique|check|constraint|auto_increment|autoincrement|generated|identity|collate|comment|on)\b").unwrap();
+
+    let statements = sql_statements(sql);
+    let mut enum_types: HashMap<String, Vec<String>> = HashMap::new();
+    for captures in statements.iter().filter_map(|statement| create_type.captures(statement)) {
+        enum_types.insert(sql_name(&captures[1]).to_lowercase(), sql_enum_values(&captures[2]));
+    }
+
+    let mut columns: Vec<IntrospectedColumn> = Vec::new();
+    for statement in &statements {
+        if let Some(captures) = create_table.captures(statement) {
+            let table = sql_name(&captures[1]);
+            let mut primary: Vec<String> = Vec::new();
+            let start = columns.len();
+            for item in sql_list(&captures[2]) {
+                if let Some(key) = table_primary.captures(&item) {
+                    primary.extend(sql_list(&key[1]).iter().map(|column| sql_name(column)));
+                    continue;
+                }
+                if table_constraint.is_match(&item) {
+                    continue;
+                }
+                let Some(column) = column_regex.captures(&item) else {
+                    return Err(format!("{table}: cannot read the column {item}"));
+                };
+                let definition = format!(" {}", &column[2]);
+                let type_end = constraint.find(&definition).map_or(definition.len(), |found| found.start());
+                let sql_type = definition[..type_end].trim().to_string();
+                let rest = definition[type_end..].to_uppercase();
+                let lowercase = sql_type.to_lowercase();
+                let serial = ["serial", "bigserial", "smallserial"].contains(&lowercase.as_str());
+                let enum_values = match lowercase.strip_prefix("enum") {
+                    Some(values) if values.trim_start().starts_with('(') => {
+                        let values = values.trim_start().strip_prefix('(').and_then(|values| values.strip_suffix(')')).unwrap_or_default();
+                        Some((format!("{}{}", capitalize(&table), capitalize(&sql_name(&column[1]))), sql_enum_values(values)))
+                    }
+                    _ => enum_types.get(&sql_name(&lowercase)).map(|values| (sql_name(&sql_type), values.clone())),
+                };
+                let is_primary = rest.contains("PRIMARY KEY");
+                columns.push(IntrospectedColumn {
+                    table: table.clone(),
+                    name: sql_name(&column[1]),
+                    sql_type,
+                    nullable: !is_primary && !serial && !rest.contains("NOT NULL"),
+                    primary: is_primary,
+                    auto_increment: serial || ["AUTO_INCREMENT", "AUTOINCREMENT", "IDENTITY", "NEXTVAL("].iter().any(|word| rest.contains(word)),
+                    enum_values,
+                });
+            }
+            for column in columns[start..].iter_mut().filter(|column| primary.contains(&column.name)) {
+                column.primary = true;
+                column.nullable = false;
+            }
+        } else if let Some(captures) = alter_primary.captures(statement) {
+            let (table, keys) = (sql_name(&captures[1]), sql_list(&captures[2]).iter().map(|column| sql_name(column)).collect::<Vec<_>>());
+            for column in columns.iter_mut().filter(|column| column.table == table && keys.contains(&column.name)) {
+                column.primary = true;
+                column.nullable = false;
+            }
+        } else if let Some(captures) = alter_identity.captures(statement) {
+            let (table, name) = (sql_name(&captures[1]), sql_name(&captures[2]));
+            for column in columns.iter_mut().filter(|column| column.table == table && column.name == name) {
+                column.auto_increment = true;
+            }
+        }
+    }
+
+    Ok(columns)
+}
+
+fn introspected_type(column: &IntrospectedColumn) -> (&'static str, &'static str) {
+    if column.enum_values.is_some() {
+        return ("ENUM", "string");
+    }
+    let sql_type = column.sql_type.to_lowercase();
+    // MySQL keeps booleans as tinyint(1).
+    if sql_type.starts_with("tinyint(1)") {
+        return ("BOOLEAN", "boolean");
+    }
+    let base = sql_type.split(['(', ' ']).next().unwrap_or_default();
+    match base {
+        "integer" | "int" | "int2" | "int4" | "smallint" | "mediumint" | "tinyint" | "serial" => ("INTEGER", "number"),
+        "bigint" | "int8" | "bigserial" => ("BIGINT", "number"),
+        "real" | "float4" => ("REAL", "number"),
+        "float" => ("FLOAT", "number"),
+        "double" | "float8" => ("DOUBLE", "number"),
+        "numeric" | "decimal" => ("DECIMAL", "number"),
+        "boolean" | "bool" | "bit" => ("BOOLEAN", "boolean"),
+        "timestamp" | "timestamptz" | "datetime" => ("DATE", "Date"),
+        "date" => ("DATEONLY", "string"),
+        "time" | "timetz" => ("TIME", "string"),
+        "uuid" => ("UUID", "string"),
+        "json" | "jsonb" => ("JSON", "object"),
+        "geometry" => ("GEOMETRY", "object"),
+        "geography" => ("GEOGRAPHY", "object"),
+        "text" | "tinytext" | "mediumtext" | "longtext" => ("TEXT", "string"),
+        "citext" => ("CITEXT", "string"),
+        "character" | "char" | "varchar" => ("STRING", "string"),
+        _ => {
+            warn(format!("{}.{} has the type {}, introspected as STRING", column.table, column.name, column.sql_type));
+            ("STRING", "string")
+        }
+    }
+}
+
+// blog_posts is the model BlogPost with the plural BlogPosts.
+fn introspected_names(table: &str) -> (String, String) {
+    let plural: String = name_words(table).iter().map(|word| capitalize(word)).collect();
+    let singular = match plural.as_str() {
+        name if name.ends_with("ies") => format!("{}y", &name[..name.len() - 3]),
+        name if name.ends_with("ss") || !name.ends_with('s') => name.to_string(),
+        name => name[..name.len() - 1].to_string(),
+    };
+    (singular, plural)
+}
+
+// The schema generate reads, one model per table with the columns as its fields.
+fn introspected_schema(columns: &[IntrospectedColumn], tables: &[String]) -> Value {
+    let mut models = serde_json::Map::new();
+    let mut enums = serde_json::Map::new();
+    let mut table_names: Vec<&str> = Vec::new();
+    for column in columns {
+        if !table_names.contains(&column.table.as_str()) {
+            table_names.push(&column.table);
+        }
+    }
+
+    for table in table_names {
+        if MIGRATION_TABLES.contains(&table) || (!tables.is_empty() && !tables.iter().any(|name| name == table)) {
+            continue;
+        }
+        // Sequelize keeps timestamps with both a created and an updated column, paranoid tables only along with them.
+        let has_column = |names: [&str; 2]| columns.iter().any(|column| column.table == table && names.contains(&column.name.as_str()));
+        let timestamps = has_column(["createdAt", "created_at"]) && has_column(["updatedAt", "updated_at"]);
+        let paranoid = timestamps && has_column(["deletedAt", "deleted_at"]);
+
+        let mut fields = serde_json::Map::new();
+        for column in columns.iter().filter(|column| column.table == table) {
+            if !identifier_regex().is_match(&column.name) || RESERVED_WORDS.contains(&column.name.as_str()) {
+                warn(format!("{table}.{} is not a valid field name, it is left out", column.name));
+                continue;
+            }
+            let (db_type, js_type) = introspected_type(column);
+            let mut attr = Vec::new();
+            if column.primary {
+                attr.push("@PrimaryKey");
+            }
+            if column.auto_increment {
+                attr.push("@AutoIncrement");
+            }
+            if column.nullable && !column.primary {
+                attr.push("@AllowNull");
+            }
+            match column.name.as_str() {
+                "createdAt" | "created_at" if timestamps => attr.push("@CreatedAt"),
+                "updatedAt" | "updated_at" if timestamps => attr.push("@UpdatedAt"),
+                "deletedAt" | "deleted_at" if paranoid => attr.push("@DeletedAt"),
+                _ => {}
+            }
+            let mut field = json!({ "db_type": db_type, "js_type": js_type });
+            if !attr.is_empty() {
+                field["attr"] = json!(attr);
+            }
+            if let Some((enum_name, values)) = &column.enum_values {
+                let enum_name: String = name_words(enum_name).iter().map(|word| capitalize(word)).collect();
+                field["enum"] = json!(enum_name);
+                enums.insert(enum_name, json!(values));
+            }
+            fields.insert(column.name.clone(), field);
+        }
+
+        let (name, plural) = introspected_names(table);
+        let mut model = json!({ "plural": plural, "tableName": table });
+        if !timestamps {
+            model["timestamps"] = json!(false);
+        }
+        if paranoid {
+            model["paranoid"] = json!(true);
+        }
+        model["fields"] = Value::Object(fields);
+        models.insert(name, model);
+    }
+
+    let mut schema = json!({});
+    if !enums.is_empty() {
+        schema["enums"] = Value::Object(enums);
+    }
+    schema["models"] = Value::Object(models);
+    schema
+}
+
+// crudify introspect reads the tables of an existing database into a schema, written to --output or stdout,
+// or with --generate piped into crudify generate along with the remaining arguments.
+fn introspect(args: &IntrospectArgs) -> Result<(), String> {
+    let url = args.db_url.clone().or_else(|| env::var("DATABASE_URL").ok()).ok_or("introspect needs --db-url <url> or DATABASE_URL")?;
+    // The schema printed to stdout can be piped into crudify --config -, the warnings go to stderr.
+    STDOUT_RESERVED.get_or_init(|| args.schema.output.is_none() && args.schema.generate.is_none());
+
+    let columns = match url.split_once("://").map(|(scheme, _)| scheme) {
+        Some("postgres") | Some("postgresql") => introspect_postgres(&url)?,
+        Some("mysql") => introspect_mysql(&url)?,
+        _ => return Err("Unsupported database URL, expected postgres:// or mysql://".to_string()),
+    };
+    if columns.is_empty() {
+        return Err("The database has no tables to introspect".to_string());
+    }
+    write_introspected(&columns, &args.schema)
+}
+
+// crudify import-sql reads the same schema as introspect out of a SQL dump, without a database to connect to.
+fn import_sql(args: &ImportSqlArgs) -> Result<(), String> {
+    STDOUT_RESERVED.get_or_init(|| args.schema.output.is_none() && args.schema.generate.is_none());
+    let source = match args.file.to_str() {
+        Some("-") => "stdin".to_string(),
+        _ => args.file.display().to_string(),
+    };
+    let sql = match args.file.to_str() {
+        Some("-") => io::read_to_string(io::stdin()),
+        _ => fs::read_to_string(&args.file),
+    }
+    .map_err(|error| format!("{source}: {error}"))?;
+    let columns = sql_columns(&sql).map_err(|error| format!("{source}: {error}"))?;
+    if columns.is_empty() {
+        return Err(format!("{source} has no CREATE TABLE statements"));
+    }
+    write_introspected(&columns, &args.schema)
+}
+
+fn write_introspected(columns: &[IntrospectedColumn], args: &SchemaOutputArgs) -> Result<(), String> {
+    let tables: Vec<String> = args.tables.iter().map(|table| table.trim().to_string()).collect();
+    let schema = introspected_schema(columns, &tables);
+    if schema["models"].as_object().is_none_or(|models| models.is_empty()) {
+        return Err("There are no tables to read into a schema".to_string());
+    }
+    let content = serde_yaml::to_string(&schema).map_err(|error| error.to_string())?;
+
+    if let Some(output) = &args.output {
+        fs::write(output, &content).map_err(|error| format!("{}: {error}", output.display()))?;
+        eprintln!("{}", output.display());
+    }
+    let Some(generate) = &args.generate else {
+        if args.output.is_none() {
+            print!("{content}");
+        }
+        return Ok(());
+    };
+
+    let executable = env::current_exe().map_err(|error| error.to_string())?;
+    let mut child = process::Command::new(executable)
+        .args(["generate", "--config", "-"])
+        .args(generate)
+        .stdin(process::Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("crudify generate: {error}"))?;
+    child.stdin.take().map(|mut stdin| stdin.write_all(content.as_bytes())).transpose().map_err(|error| error.to_string())?;
+    let status = child.wait().map_err(|error| error.to_string())?;
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+// Schemas self-test generates, with the generate flags each one needs.
+const SELF_TEST_SCHEMAS: &[(&str, &str, &[&str])] = &[
+    ("minimal", include_str!("../self_test/minimal.yaml"), &["--allow-empty"]),
+    ("all_types", include_str!("../self_test/all_types.yaml"), &[]),
+    ("nullable", include_str!("../self_test/nullable.yaml"), &[]),
+    ("relations", include_str!("../self_test/relations.yaml"), &[]),
+    ("enum", include_str!("../self_test/enum.yaml"), &[]),
+    ("uuid_key", include_str!("../self_test/uuid_key.yaml"), &[]),
+    ("composite_key", include_str!("../self_test/composite_key.yaml"), &["--composite-pk", "orderId,productId"]),
+];
+
+// Where the runs of self-test write, inside the directory of each schema.
+const SELF_TEST_OUTPUT: &str = "out";
+
+// The generated project has no node_modules, so the imports of its packages cannot resolve.
+const UNRESOLVED_MODULE_ERRORS: &[&str] = &["TS2307", "TS2792", "TS7016"];
+
+const SELF_TEST_TSCONFIG: &str = r#"{
+  "compilerOptions": {
+    "target": "ES2020",
+    "module": "commonjs",
+    "moduleResolution": "node",
+    "noEmit": true,
+    "skipLibCheck": true,
+    "esModuleInterop": true,
+    "experimentalDecorators": true,
+    "emitDecoratorMetadata": true,
+    "baseUrl": ".",
+    "paths": { "@core/*": ["core/*"], "@infrastructure/*": ["infrastructure/*"], "@presentation/*": ["presentation/*"] }
+  },
+  "include": ["**/*.ts"]
+}
+"#;
+
+// A generated file of one self-test schema and what is wrong with it.
+struct SelfTestFile {
+    artifact: &'static str,
+    path: PathBuf,
+    problems: Vec<String>
+}
+
+// Generates every SELF_TEST_SCHEMAS schema with the templates in a scratch directory, and checks the files
+// for placeholders left in them and, when a TypeScript compiler is installed, for type errors.
+// False when a schema failed.
+fn self_test(args: &SelfTestArgs) -> Result<bool, String> {
+    let (keep_output, lang, typecheck) = (&args.keep_output, args.lang.as_str(), !args.no_tsc);
+    let templates = args
+        .templates
+        .as_ref()
+        .map(|templates| fs::canonicalize(templates).ok().filter(|templates| templates.is_dir()).ok_or(format!("{} is not a directory", templates.display())))
+        .transpose()?;
+    let project = env::current_dir().map_err(|error| error.to_string())?;
+
+    let root = keep_output.clone().unwrap_or_else(|| env::temp_dir().join(format!("crudify-self-test-{}", process::id())));
+    let compiler = if typecheck && lang == "ts" { typescript_compiler(&project) } else { None };
+    if typecheck && lang == "ts" && compiler.is_none() {
+        notice("No tsc in node_modules/.bin or on the PATH, the output is not type checked");
+    }
+
+    let mut results = Vec::new();
+    for (name, schema, flags) in SELF_TEST_SCHEMAS {
+        let schema_dir = root.join(name);
+        if schema_dir.exists() {
+            fs::remove_dir_all(&schema_dir).map_err(|error| format!("{}: {error}", schema_dir.display()))?;
+        }
+        let prepared = fs::create_dir_all(&schema_dir)
+            .and_then(|_| fs::write(schema_dir.join("schema.yaml"), schema))
+            .and_then(|_| match project.join(CONFIG_FILE).is_file() {
+                true => fs::copy(project.join(CONFIG_FILE), schema_dir.join(CONFIG_FILE)).map(|_| ()),
+                false => Ok(()),
+            })
+            .and_then(|_| templates.as_ref().map_or(Ok(()), |templates| copy_directory(templates, &schema_dir.join(LOCAL_TEMPLATES_DIR))));
+        prepared.map_err(|error| format!("{}: {error}", schema_dir.display()))?;
+
+        let result = self_test_schema(&schema_dir, flags, lang, templates.is_none().then_some(project.as_path()), compiler.as_deref());
+        results.push((*name, result));
+    }
+
+    print_self_test(&results, keep_output.is_some());
+    if keep_output.is_none() {
+        let _ = fs::remove_dir_all(&root);
+    }
+    Ok(results.iter().all(|(_, result)| result.as_ref().is_ok_and(|files| files.iter().all(|file| file.problems.is_empty()))))
+}
+
+// Runs generate on one schema directory, with the .env templates read from the project when
+// --templates is not given.
+fn self_test_schema(schema_dir: &Path, flags: &[&str], lang: &str, template_root: Option<&Path>, compiler: Option<&Path>) -> Result<Vec<SelfTestFile>, String> {
+    let executable = env::current_exe().map_err(|error| error.to_string())?;
+    let mut command = process::Command::new(executable);
+    command
+        .args(["generate", "--config", "schema.yaml", "--json", "--lang", lang])
+        .args(flags)
+        .current_dir(schema_dir)
+        .env("CRUDIFY_OUTPUT", SELF_TEST_OUTPUT);
+    match template_root {
+        Some(template_root) => {
+            command.arg("--template-root").arg(template_root);
+        }
+        // The copied crudify-templates come after the .env paths, those would hide them.
+        None => {
+            for (key, _) in env::vars().filter(|(key, _)| key.ends_with("_TEMPLATE")) {
+                command.env_remove(key);
+            }
+        }
+    }
+    let output = command.output().map_err(|error| format!("crudify generate: {error}"))?;
+
+    let report: Option<Value> = String::from_utf8_lossy(&output.stdout).lines().rev().find_map(|line| serde_json::from_str(line).ok());
+    let Some(report) = report else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("crudify generate printed no report").to_string());
+    };
+    if let Some(error) = report["error"].as_str() {
+        return Err(error.to_string());
+    }
+
+    let output_dir = schema_dir.join(SELF_TEST_OUTPUT);
+    let placeholder = Regex::new(r"\{([A-Z][A-Z0-9_]*)\}").unwrap();
+    let mut files: Vec<SelfTestFile> = report["files"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|file| matches!(file["action"].as_str(), Some("created" | "modified")))
+        .filter_map(|file| file["path"].as_str())
+        .filter(|path| !path.starts_with(".crudify/"))
+        .map(|path| {
+            let content = fs::read_to_string(output_dir.join(path)).unwrap_or_default();
+            let mut left: Vec<String> = Vec::new();
+            for captures in placeholder.captures_iter(&content) {
+                let name = format!("{{{}}}", &captures[1]);
+                if (is_builtin_placeholder(&captures[1]) || captures[1].starts_with("DYNAMIC_")) && !left.contains(&name) {
+                    left.push(name);
+                }
+            }
+            let problems = if left.is_empty() { Vec::new() } else { vec![format!("still has {}", left.join(", "))] };
+            SelfTestFile { artifact: self_test_artifact(Path::new(path)), path: output_dir.join(path), problems }
+        })
+        .collect();
+
+    if let Some(compiler) = compiler {
+        fs::write(output_dir.join("tsconfig.json"), SELF_TEST_TSCONFIG).map_err(|error| error.to_string())?;
+        let output = process::Command::new(compiler)
+            .args(["-p", "tsconfig.json", "--pretty", "false"])
+            .current_dir(&output_dir)
+            .output()
+            .map_err(|error| format!("tsc: {error}"))?;
+        let diagnostic = Regex::new(r"^(.+?)\((\d+,\d+)\): error (TS\d+): (.*)$").unwrap();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let Some(captures) = diagnostic.captures(line) else { continue };
+            if UNRESOLVED_MODULE_ERRORS.contains(&&captures[3]) {
+                continue;
+            }
+            let path = output_dir.join(&captures[1]);
+            let problem = format!("({}) {}: {}", &captures[2], &captures[3], &captures[4]);
+            match files.iter_mut().find(|file| file.path == path) {
+                Some(file) => file.problems.push(problem),
+                None => files.push(SelfTestFile { artifact: self_test_artifact(Path::new(&captures[1])), path, problems: vec![problem] }),
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+// The ARTIFACTS entry a generated file is, utils for the types and requests, other for the shared files.
+fn self_test_artifact(path: &Path) -> &'static str {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let artifact = ARTIFACTS.iter().find(|(_, pattern)| {
+        let pattern = regex::escape(pattern)
+            .replace(r"\{name_lower\}", "[a-z0-9_]+")
+            .replace(r"\{NAME_PLURAL\}", "[A-Z][A-Za-z0-9_]*")
+            .replace(r"\{NAME\}", "[A-Z][A-Za-z0-9_]*")
+            .replace(r"\.ts", r"\.(ts|js)");
+        Regex::new(&format!("^{pattern}$")).is_ok_and(|pattern| pattern.is_match(&file_name))
+    });
+    match artifact {
+        Some((artifact, _)) => artifact,
+        None if path.components().any(|component| component.as_os_str() == "utils") => "utils",
+        None => "other",
+    }
+}
+
+fn typescript_compiler(project: &Path) -> Option<PathBuf> {
+    let local = ["tsc", "tsc.cmd"].iter().map(|name| project.join("node_modules").join(".bin").join(name)).find(|path| path.is_file());
+    local.or_else(|| {
+        process::Command::new("tsc")
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|_| PathBuf::from("tsc"))
+    })
+}
+
+fn copy_directory(source: &Path, target: &Path) -> io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        match entry.file_type()?.is_dir() {
+            true => copy_directory(&entry.path(), &target.join(entry.file_name()))?,
+            false => fs::copy(entry.path(), target.join(entry.file_name())).map(|_| ())?,
+        }
+    }
+    Ok(())
+}
+
+// One row per schema with whether it generated and a column per artifact, ok, FAIL or - when the schema
+// has none, then the failures.
+fn print_self_test(results: &[(&str, Result<Vec<SelfTestFile>, String>)], kept: bool) {
+    let mut columns: Vec<&str> = ARTIFACTS.iter().map(|(artifact, _)| *artifact).chain(["utils", "other"]).collect();
+    columns.retain(|column| results.iter().any(|(_, result)| result.as_ref().is_ok_and(|files| files.iter().any(|file| file.artifact == *column))));
+    let width = results.iter().map(|(name, _)| name.len()).max().unwrap_or_default();
+
+    let header: String = columns.iter().map(|column| format!("  {column}")).collect();
+    println!("{:width$}  generate{header}", "schema");
+    for (name, result) in results {
+        let cells: String = columns
+            .iter()
+            .map(|column| {
+                let cell = match result {
+                    Err(_) => "-",
+                    Ok(files) => {
+                        let artifact_files: Vec<&SelfTestFile> = files.iter().filter(|file| file.artifact == *column).collect();
+                        match artifact_files.iter().all(|file| file.problems.is_empty()) {
+                            _ if artifact_files.is_empty() => "-",
+                            true => "ok",
+                            false => "FAIL",
+                        }
+                    }
+                };
+                format!("  {cell:<len$}", len = column.len())
+            })
+            .collect();
+        let generated = if result.is_ok() { "ok" } else { "FAIL" };
+        println!("{}", format!("{name:width$}  {generated:<8}{cells}").trim_end());
+    }
+
+    let mut failed = false;
+    for (name, result) in results {
+        match result {
+            Err(error) => {
+                println!("\n{name}: {error}");
+                failed = true;
+            }
+            Ok(files) => {
+                for file in files.iter().filter(|file| !file.problems.is_empty()) {
+                    println!("\n{name}/{}: {}", file.artifact, file.path.display());
+                    for problem in &file.problems {
+                        println!("  {problem}");
+                    }
+                    failed = true;
+                }
+            }
+        }
+    }
+    if failed && !kept {
+        println!("\nPass --keep-output <dir> to keep the generated files for inspection");
+    }
+}
+
+fn seed_from_csv(path: &Path, args: &SeedArgs) -> Result<(), String> {
+    let (model_name, csv_path, date_format) = (&args.model, &args.csv, &args.date_format);
+    let mut mappings = Vec::new();
+    for mapping in &args.map {
+        let (column, field) = mapping.split_once('=').ok_or(format!("--map expects csvCol=field, got {mapping}"))?;
+        mappings.push((column.to_string(), field.to_string()));
+    }
+
+    let models = match &args.config {
+        Some(schema_path) => load_models(Path::new(schema_path), None, false, true)?,
+        None => vec![example_model(false)],
+    };
+    let model = models
+        .iter()
+        .find(|model| model.name.eq_ignore_ascii_case(model_name))
+        .ok_or(format!("Unknown model: {model_name}"))?;
+
+    let mut reader = csv::Reader::from_path(csv_path).map_err(|error| format!("{csv_path}: {error}"))?;
+    let headers = reader.headers().map_err(|error| format!("{csv_path}: {error}"))?.clone();
+
+    // Column index to field, explicit --map entries win over case-insensitive name matches.
+    let mut columns = Vec::new();
+    for (index, header) in headers.iter().enumerate() {
+        let target = mappings
+            .iter()
+            .find(|(column, _)| column == header)
+            .map_or(header, |(_, field)| field.as_str());
+        let matches = |field: &&Field| field.name.eq_ignore_ascii_case(target) || model.column_name(&field.name).eq_ignore_ascii_case(target);
+        match model.fields.iter().find(matches) {
+            Some(field) if field.is_virtual() => warn(format!("column {header} matches VIRTUAL field {} and is ignored", field.name)),
+            Some(field) => columns.push((index, field)),
+            None if target != header => return Err(format!("--map target {target} is not a field of {}", model.name)),
+            None => warn(format!("column {header} does not match any field of {} and is ignored", model.name)),
+        }
+    }
+
+    if columns.is_empty() {
+        return Err(format!("No column of {csv_path} matches a field of {}", model.name));
+    }
+
+    let seeders = path.join("infrastructure").join("seeders");
+    fs::create_dir_all(&seeders).map_err(|error| error.to_string())?;
+    let seeder_path = seeders.join(format!("{}-seed-{}.js", migration_timestamp(), model.name.to_lowercase()));
+
+    let placeholders = load_config().and_then(|config| config_placeholders(&config))?;
+    PLACEHOLDERS.get_or_init(|| placeholders);
+    let content = copy_template("SEEDER_TEMPLATE", Some(SEEDER_TEMPLATE), model, &TemplateCache::default())
+        .map_err(|error| format!("SEEDER_TEMPLATE: {error}"))?;
+    let content = replace_table_name(&content, model);
+    let (head, tail) = content.split_once("{DYNAMIC_SEED_ROWS}").ok_or("Seeder template is missing {DYNAMIC_SEED_ROWS}")?;
+
+    let mut timestamps = String::new();
+    if model.timestamps() && !model.fields.iter().any(|field| field.has_attr("@CreatedAt")) {
+        timestamps.push_str(&format!(", {}: now", model.column_name("createdAt")));
+    }
+    if model.timestamps() && !model.fields.iter().any(|field| field.has_attr("@UpdatedAt")) {
+        timestamps.push_str(&format!(", {}: now", model.column_name("updatedAt")));
+    }
+
+    // Rows are streamed straight into the seeder so large files never sit in memory.
+    let write_error = |error: io::Error| format!("{}: {error}", seeder_path.display());
+    let temporary = temporary_path(&seeder_path);
+    let mut file = BufWriter::new(File::create(&temporary).map_err(write_error)?);
+    file.write_all(head.as_bytes()).map_err(write_error)?;
+
+    let (mut written, mut invalid) = (0, 0);
+    for record in reader.records() {
+        let record = record.map_err(|error| format!("{csv_path}: {error}"))?;
+        let line = record.position().map_or(0, |position| position.line());
+
+        let mut row = serde_json::Map::new();
+        let mut errors = Vec::new();
+        for (index, field) in &columns {
+            match parse_cell(field, record.get(*index).unwrap_or_default(), date_format.as_deref()) {
+                Ok(value) => {
+                    row.insert(model.column_name(&field.name), value);
+                }
+                Err(error) => errors.push(format!("{}: {error}", field.name)),
+            }
+        }
+
+        if !errors.is_empty() {
+            invalid += 1;
+            println!("Line {line}: {}", errors.join("; "));
+            continue;
+        }
+
+        let row = Value::Object(row).to_string();
+        file.write_all(format!("\t\t\t{}{timestamps} }},\n", row.trim_end_matches('}')).as_bytes()).map_err(write_error)?;
+        written += 1;
+    }
+
+    if invalid > 0 && !args.skip_invalid {
+        drop(file);
+        fs::remove_file(&temporary).map_err(write_error)?;
+        return Err(format!("{invalid} invalid rows in {csv_path}, rerun with --skip-invalid to leave them out"));
+    }
+
+    file.write_all(tail.trim_start_matches('\n').as_bytes()).map_err(write_error)?;
+    file.flush().map_err(write_error)?;
+    drop(file);
+    fs::rename(&temporary, &seeder_path).map_err(write_error)?;
+    println!("{} ({written} rows, {invalid} skipped)", seeder_path.display());
+
+    Ok(())
+}
+
+// Built-in templates by the name they are looked up under in crudify-templates, TypeScript then JavaScript.
+// The container and request logger are left out, which one applies depends on --di and --logging.
+const BUILTIN_TEMPLATES: &[(&str, Option<&str>, Option<&str>)] = &[
+    ("model", None, Some(MODEL_JS_TEMPLATE)),
+    ("health_routes", Some(HEALTH_ROUTES_TEMPLATE), Some(HEALTH_ROUTES_JS_TEMPLATE)),
+    ("tsconfig", Some(TSCONFIG_TEMPLATE), None),
+    ("package_json", Some(PACKAGE_JSON_TEMPLATE), Some(PACKAGE_JSON_TEMPLATE)),
+    ("unit_of_work", Some(UNIT_OF_WORK_TEMPLATE), Some(UNIT_OF_WORK_JS_TEMPLATE)),
+    ("api_response", Some(API_RESPONSE_TEMPLATE), Some(API_RESPONSE_JS_TEMPLATE)),
+    ("bigint", Some(BIGINT_TEMPLATE), Some(BIGINT_JS_TEMPLATE)),
+    ("crypto", Some(CRYPTO_TEMPLATE), Some(CRYPTO_JS_TEMPLATE)),
+    ("abstract_repository", Some(ABSTRACT_REPOSITORY_TEMPLATE), Some(ABSTRACT_REPOSITORY_JS_TEMPLATE)),
+    ("abstract_use_case", Some(ABSTRACT_USE_CASE_TEMPLATE), Some(ABSTRACT_USE_CASE_JS_TEMPLATE)),
+    ("factory", Some(FACTORY_TEMPLATE), Some(FACTORY_JS_TEMPLATE)),
+    ("use_case_test", Some(USE_CASE_TEST_TEMPLATE), Some(USE_CASE_TEST_JS_TEMPLATE)),
+    ("aggregate", Some(AGGREGATE_TEMPLATE), Some(AGGREGATE_JS_TEMPLATE)),
+    ("event_store", Some(EVENT_STORE_TEMPLATE), Some(EVENT_STORE_JS_TEMPLATE)),
+    ("projection", Some(PROJECTION_TEMPLATE), Some(PROJECTION_JS_TEMPLATE)),
+    ("kafka_consumer", Some(KAFKA_CONSUMER_TEMPLATE), Some(KAFKA_CONSUMER_JS_TEMPLATE)),
+    ("tenant_context", Some(TENANT_CONTEXT_TEMPLATE), Some(TENANT_CONTEXT_JS_TEMPLATE)),
+    ("service", Some(SERVICE_TEMPLATE), Some(SERVICE_JS_TEMPLATE)),
+    ("service_controllers", Some(SERVICE_CONTROLLERS_TEMPLATE), Some(SERVICE_CONTROLLERS_JS_TEMPLATE)),
+    ("cached_repository", Some(CACHED_REPOSITORY_TEMPLATE), Some(CACHED_REPOSITORY_JS_TEMPLATE)),
+    ("redis", Some(REDIS_TEMPLATE), Some(REDIS_TEMPLATE)),
+    ("graphql_typedefs", Some(GRAPHQL_TYPEDEFS_TEMPLATE), Some(GRAPHQL_TYPEDEFS_TEMPLATE)),
+    ("federation_entity", Some(FEDERATION_ENTITY_TEMPLATE), Some(FEDERATION_ENTITY_TEMPLATE)),
+    ("federation_resolver", Some(FEDERATION_RESOLVER_TEMPLATE), Some(FEDERATION_RESOLVER_JS_TEMPLATE)),
+    ("events", Some(EVENTS_TEMPLATE), Some(EVENTS_JS_TEMPLATE)),
+    ("event_bus", Some(EVENT_BUS_TEMPLATE), Some(EVENT_BUS_JS_TEMPLATE)),
+    ("zod_schema", Some(ZOD_SCHEMA_TEMPLATE), Some(ZOD_SCHEMA_JS_TEMPLATE)),
+    ("entity", Some(ENTITY_TEMPLATE), Some(ENTITY_JS_TEMPLATE)),
+    ("entity_mapper", Some(ENTITY_MAPPER_TEMPLATE), Some(ENTITY_MAPPER_JS_TEMPLATE)),
+    ("upload_middleware", Some(UPLOAD_MIDDLEWARE_TEMPLATE), Some(UPLOAD_MIDDLEWARE_TEMPLATE)),
+    ("sequelize", Some(SEQUELIZE_TEMPLATE), Some(SEQUELIZE_JS_TEMPLATE)),
+    ("database_config", Some(DATABASE_CONFIG_TEMPLATE), Some(DATABASE_CONFIG_JS_TEMPLATE)),
+    ("migration", Some(MIGRATION_TEMPLATE), Some(MIGRATION_TEMPLATE)),
+    ("enum_migration", Some(ENUM_MIGRATION_TEMPLATE), Some(ENUM_MIGRATION_TEMPLATE)),
+    ("extension_migration", Some(EXTENSION_MIGRATION_TEMPLATE), Some(EXTENSION_MIGRATION_TEMPLATE)),
+    ("seeder", Some(SEEDER_TEMPLATE), Some(SEEDER_TEMPLATE)),
+    ("dockerfile", Some(DOCKERFILE_TEMPLATE), Some(DOCKERFILE_TEMPLATE)),
+];
+
+const DIFF_CONTEXT: usize = 2;
+
+// Line diff from the longest common subsequence, "-" for the old lines and "+" for the new ones.
+fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] { common[i + 1][j + 1] + 1 } else { common[i + 1][j].max(common[i][j + 1]) };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    // Only the changes and the lines around them are shown, "@@" separates the hunks.
+    let changed: Vec<usize> = lines.iter().enumerate().filter(|(_, (marker, _))| *marker != ' ').map(|(index, _)| index).collect();
+    let mut diff = String::new();
+    let mut previous: Option<usize> = None;
+    for (index, (marker, line)) in lines.iter().enumerate() {
+        if !changed.iter().any(|change| change.abs_diff(index) <= DIFF_CONTEXT) {
+            continue;
+        }
+        if previous.is_none_or(|previous| previous + 1 != index) {
+            diff.push_str("@@\n");
+        }
+        diff.push_str(&format!("{marker}{line}\n"));
+        previous = Some(index);
+    }
+    diff
+}
+
+const INIT_SCHEMA_FILE: &str = "schema.yaml";
+
+const INIT_SCHEMA: &str = r#"models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: ["@PrimaryKey", "@AutoIncrement"] }
+      title: { db_type: STRING, js_type: string }
+      content: { db_type: TEXT, js_type: string, attr: ["@AllowNull"] }
+"#;
+
+// crudify init starts a project in the current directory, generate then reads the crudify.toml by itself.
+fn init_project(args: &InitArgs) -> Result<(), String> {
+    let mut config = format!("dialect = \"{}\"\n", args.dialect);
+    if let Some(route_prefix) = &args.route_prefix {
+        config.push_str(&format!("route_prefix = {}\n", toml::Value::String(route_prefix.clone())));
+    }
+    let files = [(CONFIG_FILE, config), (INIT_SCHEMA_FILE, INIT_SCHEMA.to_string())];
+    if let Some((file, _)) = files.iter().find(|(file, _)| !args.force && Path::new(file).exists()) {
+        return Err(format!("{file} exists already, rerun with --force to overwrite it"));
+    }
+    for (file, content) in &files {
+        atomic_write(Path::new(file), content.as_bytes()).map_err(|error| format!("{file}: {error}"))?;
+        println!("Created {file}");
+    }
+
+    if args.templates {
+        upgrade_templates(&UpgradeTemplatesArgs { target_dir: PathBuf::from(LOCAL_TEMPLATES_DIR), lang: args.lang.clone(), dry_run: false })?;
+    }
+    println!("Put the templates in {LOCAL_TEMPLATES_DIR} or point the *_TEMPLATE entries of .env at them, then run crudify generate --config {INIT_SCHEMA_FILE}");
+
+    Ok(())
+}
+
+// Writes the built-in templates to the project so customized copies can be compared with them.
+fn upgrade_templates(args: &UpgradeTemplatesArgs) -> Result<(), String> {
+    let (target_dir, lang, dry_run) = (&args.target_dir, args.lang.as_str(), args.dry_run);
+
+    fs::create_dir_all(target_dir).map_err(|error| format!("{}: {error}", target_dir.display()))?;
+    let (mut created, mut updated) = (0, 0);
+    for (name, typescript, javascript) in BUILTIN_TEMPLATES {
+        let Some(builtin) = (if lang == "js" { javascript } else { typescript }) else {
+            continue;
+        };
+        let template_path = target_dir.join(format!("{name}.{lang}.tpl"));
+
+        match fs::read_to_string(&template_path) {
+            Ok(existing) if existing == *builtin => continue,
+            Ok(existing) => {
+                println!("--- {}\n+++ built-in {name}\n{}", template_path.display(), line_diff(&existing, builtin));
+                updated += 1;
+            }
+            Err(_) => {
+                println!("Created {}", template_path.display());
+                created += 1;
+            }
+        }
+        if !dry_run {
+            atomic_write(&template_path, builtin.as_bytes()).map_err(|error| format!("{}: {error}", template_path.display()))?;
+        }
+    }
+
+    for (name, builtin) in BUILTIN_PARTIALS {
+        let partial_path = target_dir.join(format!("{name}.tpl"));
+        match fs::read_to_string(&partial_path) {
+            Ok(existing) if existing == *builtin => continue,
+            Ok(existing) => {
+                println!("--- {}\n+++ built-in {name}\n{}", partial_path.display(), line_diff(&existing, builtin));
+                updated += 1;
+            }
+            Err(_) => {
+                println!("Created {}", partial_path.display());
+                created += 1;
+            }
+        }
+        if !dry_run {
+            if let Some(parent) = partial_path.parent() {
+                fs::create_dir_all(parent).map_err(|error| format!("{}: {error}", parent.display()))?;
+            }
+            atomic_write(&partial_path, builtin.as_bytes()).map_err(|error| format!("{}: {error}", partial_path.display()))?;
+        }
+    }
+
+    let verb = if dry_run { "would be" } else { "were" };
+    println!("{created} templates {verb} created and {updated} updated in {}", target_dir.display());
+
+    Ok(())
+}
+
+fn postman_request(name: &str, method: &str, segments: &[&str], body: Option<&Value>) -> Value {
+    let mut url = json!({
+        "raw": format!("{{{{BASE_URL}}}}/{}", segments.join("/")),
+        "host": ["{{BASE_URL}}"],
+        "path": segments,
+    });
+    if segments.iter().any(|segment| segment.starts_with(':')) {
+        let variables: Vec<Value> = segments.iter().filter_map(|segment| segment.strip_prefix(':')).map(|key| json!({ "key": key, "value": "1" })).collect();
+        url["variable"] = json!(variables);
+    }
+
+    let mut request = json!({ "method": method, "header": [], "url": url });
+    if let Some(body) = body {
+        request["header"] = json!([{ "key": "Content-Type", "value": "application/json" }]);
+        request["body"] = json!({
+            "mode": "raw",
+            "raw": serde_json::to_string_pretty(body).unwrap_or_default(),
+            "options": { "raw": { "language": "json" } },
+        });
+    }
+
+    json!({ "name": name, "request": request })
+}
+
+struct Endpoint {
+    name: String,
+    method: &'static str,
+    with_id: bool,
+    with_body: bool
+}
+
+fn crud_endpoints(model: &Model) -> [Endpoint; 4] {
+    [
+        Endpoint { name: format!("Add {}", model.name), method: "POST", with_id: false, with_body: true },
+        Endpoint { name: format!("Get {}", model.name_plural), method: "GET", with_id: false, with_body: false },
+        Endpoint { name: format!("Update {}", model.name), method: "PUT", with_id: true, with_body: true },
+        Endpoint { name: format!("Delete {}", model.name), method: "DELETE", with_id: true, with_body: false },
+    ]
+}
+
+fn implement_postman_collection(path: PathBuf, model: &Model, options: &Options) -> io::Result<()>{
+    let resource = resource_path(model, options);
+    let body = model.example_body();
+
+    let items: Vec<Value> = crud_endpoints(model)
+        .iter()
+        .map(|endpoint| {
+            let mut segments: Vec<&str> = resource.split('/').filter(|segment| !segment.is_empty()).collect();
+            let params = model.key_params();
+            if endpoint.with_id {
+                segments.extend(params.split('/').filter(|segment| !segment.is_empty()));
+            }
+            postman_request(&endpoint.name, endpoint.method, &segments, endpoint.with_body.then_some(&body))
+        })
+        .collect();
+
+    let collection = json!({
+        "info": { "name": model.name.as_str(), "schema": POSTMAN_SCHEMA },
+        "variable": [{ "key": "BASE_URL", "value": "http://localhost:3000" }],
+        "item": items,
+    });
+
+    write_file(&path.join(format!("{}{POSTMAN_SUFFIX}", model.name.to_lowercase())), &serde_json::to_string_pretty(&collection)?)?;
+
+    Ok(())
+}
+
+// Shared argument handling of the merge-* subcommands: an optional input
+// directory and --output file, returning the output and the files to merge.
+fn merge_inputs(default_directory: PathBuf, suffix: &str, default_output: &str, args: &MergeArgs) -> io::Result<(PathBuf, Vec<PathBuf>)> {
+    let directory = args.directory.clone().unwrap_or(default_directory);
+    let output = args.output.clone().unwrap_or_else(|| directory.join(default_output));
+
+    let mut inputs: Vec<PathBuf> = fs::read_dir(&directory)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|file_path| file_path.to_string_lossy().ends_with(suffix) && *file_path != output)
+        .collect();
+    inputs.sort();
+
+    Ok((output, inputs))
+}
+
+fn merge_postman(path: &Path, args: &MergeArgs) -> io::Result<()>{
+    let (output, collections) = merge_inputs(path.join("docs").join("postman"), POSTMAN_SUFFIX, &format!("workspace{POSTMAN_SUFFIX}"), args)?;
+
+    let mut folders = Vec::new();
+    let mut variables: Vec<Value> = Vec::new();
+    for collection_path in collections {
+        let collection: Value = serde_json::from_str(&fs::read_to_string(&collection_path)?)?;
+        for variable in collection["variable"].as_array().into_iter().flatten() {
+            if !variables.iter().any(|known| known["key"] == variable["key"]) {
+                variables.push(variable.clone());
+            }
+        }
+        folders.push(json!({ "name": collection["info"]["name"], "item": collection["item"] }));
+    }
+
+    let workspace = json!({
+        "info": { "name": "Crudify workspace", "schema": POSTMAN_SCHEMA },
+        "variable": variables,
+        "item": folders,
+    });
+
+    write_file(&output, &serde_json::to_string_pretty(&workspace)?)?;
+    println!("{}", output.display());
+
+    Ok(())
+}
+
+// A container that loads every {name}Bindings.ts module, in place of the bind calls generate adds one by one.
+fn merge_inversify(path: &Path, args: &MergeArgs) -> io::Result<()>{
+    let (output, modules) = merge_inputs(path.join("infrastructure").join("config"), BINDINGS_SUFFIX, "container.ts", args)?;
+
+    let export = Regex::new(r"export\s+const\s+(\w+)\s*=\s*new\s+ContainerModule").unwrap();
+    let mut content = CONTAINER_INVERSIFY_TEMPLATE.to_string();
+    let mut loaded = Vec::new();
+    for module_path in modules {
+        let source = fs::read_to_string(&module_path)?;
+        let module = module_path.file_name().unwrap_or_default().to_string_lossy().trim_end_matches(".ts").to_string();
+        let name = export.captures(&source).map_or_else(|| module.clone(), |captures| captures[1].to_string());
+        content = insert_import(&content, &format!("import {{ {name} }} from \"@infrastructure/config/{module}\";"));
+        loaded.push(name);
+    }
+    content = format!("{}\ncontainer.load({});\n", content.trim_end(), loaded.join(", "));
+
+    write_file(&output, &content)?;
+    println!("{}", output.display());
+
+    Ok(())
+}
+
+fn insomnia_id(prefix: &str, name: &str) -> String {
+    format!("{prefix}_{}", name.to_lowercase().replace(' ', "_"))
+}
+
+fn implement_insomnia_workspace(path: PathBuf, model: &Model, options: &Options) -> io::Result<()>{
+    let resource = resource_path(model, options);
+    let body = serde_json::to_string_pretty(&model.example_body())?;
+    let workspace_id = insomnia_id("wrk", &model.name);
+
+    let mut resources = vec![
+        json!({ "_id": workspace_id, "_type": "workspace", "name": model.name.as_str() }),
+        json!({
+            "_id": insomnia_id("env", &model.name),
+            "_type": "environment",
+            "parentId": workspace_id,
+            "name": "Base Environment",
+            "data": { "BASE_URL": "http://localhost:3000" },
+        }),
+    ];
+
+    // An example key that parses, a UUID key is rejected as /1.
+    let key: String = model.primary_keys()
+        .iter()
+        .map(|field| match field.example_value() {
+            Value::String(value) => format!("/{value}"),
+            value => format!("/{value}"),
+        })
+        .collect();
+    for endpoint in crud_endpoints(model) {
+        let url = if endpoint.with_id {
+            format!("{{{{ _.BASE_URL }}}}{resource}{key}")
+        } else {
+            format!("{{{{ _.BASE_URL }}}}{resource}")
+        };
+        let mut request = json!({
+            "_id": insomnia_id("req", &endpoint.name),
+            "_type": "request",
+            "parentId": workspace_id,
+            "name": endpoint.name,
+            "method": endpoint.method,
+            "url": url,
+            "headers": [],
+            "body": {},
+        });
+        if endpoint.with_body {
+            request["headers"] = json!([{ "name": "Content-Type", "value": "application/json" }]);
+            request["body"] = json!({ "mimeType": "application/json", "text": body });
+        }
+        resources.push(request);
+    }
+
+    let export = json!({
+        "_type": "export",
+        "__export_format": 4,
+        "__export_source": "crudify",
+        "resources": resources,
+    });
+
+    write_file(&path.join(format!("{}{INSOMNIA_SUFFIX}", model.name.to_lowercase())), &serde_yaml::to_string(&export).map_err(io::Error::other)?)?;
+
+    Ok(())
+}
+
+fn merge_insomnia(path: &Path, args: &MergeArgs) -> io::Result<()>{
+    let (output, exports) = merge_inputs(path.join("docs").join("insomnia"), INSOMNIA_SUFFIX, &format!("workspace{INSOMNIA_SUFFIX}"), args)?;
+
+    let workspace_id = "wrk_crudify";
+    let environment_id = "env_crudify";
+    let mut environment = serde_json::Map::new();
+    let mut resources = vec![json!({ "_id": workspace_id, "_type": "workspace", "name": "Crudify workspace" })];
+    let mut groups = Vec::new();
+
+    for export_path in exports {
+        let export: Value = serde_yaml::from_str(&fs::read_to_string(&export_path)?).map_err(io::Error::other)?;
+        let items = export["resources"].as_array().cloned().unwrap_or_default();
+
+        // Each model workspace becomes a request group of the merged workspace.
+        for item in &items {
+            match item["_type"].as_str() {
+                Some("workspace") => {
+                    let group_id = item["_id"].as_str().unwrap_or_default().replacen("wrk_", "fld_", 1);
+                    groups.push(json!({ "_id": group_id, "_type": "request_group", "parentId": workspace_id, "name": item["name"] }));
+                }
+                Some("environment") => {
+                    for (key, value) in item["data"].as_object().into_iter().flatten() {
+                        environment.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                }
+                _ => {
+                    let mut item = item.clone();
+                    let parent = item["parentId"].as_str().unwrap_or_default().replacen("wrk_", "fld_", 1);
+                    item["parentId"] = json!(parent);
+                    groups.push(item);
+                }
+            }
+        }
+    }
+
+    resources.push(json!({
+        "_id": environment_id,
+        "_type": "environment",
+        "parentId": workspace_id,
+        "name": "Base Environment",
+        "data": environment,
+    }));
+    resources.extend(groups);
+
+    let export = json!({
+        "_type": "export",
+        "__export_format": 4,
+        "__export_source": "crudify",
+        "resources": resources,
+    });
+
+    write_file(&output, &serde_yaml::to_string(&export).map_err(io::Error::other)?)?;
+    println!("{}", output.display());
+
+    Ok(())
+}
+
+// The {name}-events topic the --queue-type consumer reads, as an AsyncAPI document. The service
+// publishes the aggregate events the subscribe operation describes and projects those of publish.
+fn implement_asyncapi(path: PathBuf, model: &Model, options: &Options) -> io::Result<()>{
+    let name = model.name.as_str();
+    let channel = format!("{}-events", name.to_lowercase());
+    let events = [("Added", "Attributes", "added"), ("Updated", "Changes", "updated"), ("Deleted", "Deleted", "deleted")];
+
+    let mut messages = serde_json::Map::new();
+    for (event, payload, verb) in events {
+        messages.insert(format!("{name}{event}"), json!({
+            "name": format!("{name}{event}"),
+            "title": format!("{name} {verb}"),
+            "contentType": "application/json",
+            "bindings": { "kafka": { "key": model.primary_key().to_json_schema() } },
+            "payload": {
+                "type": "object",
+                "required": ["type", "payload"],
+                "properties": {
+                    "type": { "type": "string", "const": format!("{name}{event}") },
+                    "payload": { "$ref": format!("#/components/schemas/{name}{payload}") },
+                },
+            },
+        }));
+    }
+    let one_of: Vec<Value> = events.iter().map(|(event, ..)| json!({ "$ref": format!("#/components/messages/{name}{event}") })).collect();
+
+    let document = json!({
+        "asyncapi": ASYNCAPI_VERSION,
+        "info": { "title": format!("{name} events"), "version": options.version().unwrap_or("1") },
+        "servers": { "kafka": { "url": "localhost:9092", "protocol": "kafka", "description": "Set by KAFKA_BROKERS" } },
+        "channels": {
+            channel.as_str(): {
+                "description": format!("{name} aggregate events, keyed by the {}", model.primary_key().name),
+                "subscribe": { "operationId": format!("publish{name}Event"), "message": { "oneOf": one_of } },
+                "publish": { "operationId": format!("project{name}Event"), "message": { "oneOf": one_of } },
+            },
+        },
+        "components": {
+            "messages": messages,
+            "schemas": {
+                format!("{name}Attributes"): model.to_json_schema(false),
+                format!("{name}Changes"): model.to_json_schema(true),
+                format!("{name}Deleted"): { "type": "object", "additionalProperties": false },
+            },
+        },
+    });
+
+    write_file(&path.join(format!("{}{ASYNCAPI_SUFFIX}", name.to_lowercase())), &serde_yaml::to_string(&document).map_err(io::Error::other)?)?;
+
+    Ok(())
+}
+
+fn merge_asyncapi(path: &Path, args: &MergeArgs) -> io::Result<()>{
+    let (output, documents) = merge_inputs(path.join("docs").join("asyncapi"), ASYNCAPI_SUFFIX, &format!("workspace{ASYNCAPI_SUFFIX}"), args)?;
+
+    let mut merged = json!({
+        "asyncapi": ASYNCAPI_VERSION,
+        "info": { "title": "Crudify events", "version": "1" },
+        "servers": {},
+        "channels": {},
+        "components": { "messages": {}, "schemas": {} },
+    });
+    // The channels, messages and schemas are named after their model, so the documents only meet on the servers.
+    for document_path in documents {
+        let document: Value = serde_yaml::from_str(&fs::read_to_string(&document_path)?).map_err(io::Error::other)?;
+        if let Some(version) = document["info"]["version"].as_str() {
+            merged["info"]["version"] = json!(version);
+        }
+        for section in ["/servers", "/channels", "/components/messages", "/components/schemas"] {
+            let Some(merged_section) = merged.pointer_mut(section).and_then(Value::as_object_mut) else { continue };
+            for (key, value) in document.pointer(section).and_then(Value::as_object).into_iter().flatten() {
+                merged_section.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    write_file(&output, &serde_yaml::to_string(&merged).map_err(io::Error::other)?)?;
+    println!("{}", output.display());
+
+    Ok(())
+}
+
+fn implement_prisma_schema(path: PathBuf, model: &Model) -> io::Result<()>{
+    let schema_path = path.join("schema.prisma");
+    let mut content = if file_exists(&schema_path) {
+        read_file(&schema_path)?
+    } else {
+        PRISMA_SCHEMA_HEADER.to_string()
+    };
+
+    let blocks = model.to_prisma_enums().into_iter().map(|(enum_name, block)| ("enum", enum_name.to_string(), block));
+    for (kind, name, block) in blocks.chain([("model", model.name.to_string(), model.to_prisma_block())]) {
+        let block_regex = Regex::new(&format!(r"(?ms)^{kind} {} \{{.*?^\}}", regex::escape(&name))).unwrap();
+        if block_regex.is_match(&content) {
+            content = block_regex.replace(&content, regex::NoExpand(&block)).into_owned();
+        } else {
+            content = format!("{}\n\n{block}\n", content.trim_end());
+        }
+    }
+
+    write_file(&schema_path, &content)?;
+
+    Ok(())
+}
+
+fn uploaded_file_path(model: &Model, field: &Field) -> String {
+    if model.file_fields().len() == 1 {
+        "req.file?.path".to_string()
+    } else if output_style().is_js() {
+        format!("req.files?.{}?.[0]?.path", field.name)
+    } else {
+        format!("(req.files as Record<string, Express.Multer.File[]> | undefined)?.{}?.[0]?.path", field.name)
+    }
+}
+
+fn upload_middleware(model: &Model) -> (String, String) {
+    let file_fields = model.file_fields();
+    let middleware = match file_fields.as_slice() {
+        [] => return (String::new(), String::new()),
+        [field] => format!("upload.single(\"{}\"), ", field.name),
+        fields => format!(
+            "upload.fields([{}]), ",
+            fields.iter().map(|field| format!("{{ name: \"{}\", maxCount: 1 }}", field.name)).collect::<Vec<_>>().join(", ")
+        ),
+    };
+    ("import { upload } from \"@presentation/middleware/upload\";".to_string(), middleware)
+}
+
+fn implement_upload_middleware(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default("UPLOAD_MIDDLEWARE_TEMPLATE", UPLOAD_MIDDLEWARE_TEMPLATE, templates)?;
+
+    write_project_file(&path.join("upload.ts"), &content)
+}
+
+fn implement_request_logger(path: PathBuf, logger: &str, templates: &TemplateCache) -> io::Result<()>{
+    let default = match logger {
+        "morgan" => builtin(REQUEST_LOGGER_MORGAN_TEMPLATE, REQUEST_LOGGER_MORGAN_JS_TEMPLATE),
+        _ => builtin(REQUEST_LOGGER_PINO_TEMPLATE, REQUEST_LOGGER_PINO_JS_TEMPLATE),
+    };
+    let content = template_or_default(&template_key("REQUEST_LOGGER_TEMPLATE"), default, templates)?;
+
+    write_project_file(&path.join("requestLogger.ts"), &content)
+}
+
+fn implement_tenant_context(path: PathBuf, tenant: &Field, templates: &TemplateCache) -> io::Result<()>{
+    let (parse, valid) = match tenant.js_type.as_str() {
+        "string" => ("header", r"/^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i.test(id)"),
+        _ => ("Number(header)", "Number.isInteger(id) && id > 0"),
+    };
+    let content = template_or_default(&template_key("TENANT_CONTEXT_TEMPLATE"), builtin(TENANT_CONTEXT_TEMPLATE, TENANT_CONTEXT_JS_TEMPLATE), templates)?
+        .replace("{TENANT_ID_TYPE}", &tenant.js_type)
+        .replace("{TENANT_ID_PARSE}", parse)
+        .replace("{TENANT_ID_VALID}", valid);
+    check_rendered(&template_key("TENANT_CONTEXT_TEMPLATE"), &content)?;
+
+    write_project_file(&path.join("tenantContext.ts"), &content)
+}
+
+// Logs the failures the controllers catch with the correlation id the request logger assigned.
+fn apply_logging(content: &str, model: &Model) -> String {
+    let log = |error: &str| format!("req.log?.error({{ correlationId: req.correlationId, err: {error} }}, \"{} request failed\");", model.name);
+    if content.contains("{LOG_ERROR}") {
+        return content.replace("{LOG_ERROR}", &log("error"));
+    }
+
+    // Templates without the placeholder get the log call at the top of their catch blocks.
+    let catch_block = Regex::new(r"catch \((\w+)(?::\s*\w+)?\) \{\n([ \t]*)").unwrap();
+    catch_block.replace_all(content, |captures: &regex::Captures| format!("{}{}\n{}", &captures[0], log(&captures[1]), &captures[2])).into_owned()
+}
+
+fn implement_routes(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_name = naming().file_name("routes", model);
+    let file_path = path.join(file_name);
+
+    let (repository_import, repository_instance) = repository_wiring(model, options);
+    let (upload_import, upload_middleware) = upload_middleware(model);
+    let mut content: String = copy_template(&template_key("ROUTES_TEMPLATE"), None, model, templates)?
+        .replace("{REPOSITORY_IMPORT}", &repository_import)
+        .replace("{REPOSITORY_INSTANCE}", &repository_instance)
+        .replace("{DYNAMIC_UPLOAD_IMPORT}", &upload_import)
+        .replace("{DYNAMIC_UPLOAD_MIDDLEWARE}", &upload_middleware)
+        .replace("{KEY_PARAMS}", &model.key_params());
+    // Templates that spell the param out as :id get the one named after the key.
+    if model.key_params() != "/:id" {
+        let param_regex = Regex::new(r#"(router\.\w+\(\s*["'`][^"'`]*)/:id\b"#).unwrap();
+        content = param_regex.replace_all(&content, format!("${{1}}{}", model.key_params())).into_owned();
+    }
+
+    if let Some(version) = options.version() {
+        let prefix = options.path_version_prefix();
+        // Templates without the placeholder get the prefix in front of every route path.
+        if content.contains("{VERSION_PREFIX}") {
+            content = content.replace("{VERSION_PREFIX}", prefix);
+        } else if !prefix.is_empty() {
+            let route_regex = Regex::new(r#"(router\.\w+\(\s*["'`])/"#).unwrap();
+            content = route_regex.replace_all(&content, format!("${{1}}{prefix}/")).into_owned();
+        }
+
+        // Requests asking for another version skip the router, so the one mounted for that version can answer.
+        let requested = match options.api_versioning.as_deref() {
+            Some("header") => format!("(req.get(\"Accept-Version\") ?? \"{version}\")"),
+            Some("query") => format!("String(req.query.version ?? \"{version}\")"),
+            _ => String::new(),
+        };
+        let middleware = match requested.is_empty() {
+            true => String::new(),
+            false => format!("router.use((req, _res, next) => ({requested} === \"{version}\" ? next() : next(\"router\")));"),
+        };
+        if content.contains("{VERSION_MIDDLEWARE}") {
+            content = content.replace("{VERSION_MIDDLEWARE}", &middleware);
+        } else if !middleware.is_empty() {
+            let router_regex = Regex::new(r"(?m)^(?:const|let|var)\s+router\s*=.*$").unwrap();
+            content = match router_regex.find(&content) {
+                Some(router) => format!("{}\n\n{middleware}{}", &content[..router.end()], &content[router.end()..]),
+                None => {
+                    warn(format!("routes template of {} creates no router, the {} version check is left out", model.name, options.api_versioning.as_deref().unwrap_or_default()));
+                    content
+                }
+            };
+        }
+
+        let version_constant = format!("export const VERSION = {version};\n\n");
+        content = match content.rfind("export default").or_else(|| content.rfind("module.exports")) {
+            Some(position) => format!("{}{version_constant}{}", &content[..position], &content[position..]),
+            None => format!("{content}\n{version_constant}"),
+        };
+    } else {
+        content = content.replace("{VERSION_PREFIX}", "").replace("{VERSION_MIDDLEWARE}", "");
+    }
+    // Each route of a scoped model resolves the tenant, routers of other models and the health check go without.
+    if model.tenant().is_some() {
+        let route = Regex::new(r#"(router\.(?:get|post|put|patch|delete)\(\s*["'`][^"'`]*["'`],\s*)"#).unwrap();
+        content = route.replace_all(&content, "${1}tenantContext, ").into_owned();
+        content = insert_import(&content, module_import("import tenantContext from \"@presentation/middleware/tenantContext\";").trim_end());
+    }
+    check_rendered(&template_key("ROUTES_TEMPLATE"), &content)?;
+
+    write_file(&file_path, &content)?;
+
+    Ok(())
+}
+fn implement_controllers(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let properties: Vec<&Field> = model.fields.iter().filter(|field| !field.is_virtual()).collect();
+    let file_name = naming().file_name("controllers", model);
+    let file_path = path.join(file_name);
+
+    // Minimal controllers call the service, the builtin unless SERVICE_CONTROLLERS_TEMPLATE is set.
+    let (controllers_template_key, default) = match options.is_minimal() {
+        true => (template_key("SERVICE_CONTROLLERS_TEMPLATE"), Some(builtin(SERVICE_CONTROLLERS_TEMPLATE, SERVICE_CONTROLLERS_JS_TEMPLATE))),
+        false => (template_key("CONTROLLERS_TEMPLATE"), None),
+    };
+    let mut content: String = apply_key_parser(&copy_template(&controllers_template_key, default, model, templates)?.replace("{PRIMARY_KEY}", &model.primary_key().name), model);
+
+    // Uploaded files come from the request file, everything else from the body.
+    let dynamic_properties_details = properties
+        .iter()
+        .filter(|property| model.is_supplied(property))
+        .map(|property| match property.db_type.as_str() {
+            "FILE" => format!("{}: {},", property.name, uploaded_file_path(model, property)),
+            _ if property.is_bigint() => format!("{0}: parseBigInt(req.body.{0}),", property.name),
+            _ => process_loops("{FOREACH field}{FIELD_NAME}: req.body.{FIELD_NAME},{/FOREACH}", std::slice::from_ref(*property)),
+        })
+        .filter(|detail| !detail.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\t\t\t\t");
+
+    let (repository_import, repository_instance) = repository_wiring(model, options);
+    content = apply_entities(&apply_di(&content, model, options), model, options)
+        .replace("{DYNAMIC_PROPERTIES_DETAILS}", &dynamic_properties_details)
+        .replace("{REPOSITORY_IMPORT}", &repository_import)
+        .replace("{REPOSITORY_INSTANCE}", &repository_instance);
+    if options.envelope {
+        content = apply_envelope(&content);
+    }
+    if model.has_bigint() {
+        content = apply_bigint(&content);
+    }
+    content = match options.logging {
+        Some(_) => apply_logging(&content, model),
+        None => content.replace("{LOG_ERROR}", ""),
+    };
+    content = apply_tenant(&content, model, "controllers");
+    check_rendered(&controllers_template_key, &content)?;
+
+    write_file(&file_path, &content)?;
+
+    Ok(())
+}
+
+// The user-level defaults with crudify.toml merged over them, the flags then go over both.
+fn load_config() -> Result<toml::Table, String> {
+    let mut config = global_config().map(|(_, config)| config).unwrap_or_default();
+    if let Some(project) = project_config()? {
+        merge_config(&mut config, project);
+    }
+    Ok(config)
+}
+
+fn project_config() -> Result<Option<toml::Table>, String> {
+    match fs::read_to_string(CONFIG_FILE) {
+        Ok(content) => content.parse::<toml::Table>().map(Some).map_err(|error| format!("{CONFIG_FILE}: {error}")),
+        Err(_) => Ok(None),
+    }
+}
+
+// ~/.config/crudify/config.toml, left out with a warning when it does not parse or holds a value generate
+// rejects, so a broken global file never stops a project that does not need it.
+fn global_config() -> Option<(PathBuf, toml::Table)> {
+    let config_path = user_config_dir()?.join(GLOBAL_CONFIG_FILE);
+    let content = fs::read_to_string(&config_path).ok()?;
+    let config = match content.parse::<toml::Table>() {
+        Ok(config) => config,
+        Err(error) => {
+            warn(format!("the global config {} is ignored: {}", config_path.display(), error.to_string().trim_end()));
+            return None;
+        }
+    };
+    if let Err(error) = Options::from_args(&GenerateArgs::default(), &config) {
+        warn(format!("the global config {} is ignored: {error}", config_path.display()));
+        return None;
+    }
+    Some((config_path, config))
+}
+
+// Tables are merged key by key, any other value of `overrides` replaces the one in `config`.
+fn merge_config(config: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (config.get_mut(&key), value) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(value)) => merge_config(table, value),
+            (_, value) => {
+                config.insert(key, value);
+            }
+        }
+    }
+}
+
+// $XDG_CONFIG_HOME/crudify or ~/.config/crudify, %APPDATA%\crudify on Windows.
+fn user_config_dir() -> Option<PathBuf> {
+    platform_dir("APPDATA", "XDG_CONFIG_HOME", ".config")
+}
+
+// $XDG_CACHE_HOME/crudify or ~/.cache/crudify, %LOCALAPPDATA%\crudify on Windows.
+fn user_cache_dir() -> Option<PathBuf> {
+    platform_dir("LOCALAPPDATA", "XDG_CACHE_HOME", ".cache")
+}
+
+fn platform_dir(windows_var: &str, xdg_var: &str, home_dir: &str) -> Option<PathBuf> {
+    let base = match cfg!(windows) {
+        true => env::var_os(windows_var).map(PathBuf::from),
+        false => env::var_os(xdg_var)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(home_dir))),
+    };
+    base.map(|base| base.join("crudify"))
+}
+
+// Backups go to the cache dir, in a directory per project, and next to the file when there is none.
+fn backup_path(file_path: &Path) -> PathBuf {
+    let Some(cache_dir) = user_cache_dir() else {
+        return PathBuf::from(format!("{}.bak", file_path.display()));
+    };
+    let project = env::current_dir().map(|dir| content_hash(&canonical_path(&dir).to_string_lossy())).unwrap_or_default();
+    let relative: PathBuf = file_path.components().filter(|component| matches!(component, Component::Normal(_))).collect();
+    cache_dir.join("backups").join(project).join(format!("{}.bak", relative.display()))
+}
+
+// Crudify config prints the merged configuration, --show-origin with the file each value comes from.
+fn show_config(args: &ConfigArgs) -> Result<(), String> {
+    let show_origin = args.show_origin;
+
+    let mut layers = Vec::new();
+    if let Some((config_path, config)) = global_config() {
+        layers.push((config_path.display().to_string(), config));
+    }
+    if let Some(config) = project_config()? {
+        layers.push((CONFIG_FILE.to_string(), config));
+    }
+    if !show_origin {
+        let mut config = toml::Table::new();
+        for (_, layer) in layers {
+            merge_config(&mut config, layer);
+        }
+        print!("{}", toml::to_string(&config).map_err(|error| error.to_string())?);
+        return Ok(());
+    }
+
+    let mut values: Vec<(String, String, &str)> = Vec::new();
+    for (origin, layer) in &layers {
+        let mut leaves = Vec::new();
+        config_leaves("", layer, &mut leaves);
+        for (key, value) in leaves {
+            match values.iter_mut().find(|(existing, _, _)| *existing == key) {
+                Some(entry) => *entry = (key, value, origin),
+                None => values.push((key, value, origin)),
+            }
+        }
+    }
+    for (key, value, origin) in values {
+        println!("{origin}\t{key} = {value}");
+    }
+    Ok(())
+}
+
+// The values of a table under their dotted keys, naming.model or placeholders.AUTHOR.
+fn config_leaves(prefix: &str, table: &toml::Table, leaves: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+        match value {
+            toml::Value::Table(table) => config_leaves(&key, table, leaves),
+            value => leaves.push((key, value.to_string())),
+        }
+    }
+}
+
+// Schema files are YAML (or JSON) keyed by model name:
+//
+// models:
+//   Post:
+//     plural: Posts
+//     fields:
+//       id: { db_type: INTEGER, js_type: number, attr: ["@PrimaryKey", "@AutoIncrement"] }
+//       title: { db_type: STRING, js_type: string, attr: ['@Index("idx_posts_title", { unique: true })'] }
+//       meta: { db_type: JSON, shape: { tags: "string[]", pinned: boolean, author: { name: string } } }
+//       email: { db_type: STRING, js_type: string, transform: lowercase }
+//       fullName: { db_type: VIRTUAL, js_type: string, depends_on: [firstName, lastName], get: "`${this.firstName} ${this.lastName}`" }
+//     route_prefix: /api/v1
+//     group: billing
+//     tableName: blog_posts
+//     underscored: true
+//     paranoid: true
+// The schema text of a --config path, where - reads stdin once for the whole run.
+fn read_schema(schema_path: &Path) -> Result<String, String> {
+    static STDIN: OnceLock<Result<String, String>> = OnceLock::new();
+    if schema_path != Path::new("-") {
+        return fs::read_to_string(schema_path).map_err(|error| format!("{}: {error}", schema_path.display()));
+    }
+    STDIN.get_or_init(|| {
+        let stdin = io::stdin();
+        if stdin.is_terminal() {
+            return Err("--config - reads the schema from stdin, pipe one in instead of typing it".to_string());
+        }
+        let mut bytes = Vec::new();
+        stdin.lock().take(MAX_STDIN_SCHEMA + 1).read_to_end(&mut bytes).map_err(|error| format!("stdin: {error}"))?;
+        if bytes.len() as u64 > MAX_STDIN_SCHEMA {
+            return Err(format!("stdin holds more than {} MiB, pass the schema as a file", MAX_STDIN_SCHEMA / 1024 / 1024));
+        }
+        let content = String::from_utf8(bytes).map_err(|error| format!("stdin is not valid UTF-8 at byte {}", error.utf8_error().valid_up_to()))?;
+        if content.trim().is_empty() {
+            return Err("stdin is empty, pipe a YAML or JSON schema into --config -".to_string());
+        }
+        Ok(content)
+    }).clone()
+}
+
+// A forced --schema-format, otherwise JSON when the document opens with { and YAML, which reads JSON too, for the rest.
+fn parse_schema(content: &str, format: Option<&str>, origin: &str) -> Result<Value, String> {
+    let json = format.map_or_else(|| content.trim_start().starts_with('{'), |format| format == "json");
+    match json {
+        true => serde_json::from_str(content).map_err(|error| format!("{origin}: invalid JSON: {error}")),
+        false => serde_yaml::from_str(content).map_err(|error| format!("{origin}: {error}")),
+    }
+}
+
+fn load_models(schema_path: &Path, format: Option<&str>, strict: bool, implicit_id: bool) -> Result<Vec<Model>, String> {
+    let content = read_schema(schema_path)?;
+    let origin = if schema_path == Path::new("-") { "stdin".to_string() } else { schema_path.display().to_string() };
+    let schema = parse_schema(&content, format, &origin)?;
+
+    let mut enums = defined_enums(&schema)?;
+    let models = schema_models(&schema, &mut enums, strict, implicit_id)?;
+    warn_unused_enums(&enums, &models);
+    Ok(models)
+}
+
+// The `enums` mapping of a schema.
+fn defined_enums(schema: &Value) -> Result<Vec<(String, Vec<String>)>, String> {
+    let mut enums: Vec<(String, Vec<String>)> = Vec::new();
+    for (enum_name, values) in schema["enums"].as_object().into_iter().flatten() {
+        enums.push((enum_name.clone(), parse_enum_values(enum_name, values)?));
+    }
+    Ok(enums)
+}
+
+// The models of a schema, the enums its fields define themselves are added to `enums`.
+fn schema_models(schema: &Value, enums: &mut Vec<(String, Vec<String>)>, strict: bool, implicit_id: bool) -> Result<Vec<Model>, String> {
+    let models = schema["models"].as_object().ok_or("Schema needs a `models` mapping")?;
+
+    let mut result: Vec<Model> = Vec::new();
+    for (input, definition) in models {
+        let model_name = ModelName::parse(input)?;
+        if *model_name != **input {
+            notice(&format!("model {input} is generated as {model_name}"));
+        }
+        if result.iter().any(|model| model.name == model_name) {
+            return Err(format!("Model {input} becomes {model_name}, the name of another model"));
+        }
+        let plural = match definition["plural"].as_str() {
+            Some(input) => {
+                let plural = ModelName::parse(input).map_err(|error| format!("{model_name}: plural: {error}"))?;
+                if *plural != *input {
+                    notice(&format!("plural {input} of {model_name} is generated as {plural}"));
+                }
+                plural
+            }
+            None => ModelName::parse(&format!("{model_name}s"))?,
+        };
+        let name = model_name.to_string();
+        let fields = definition["fields"].as_object().ok_or(format!("Model {name} needs a `fields` mapping"))?;
+
+        let mut properties = Vec::new();
+        for (field_name, field) in fields {
+            let declared_type = field["db_type"].as_str().ok_or(format!("{name}.{field_name} needs a db_type"))?;
+            let db_type = attr_name(declared_type);
+            let type_args = attr_args(declared_type);
+            if type_args.is_some() && !SPATIAL_TYPES.contains(&db_type) {
+                return Err(format!("{name}.{field_name}: only GEOMETRY and GEOGRAPHY take type arguments, not {db_type}"));
+            }
+            let shaped = !field["shape"].is_null();
+            // bigint_as picks how a BIGINT travels, as a bigint or as a string of digits.
+            let bigint_as = field["bigint_as"].as_str();
+            if let Some(bigint_as) = bigint_as {
+                if db_type != "BIGINT" {
+                    return Err(format!("{name}.{field_name}: bigint_as applies to BIGINT fields, not {db_type}"));
+                }
+                if !BIGINT_AS.contains(&bigint_as) {
+                    return Err(format!("{name}.{field_name}: bigint_as must be bigint or string, not {bigint_as}"));
+                }
+                if let Some(js_type) = field["js_type"].as_str().filter(|js_type| *js_type != bigint_as) {
+                    return Err(format!("{name}.{field_name} has js_type {js_type} and bigint_as {bigint_as}, keep one of them"));
+                }
+            }
+            let js_type = match field["js_type"].as_str().or(bigint_as) {
+                Some(js_type) => js_type,
+                None if shaped || SPATIAL_TYPES.contains(&db_type) => "object",
+                None if db_type == "ENUM" => "string",
+                None => return Err(format!("{name}.{field_name} needs a js_type")),
+            };
+            let attr: Vec<&str> = field["attr"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+
+            let mut property = Field::validate(attr, field_name, db_type, js_type, strict).map_err(|error| format!("{name}: {error}"))?;
+            if let Some(args) = type_args {
+                property.spatial = Some(Spatial::parse(args).map_err(|error| format!("{name}.{field_name}: {error}"))?);
+            }
+            if shaped {
+                if db_type != "JSON" {
+                    return Err(format!("{name}.{field_name} has a shape, which only JSON fields take"));
+                }
+                let shape = Shape::parse(&field["shape"]).map_err(|error| format!("{name}.{field_name}: {error}"))?;
+                if !matches!(shape, Shape::Object(_)) {
+                    return Err(format!("{name}.{field_name}: shape must be a mapping of properties"));
+                }
+                property.shape = Some((format!("{name}{}", capitalize(field_name)), shape));
+            }
+            match (db_type, field["enum"].as_str()) {
+                ("ENUM", Some(enum_name)) => {
+                    // A field may define the enum itself, but every definition has to list the same values.
+                    let defined = enums.iter().find(|(other, _)| other == enum_name).map(|(_, values)| values.clone());
+                    let values = match (defined, &field["values"]) {
+                        (Some(values), Value::Null) => values,
+                        (None, Value::Null) => return Err(format!("{name}.{field_name} references the undefined enum {enum_name}")),
+                        (defined, values) => {
+                            let values = parse_enum_values(enum_name, values).map_err(|error| format!("{name}.{field_name}: {error}"))?;
+                            match defined {
+                                Some(defined) if defined != values => {
+                                    return Err(format!("{name}.{field_name} redefines the enum {enum_name} with different values"));
+                                }
+                                Some(_) => {}
+                                None => enums.push((enum_name.to_string(), values.clone())),
+                            }
+                            values
+                        }
+                    };
+                    property.enumeration = Some((enum_name.to_string(), values));
+                }
+                ("ENUM", None) => return Err(format!("{name}.{field_name} has type ENUM and needs an enum")),
+                (_, Some(_)) => return Err(format!("{name}.{field_name} has an enum, which only ENUM fields take")),
+                _ => {}
+            }
+            if let Some(transform) = field["transform"].as_str() {
+                if !TRANSFORMS.contains(&transform) {
+                    return Err(format!("{name}.{field_name}: unknown transform {transform}, expected lowercase, trim, encrypt or custom"));
+                }
+                if db_type == "VIRTUAL" {
+                    return Err(format!("{name}.{field_name} is VIRTUAL and has no stored value to transform"));
+                }
+                if transform != "custom" && !["STRING", "TEXT", "CITEXT"].contains(&db_type) {
+                    return Err(format!("{name}.{field_name}: the {transform} transform needs a STRING, TEXT or CITEXT field, not {db_type}"));
+                }
+                if property.has_attr("@UniqueInsensitive") && transform != "lowercase" {
+                    return Err(format!("{name}.{field_name} has @UniqueInsensitive, which lowercases the value and leaves no room for the {transform} transform"));
+                }
+                // Encrypted values stay out of events and entities unless [entities] asks for hidden fields.
+                if transform == "encrypt" && !property.has_attr("@Hidden") {
+                    property.attr.push("@Hidden".to_string());
+                }
+                property.transform = Some(transform.to_string());
+            }
+            // The setter lowercases what the LOWER(column) index compares.
+            if property.has_attr("@UniqueInsensitive") {
+                property.transform = Some("lowercase".to_string());
+            }
+            if db_type == "VIRTUAL" {
+                let returns = field["returns"].as_str();
+                if returns.is_some_and(|returns| !DB_TYPES.contains(&returns) || ["VIRTUAL", "FILE"].contains(&returns)) {
+                    return Err(format!("{name}.{field_name}: returns must be a stored database type"));
+                }
+                property.computed = Some(Computed {
+                    depends_on: field["depends_on"].as_array().into_iter().flatten().filter_map(Value::as_str).map(str::to_string).collect(),
+                    getter: field["get"].as_str().map(str::to_string),
+                    returns: returns.map(str::to_string),
+                });
+            }
+            if !field["example"].is_null() {
+                property.example = Some(field["example"].clone());
+            }
+            properties.push(property);
+        }
+
+        if implicit_id && !properties.iter().any(|property| property.has_attr("@PrimaryKey")) {
+            match properties.iter_mut().find(|property| property.name == "id") {
+                Some(id) => {
+                    id.attr.insert(0, "@PrimaryKey".to_string());
+                    if ["INTEGER", "BIGINT"].contains(&id.db_type.as_str()) && !id.has_attr("@AutoIncrement") && !id.has_attr("@Default") {
+                        id.attr.insert(1, "@AutoIncrement".to_string());
+                    }
+                }
+                None => properties.insert(0, Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number")),
+            }
+            notice(&format!("{name} declares no @PrimaryKey and is keyed by its id, pass --no-id to declare the key yourself"));
+        }
+        let mut model = Model::validate(model_name, plural, properties).map_err(|error| format!("{name}: {error}"))?;
+        model.route_prefix = definition["route_prefix"].as_str().map(normalize_prefix);
+        model.table = TableOptions {
+            table_name: definition["tableName"].as_str().map(str::to_string),
+            schema: definition["schema"].as_str().map(str::to_string),
+            underscored: definition["underscored"].as_bool().unwrap_or(false),
+            paranoid: definition["paranoid"].as_bool().unwrap_or(false),
+            timestamps: definition["timestamps"].as_bool(),
+        };
+        model.check_table_options().map_err(|error| format!("{name}: {error}"))?;
+        model.tenant_scoped = definition["tenant"].as_bool().unwrap_or(true);
+        if let Some(group) = definition["group"].as_str() {
+            if !identifier_regex().is_match(&group.replace('-', "_")) {
+                return Err(format!("{name}: group {group} must be a plain directory name"));
+            }
+            model.group = Some(group.to_string());
+        }
+        result.push(model);
+    }
+
+    Ok(result)
+}
+
+fn warn_unused_enums(enums: &[(String, Vec<String>)], models: &[Model]) {
+    for (enum_name, _) in enums {
+        if !models.iter().any(|model| model.enum_names().contains(&enum_name.as_str())) {
+            warn(format!("enum {enum_name} is not used by any field"));
+        }
+    }
+}
+
+// A schema file of --schema-dir with its models, or why it is left out.
+#[derive(Debug)]
+struct SchemaFile {
+    path: PathBuf,
+    models: Vec<Model>,
+    error: Option<String>
+}
+
+// The .yaml, .yml and .json files of the directory in path order, those of the subdirectories too when recursive.
+fn schema_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|error| format!("{}: {error}", dir.display()))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry_path = entry.map_err(|error| format!("{}: {error}", dir.display()))?.path();
+        let hidden = entry_path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if entry_path.is_dir() {
+            if recursive && !hidden {
+                files.extend(schema_files(&entry_path, recursive)?);
+            }
+        } else if entry_path.extension().is_some_and(|extension| ["yaml", "yml", "json"].iter().any(|known| extension == *known)) {
+            files.push(entry_path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// Loads every schema file of --schema-dir as one schema: the enums are shared, a model name is taken by the first
+// file defining it and relations resolve across the files. A file that fails is returned with its error, so is a
+// file relating to a model only a failing file defines.
+fn load_schema_dir(dir: &Path, recursive: bool, format: Option<&str>, strict: bool, implicit_id: bool) -> Result<Vec<SchemaFile>, String> {
+    let paths = schema_files(dir, recursive)?;
+    if paths.is_empty() {
+        return Err(format!("{} holds no .yaml, .yml or .json schema files", dir.display()));
+    }
+
+    let mut schemas: Vec<(PathBuf, Result<Value, String>)> = paths
+        .into_iter()
+        .map(|schema_path| {
+            let json = schema_path.extension().is_some_and(|extension| extension == "json");
+            let schema = read_schema(&schema_path).and_then(|content| parse_schema(&content, format.or(Some(if json { "json" } else { "yaml" })), &schema_path.display().to_string()));
+            (schema_path, schema)
+        })
+        .collect();
+
+    // The enums mappings come first, so a field can use the enum of any file.
+    let mut enums: Vec<(String, Vec<String>)> = Vec::new();
+    for (_, schema) in schemas.iter_mut() {
+        let defined = schema.as_ref().map_err(String::clone).and_then(defined_enums).and_then(|defined| {
+            match defined.iter().find(|(enum_name, values)| enums.iter().any(|(other, other_values)| other == enum_name && other_values != values)) {
+                Some((enum_name, _)) => Err(format!("enum {enum_name} is defined with different values in another schema file")),
+                None => Ok(defined),
+            }
+        });
+        match defined {
+            Ok(defined) => enums.extend(defined.into_iter().filter(|(enum_name, _)| !enums.iter().any(|(other, _)| other == enum_name)).collect::<Vec<_>>()),
+            Err(error) => *schema = Err(error),
+        }
+    }
+
+    let mut loaded: Vec<SchemaFile> = Vec::new();
+    for (schema_path, schema) in schemas {
+        let mut file_enums = enums.clone();
+        let models = schema.and_then(|schema| schema_models(&schema, &mut file_enums, strict, implicit_id)).and_then(|models| {
+            for model in &models {
+                if let Some(other) = loaded.iter().find(|other| other.models.iter().any(|defined| defined.name == model.name)) {
+                    return Err(format!("Model {} is already defined in {}", model.name, other.path.display()));
+                }
+            }
+            Ok(models)
+        });
+        match models {
+            Ok(models) => {
+                enums = file_enums;
+                loaded.push(SchemaFile { path: schema_path, models, error: None });
+            }
+            Err(error) => loaded.push(SchemaFile { path: schema_path, models: Vec::new(), error: Some(error) }),
+        }
+    }
+
+    // Leaving a file out can leave a relation of another one unresolved, until none is.
+    loop {
+        let defined: Vec<String> = loaded.iter().flat_map(|file| &file.models).map(|model| model.name.to_string()).collect();
+        let unresolved = loaded.iter().enumerate().find_map(|(index, file)| {
+            file.models.iter().find_map(|model| {
+                let target = model.relation_targets().into_iter().find(|target| !defined.contains(target))?;
+                Some((index, format!("{} relates to {target}, which no valid schema file defines", model.name)))
+            })
+        });
+        let Some((index, error)) = unresolved else {
+            break;
+        };
+        loaded[index].models.clear();
+        loaded[index].error = Some(error);
+    }
+
+    let models: Vec<Model> = loaded.iter().flat_map(|file| file.models.clone()).collect();
+    warn_unused_enums(&enums, &models);
+    Ok(loaded)
+}
+
+fn apply_composite_key(models: &mut [Model], key: &[String]) -> Result<(), String> {
+    let mut keyed = 0;
+    for model in models.iter_mut().filter(|model| key.iter().all(|name| model.fields.iter().any(|field| &field.name == name))) {
+        model.set_composite_key(key)?;
+        keyed += 1;
+    }
+    if keyed == 0 {
+        return Err(format!("No model has all of the --composite-pk fields {}", key.join(", ")));
+    }
+    Ok(())
+}
+
+// Adds the indexed tenant column to every scoped model, a schema field of that name is taken as it is
+// when its type matches.
+fn apply_tenant_field(models: &mut [Model], name: &str, db_type: &str) -> Result<(), String> {
+    let js_type = if db_type == "UUID" { "string" } else { "number" };
+    for model in models.iter_mut().filter(|model| model.tenant_scoped) {
+        match model.fields.iter().find(|field| field.name == name) {
+            Some(field) if field.js_type != js_type => {
+                return Err(format!("{}.{name} is a {}, --tenant-type expects {db_type}", model.name, field.db_type));
+            }
+            Some(_) => {}
+            None => model.fields.push(Field::new(vec!["@Index"], name, db_type, js_type)),
+        }
+        model.tenant_field = Some(name.to_string());
+    }
+    Ok(())
+}
+
+// --emit-schema writes the schema the run read as JSON, to stdout for -, so a piped schema can be kept.
+fn emit_schema(schema_path: &Path, format: Option<&str>, target: &str) -> Result<(), String> {
+    let schema = parse_schema(&read_schema(schema_path)?, format, &schema_path.display().to_string())?;
+    let content = serde_json::to_string_pretty(&schema).map_err(|error| error.to_string())?;
+    match target {
+        "-" => println!("{content}"),
+        _ => fs::write(target, format!("{content}\n")).map_err(|error| format!("{target}: {error}"))?,
+    }
+    Ok(())
+}
+
+fn parse_enum_values(enum_name: &str, values: &Value) -> Result<Vec<String>, String> {
+    if !identifier_regex().is_match(enum_name) || RESERVED_WORDS.contains(&enum_name) {
+        return Err(format!("Enum name {enum_name} is not a valid identifier"));
+    }
+    let values = values.as_array().filter(|values| !values.is_empty()).ok_or(format!("Enum {enum_name} needs a list of values"))?;
+
+    let mut result: Vec<String> = Vec::new();
+    for value in values {
+        let value = value.as_str().ok_or(format!("Enum {enum_name} has a value that is not a string: {value}"))?;
+        if result.iter().any(|other| other == value) {
+            return Err(format!("Enum {enum_name} lists {value} twice"));
+        }
+        result.push(value.to_string());
+    }
+    Ok(result)
+}
+
+fn update_sequelize(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let sequelize_path = path.join("sequelize.ts");
+    if !file_exists(&sequelize_path) {
+        let content = template_or_default(&template_key("SEQUELIZE_TEMPLATE"), builtin(SEQUELIZE_TEMPLATE, SEQUELIZE_JS_TEMPLATE), templates)?;
+        write_file(&sequelize_path, &content)?;
+    }
+
+    update_sequelize_file(&sequelize_path, model, options)
+}
+
+// Registers the model in an existing sequelize.ts, a file without a models array is left as it is.
+fn update_sequelize_file(sequelize_path: &Path, model: &Model, options: &Options) -> io::Result<()>{
+    let file_content = read_file(sequelize_path)?;
+    if !array_regex("models").is_match(&file_content) {
+        warn(format!("{} has no models: [...] array, {} is not registered in it", sequelize_path.display(), model.name));
+        return Ok(());
+    }
+    let configured = file_content.replace("{DIALECT}", &options.dialect);
+    if configured != file_content {
+        write_file(sequelize_path, &configured)?;
+    }
+
+    register_sequelize_model(sequelize_path, &model.name, &format!("@infrastructure/models/{}", naming().module("model", model)))?;
+    if options.event_sourcing {
+        let record = format!("{}EventRecord", model.name);
+        register_sequelize_model(sequelize_path, &record, &format!("@infrastructure/event_store/{}EventStore", model.name))?;
+    }
+
+    Ok(())
+}
+
+// Imports the model class from `module` and adds it to the models array, once.
+fn register_sequelize_model(sequelize_path: &Path, class: &str, module: &str) -> io::Result<()> {
+    let file_content = read_file(sequelize_path)?;
+
+    // JavaScript models are registered through their init function.
+    let entry = if output_style().is_js() { format!("init{class}") } else { class.to_string() };
+    let import = module_import(&format!("import {{ {entry} }} from \"{module}\";\n"));
+    // Drops the import left behind by a previous naming convention.
+    let stale = Regex::new(&format!(r#"(?:import \{{ {0} \}} from "[^"]*"|const \{{ {0} \}} = require\("[^"]*"\));\n"#, regex::escape(&entry))).unwrap();
+    let mut imported = stale.replace_all(&file_content, |captures: &regex::Captures| {
+        if captures[0] == import { import.clone() } else { String::new() }
+    }).into_owned();
+    if !imported.contains(&import) {
+        imported = import + &imported;
+    }
+    if imported != file_content {
+        write_file(sequelize_path, &imported)?;
+    }
+
+    if !update_array_in_file(sequelize_path, "models", &entry)? {
+        warn(format!("{} has no models: [...] array, {class} is imported but not registered in it", sequelize_path.display()));
+    }
+    Ok(())
+}
+
+// An `array_key: [...]` or `array_key = [...]` array, which may span several lines. Compiled once per key.
+fn array_regex(array_key: &str) -> Regex {
+    static ARRAYS: OnceLock<Mutex<HashMap<String, Regex>>> = OnceLock::new();
+    let mut arrays = ARRAYS.get_or_init(Mutex::default).lock().unwrap();
+    arrays
+        .entry(array_key.to_string())
+        .or_insert_with(|| Regex::new(&format!(r"(?s)({}(?::|\s*=)\s*)\[(\s*)(.*?)(\s*)]", regex::escape(array_key))).unwrap())
+        .clone()
+}
+
+// Adds `value` to the array_key array of the file, once. A multi-line array gets the value on a line
+// of its own with the indentation of the first entry, keeping a trailing comma.
+// False when the file has no such array.
+fn update_array_in_file(path: &Path, array_key: &str, value: &str) -> io::Result<bool> {
+    let mut file_content = read_file(path)?;
+    let Some(captures) = array_regex(array_key).captures(&file_content) else {
+        return Ok(false);
+    };
+
+    let (assignment, leading, array_content, trailing) = (&captures[1], &captures[2], &captures[3], &captures[4]);
+    let mut entries: Vec<&str> = array_content.split(',').map(str::trim).filter(|entry| !entry.is_empty()).collect();
+    if entries.contains(&value) {
+        return Ok(true);
+    }
+    entries.push(value);
+    let separator = match leading.rfind('\n') {
+        Some(newline) => format!(",\n{}", &leading[newline + 1..]),
+        None => ", ".to_string(),
+    };
+    let trailing_comma = if array_content.ends_with(',') { "," } else { "" };
+    let updated_array = format!("{assignment}[{leading}{}{trailing_comma}{trailing}]", entries.join(&separator));
+    let range = captures.get(0).unwrap().range();
+    file_content.replace_range(range, &updated_array);
+
+    write_file(path, &file_content)?;
+    Ok(true)
+}
+
+fn implement_database_config(path: PathBuf, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default(&template_key("DATABASE_CONFIG_TEMPLATE"), builtin(DATABASE_CONFIG_TEMPLATE, DATABASE_CONFIG_JS_TEMPLATE), templates)?
+        .replace("{DIALECT}", &options.dialect);
+
+    write_project_file(&path.join("database.ts"), &content)
+}
+
+// Appends the variables missing from .env.example, leaving existing entries alone.
+fn update_env_example(path: &Path, options: &Options) -> io::Result<()>{
+    let env_path = path.join(".env.example");
+    let mut file_content = if file_exists(&env_path) { read_file(&env_path)? } else { String::new() };
+
+    let defined: Vec<String> = file_content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, _)| key.trim().to_string())
+        .collect();
+    let missing: Vec<&str> = options.env_variables()
+        .into_iter()
+        .filter(|variable| !defined.iter().any(|key| key == variable))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !file_content.is_empty() && !file_content.ends_with('\n') {
+        file_content.push('\n');
+    }
+    for variable in missing {
+        file_content.push_str(&format!("{variable}=\n"));
+    }
+
+    write_file(&env_path, &file_content)?;
+
+    Ok(())
+}
+
+fn database_port(dialect: &str) -> i64 {
+    match dialect {
+        "mysql" => 3306,
+        "mssql" => 1433,
+        _ => 5432,
+    }
+}
+
+fn database_service(options: &Options, settings: &DockerSettings) -> Option<Value> {
+    let (image, data_dir, environment, healthcheck) = match options.dialect.as_str() {
+        "postgres" => (
+            "postgres:16-alpine",
+            "/var/lib/postgresql/data",
+            json!({ "POSTGRES_USER": settings.user, "POSTGRES_PASSWORD": settings.password, "POSTGRES_DB": settings.database }),
+            json!(["CMD-SHELL", format!("pg_isready -U {} -d {}", settings.user, settings.database)]),
+        ),
+        "mysql" => (
+            "mysql:8",
+            "/var/lib/mysql",
+            json!({
+                "MYSQL_USER": settings.user,
+                "MYSQL_PASSWORD": settings.password,
+                "MYSQL_ROOT_PASSWORD": settings.password,
+                "MYSQL_DATABASE": settings.database,
+            }),
+            json!(["CMD", "mysqladmin", "ping", "-h", "localhost"]),
+        ),
+        "mssql" => (
+            "mcr.microsoft.com/mssql/server:2022-latest",
+            "/var/opt/mssql",
+            json!({ "ACCEPT_EULA": "Y", "MSSQL_SA_PASSWORD": settings.password }),
+            json!([
+                "CMD-SHELL",
+                format!("/opt/mssql-tools18/bin/sqlcmd -C -S localhost -U sa -P \"{}\" -Q \"SELECT 1\"", settings.password)
+            ]),
+        ),
+        _ => return None,
+    };
+
+    Some(json!({
+        "image": image,
+        "restart": "unless-stopped",
+        "environment": environment,
+        "ports": [format!("{}:{}", settings.port, database_port(&options.dialect))],
+        "volumes": [format!("db-data:{data_dir}")],
+        "healthcheck": { "test": healthcheck, "interval": "10s", "timeout": "5s", "retries": 5 },
+    }))
+}
+
+// Rewrites only the services crudify owns, so services added by hand survive a rerun.
+fn update_docker_compose(path: &Path, options: &Options, settings: &DockerSettings) -> io::Result<()>{
+    let compose_path = path.join("docker-compose.yml");
+    let mut compose: Value = if file_exists(&compose_path) {
+        serde_yaml::from_str(&read_file(&compose_path)?).map_err(io::Error::other)?
+    } else {
+        json!({ "services": {}, "volumes": {} })
+    };
+    if !compose["services"].is_object() {
+        compose["services"] = json!({});
+    }
+    if !compose["volumes"].is_object() {
+        compose["volumes"] = json!({});
+    }
+
+    let mut environment = json!({
+        "NODE_ENV": "production",
+        "DB_HOST": "db",
+        "DB_PORT": database_port(&options.dialect).to_string(),
+        "DB_NAME": settings.database,
+        "DB_USER": settings.user,
+        "DB_PASSWORD": settings.password,
+    });
+    if options.cache.is_some() {
+        environment["REDIS_URL"] = json!("redis://redis:6379");
+    }
+
+    let services = compose["services"].as_object_mut().unwrap();
+    let mut depends_on = serde_json::Map::new();
+    match database_service(options, settings) {
+        Some(database) => {
+            services.insert("db".to_string(), database);
+            depends_on.insert("db".to_string(), json!({ "condition": "service_healthy" }));
+        }
+        None => {
+            services.remove("db");
+        }
+    }
+    if options.cache.is_some() {
+        services.insert("redis".to_string(), json!({ "image": "redis:7-alpine", "restart": "unless-stopped", "ports": ["6379:6379"] }));
+        depends_on.insert("redis".to_string(), json!({ "condition": "service_started" }));
+    }
+    services.insert("app".to_string(), json!({
+        "build": ".",
+        "ports": ["3000:3000"],
+        "environment": environment,
+        "depends_on": depends_on,
+    }));
+
+    if services.contains_key("db") {
+        compose["volumes"].as_object_mut().unwrap().entry("db-data").or_insert(json!({}));
+    }
+
+    write_file(&compose_path, &serde_yaml::to_string(&compose).map_err(io::Error::other)?)?;
+
+    Ok(())
+}
+
+fn implement_docker(path: &Path, options: &Options, settings: &DockerSettings, templates: &TemplateCache) -> io::Result<()>{
+    let dockerfile = template_or_default("DOCKERFILE_TEMPLATE", DOCKERFILE_TEMPLATE, templates)?;
+    write_project_file(&path.join("Dockerfile"), &dockerfile)?;
+
+    update_docker_compose(path, options, settings)
+}
+
+// A tsconfig.json in each layer root, compiling the project from its root with the aliases the imports use.
+fn implement_tsconfig(path: &Path, models: &[Model], options: &Options, templates: &TemplateCache) -> io::Result<()> {
+    let layers = ["core", "infrastructure", "presentation"];
+    let mut paths: Vec<(String, PathBuf)> = layers
+        .iter()
+        .map(|layer| {
+            let alias = format!("@{layer}/");
+            let alias = options.layout.import_aliases(path).into_iter().find(|(from, _)| *from == alias).map_or(alias, |(_, to)| to);
+            (alias, options.layout.layer_dir(path, layer))
+        })
+        .collect();
+    let mut groups: Vec<&str> = Vec::new();
+    for group in models.iter().filter_map(|model| model.group.as_deref()) {
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    for group in groups {
+        paths.extend(layers.iter().map(|layer| (format!("@{group}/{layer}/"), path.join(options.layout.group_dir(group, layer)))));
+    }
+    let entries = paths
+        .iter()
+        .map(|(alias, directory)| format!("      \"{alias}*\": [\"{}/*\"]", relative_path(path, directory).trim_start_matches("./")))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    // Without --decorator-style the settings follow the output: sequelize-typescript and the DI containers read
+    // the metadata only the experimental decorators emit.
+    let experimental = match options.decorator_style.as_deref() {
+        Some(style) => style == "experimental",
+        None => options.orm == "sequelize" || options.di != "none",
+    };
+
+    let template = template_or_default("TSCONFIG_TEMPLATE", TSCONFIG_TEMPLATE, templates)?;
+    for layer in layers {
+        let layer_dir = options.layout.layer_dir(path, layer);
+        // A layer in its own package builds into the package, the others into dist/{layer} of the project.
+        let out_dir = match options.layout.packages.iter().any(|(name, _)| name == layer) {
+            true => layer_dir.join("dist"),
+            false => path.join("dist").join(layer),
+        };
+        let content = template
+            .replace("{DYNAMIC_TSCONFIG_PATHS}", &entries)
+            .replace("{BASE_URL}", &relative_path(&layer_dir, path))
+            .replace("{OUT_DIR}", &relative_path(&layer_dir, &out_dir))
+            .replace("{EXPERIMENTAL_DECORATORS}", &experimental.to_string());
+        check_rendered("TSCONFIG_TEMPLATE", &content)?;
+        write_project_file(&layer_dir.join("tsconfig.json"), &content)?;
+    }
+    Ok(())
+}
+
+// The npm packages of a generated package.json by name and version range.
+#[derive(Debug, Default)]
+struct PackageDependencies {
+    dependencies: Vec<(&'static str, &'static str)>,
+    dev_dependencies: Vec<(&'static str, &'static str)>
+}
+
+// The npm packages the generated sources import with these options, and those they are built and tested with.
+fn package_dependencies(models: &[Model], options: &Options) -> PackageDependencies {
+    let ts = !output_style().is_js();
+    let mut dependencies = vec![("express", "^4.19.2")];
+    let mut dev_dependencies = vec![("jest", "^29.7.0")];
+    if ts {
+        dev_dependencies.extend([("typescript", "^5.5.3"), ("ts-jest", "^29.2.0"), ("@types/jest", "^29.5.12"), ("@types/node", "^20.14.10"), ("@types/express", "^4.17.21")]);
+    }
+
+    if options.orm == "prisma" {
+        dependencies.push(("@prisma/client", "^5.16.1"));
+        dev_dependencies.push(("prisma", "^5.16.1"));
+    } else {
+        dependencies.push(("sequelize", "^6.37.3"));
+        if ts {
+            dependencies.extend([("sequelize-typescript", "^2.1.6"), ("reflect-metadata", "^0.2.2")]);
+        }
+        dependencies.extend(match options.dialect.as_str() {
+            "mysql" => vec![("mysql2", "^3.10.2")],
+            "sqlite" => vec![("sqlite3", "^5.1.7")],
+            "mssql" => vec![("tedious", "^18.2.0")],
+            _ => vec![("pg", "^8.12.0"), ("pg-hstore", "^2.3.4")],
+        });
+    }
+    match options.di.as_str() {
+        "tsyringe" => dependencies.extend([("tsyringe", "^4.8.0"), ("reflect-metadata", "^0.2.2")]),
+        "inversify" => dependencies.extend([("inversify", "^6.0.2"), ("reflect-metadata", "^0.2.2")]),
+        _ => {}
+    }
+    if options.cache.is_some() {
+        dependencies.push(("ioredis", "^5.4.1"));
+    }
+    if options.validation.is_some() {
+        dependencies.push(("zod", "^3.23.8"));
+    }
+    match options.logging.as_deref() {
+        Some("pino") => dependencies.push(("pino", "^9.2.0")),
+        Some("morgan") => {
+            dependencies.push(("morgan", "^1.10.0"));
+            dev_dependencies.push(("@types/morgan", "^1.9.9"));
+        }
+        _ => {}
+    }
+    if options.graphql {
+        dependencies.extend([("graphql", "^16.9.0"), ("graphql-tag", "^2.12.6")]);
+    }
+    if options.queue_type.is_some() {
+        dependencies.push(("kafkajs", "^2.2.4"));
+    }
+    if models.iter().any(|model| !model.file_fields().is_empty()) {
+        dependencies.push(("multer", "^1.4.5-lts.1"));
+        dev_dependencies.push(("@types/multer", "^1.4.11"));
+    }
+    if !ts {
+        dev_dependencies.retain(|(name, _)| !name.starts_with("@types/"));
+    }
+
+    for packages in [&mut dependencies, &mut dev_dependencies] {
+        packages.sort();
+        packages.dedup();
+    }
+    PackageDependencies { dependencies, dev_dependencies }
+}
+
+// A package.json in each layer root. A package.json already there, such as the one of a --monorepo-packages
+// package, only gets the keys and packages it is missing, the values it has are kept.
+fn implement_package_json(path: &Path, models: &[Model], options: &Options, templates: &TemplateCache) -> io::Result<()> {
+    let PackageDependencies { dependencies, dev_dependencies } = package_dependencies(models, options);
+    let entries = |packages: &[(&str, &str)]| packages.iter().map(|(name, version)| format!("    \"{name}\": \"{version}\"")).collect::<Vec<_>>().join(",\n");
+    let project: String = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+        .chars()
+        .map(|letter| if letter.is_ascii_alphanumeric() || letter == '-' { letter } else { '-' })
+        .collect();
+    let (build, test) = match output_style().is_js() {
+        true => ("echo JavaScript sources are not compiled", "jest"),
+        false => ("tsc -p .", "jest --preset ts-jest"),
+    };
+    let module_type = if output_style().is_js() && !output_style().is_commonjs() && !output_style().is_amd() { "module" } else { "commonjs" };
+
+    let template = template_or_default("PACKAGE_JSON_TEMPLATE", PACKAGE_JSON_TEMPLATE, templates)?;
+    let packages = options.layout.import_aliases(path);
+    for layer in ["core", "infrastructure", "presentation"] {
+        let layer_dir = options.layout.layer_dir(path, layer);
+        let name = packages
+            .iter()
+            .find(|(alias, _)| *alias == format!("@{layer}/"))
+            .map_or_else(|| format!("{project}-{layer}"), |(_, package)| package.trim_end_matches('/').to_string());
+        let content = template
+            .replace("{PACKAGE_NAME}", &name)
+            .replace("{MODULE_TYPE}", module_type)
+            .replace("{BUILD_SCRIPT}", build)
+            .replace("{TEST_SCRIPT}", test)
+            .replace("{DEPENDENCIES}", &entries(&dependencies))
+            .replace("{DEV_DEPENDENCIES}", &entries(&dev_dependencies));
+        check_rendered("PACKAGE_JSON_TEMPLATE", &content)?;
+        let generated: Value = serde_json::from_str(&content)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{} is not valid JSON after rendering: {error}", template_source("PACKAGE_JSON_TEMPLATE"))))?;
+
+        let file_path = layer_dir.join("package.json");
+        let manifest = match file_exists(&file_path) {
+            true => {
+                let mut existing: Value = serde_json::from_str(&read_file(&file_path)?)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {error}", file_path.display())))?;
+                let mut kept = Vec::new();
+                add_missing_keys(&mut existing, &generated, "", &mut kept);
+                // The name and versions are left to the project, a differing type or script most likely comes from earlier flags.
+                kept.retain(|key| key != "name" && !key.starts_with("dependencies.") && !key.starts_with("devDependencies."));
+                if !kept.is_empty() {
+                    warn(format!("{} keeps its own value of {}, delete the key to get the generated one", file_path.display(), kept.join(", ")));
+                }
+                existing
+            }
+            false => generated,
+        };
+        write_file(&file_path, &format!("{}\n", serde_json::to_string_pretty(&manifest)?))?;
+    }
+    Ok(())
+}
+
+// Adds the keys of `generated` that `existing` lacks, the objects of both merged key by key. The dotted keys
+// whose existing value differs from the generated one go to `kept`.
+fn add_missing_keys(existing: &mut Value, generated: &Value, prefix: &str, kept: &mut Vec<String>) {
+    let (Some(existing), Some(generated)) = (existing.as_object_mut(), generated.as_object()) else {
+        return;
+    };
+    for (key, value) in generated {
+        let dotted = format!("{prefix}{key}");
+        match existing.get_mut(key) {
+            Some(current) if current.is_object() && value.is_object() => add_missing_keys(current, value, &format!("{dotted}."), kept),
+            Some(current) if current != value => kept.push(dotted),
+            Some(_) => {}
+            None => {
+                existing.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn implement_health_routes(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default(&template_key("HEALTH_ROUTES_TEMPLATE"), builtin(HEALTH_ROUTES_TEMPLATE, HEALTH_ROUTES_JS_TEMPLATE), templates)?;
+
+    write_project_file(&path.join("healthRoutes.ts"), &content)
+}
+
+fn implement_cached_repository(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(naming().file_name("cached_repository", model));
+
+    // Cached entities are plain objects already, cached models are built back into instances, whose getters
+    // turn the cached bigint strings back. Entities get theirs back through reviveBigInt.
+    let bigint_fields: Vec<String> = match &options.entities {
+        Some(settings) => model.fields.iter().filter(|field| field.is_bigint() && settings.includes(field)).map(|field| format!("\"{}\"", field.name)).collect(),
+        None => Vec::new(),
+    };
+    let parsed = match bigint_fields.is_empty() {
+        true => "JSON.parse(cached)".to_string(),
+        false => format!("JSON.parse(cached, reviveBigInt([{}]))", bigint_fields.join(", ")),
+    };
+    let (cached_item, cached_list) = match (options.entities.is_some(), output_style().is_js()) {
+        (true, true) => (parsed.clone(), parsed),
+        (true, false) => (format!("{parsed} as {}Entity", model.name), format!("{parsed} as {}Entity[]", model.name)),
+        (false, _) => (
+            format!("{}.build(JSON.parse(cached), {{ isNewRecord: false }})", model.name),
+            format!("{}.bulkBuild(JSON.parse(cached), {{ isNewRecord: false }})", model.name),
+        ),
+    };
+    let content = apply_entities(&copy_template(
+        &template_key("CACHED_REPOSITORY_TEMPLATE"),
+        Some(builtin(CACHED_REPOSITORY_TEMPLATE, CACHED_REPOSITORY_JS_TEMPLATE)),
+        model,
+        templates,
+    )?, model, options)
+    .replace("{CACHED_ITEM}", &cached_item)
+    .replace("{CACHED_LIST}", &cached_list)
+    .replace("{PRIMARY_KEY}", &model.primary_key().name);
+    check_rendered(&template_key("CACHED_REPOSITORY_TEMPLATE"), &content)?;
+    let content = match bigint_fields.is_empty() {
+        true => content,
+        false => merge_import(&content, "import { reviveBigInt } from \"@core/utils/bigint\";"),
+    };
+    let content = match options.abstract_base {
+        true => apply_abstract_base(&content, &format!("Abstract{}Repository", model.name), &abstract_repository_import(model), &template_key("CACHED_REPOSITORY_TEMPLATE")),
+        false => content,
+    };
+
+    write_file(&file_path, &content)?;
+
+    Ok(())
+}
+
+// Replaces the key placeholders of the event sourcing templates, which are written as they render.
+fn render_event_sourcing(key: &str, ts: &'static str, js: &'static str, model: &Model, templates: &TemplateCache) -> io::Result<String> {
+    let template_key = template_key(key);
+    let content = copy_template(&template_key, Some(builtin(ts, js)), model, templates)?
+        .replace("{KEY_FROM_MESSAGE}", match model.primary_key().js_type.as_str() {
+            "number" => "Number(message.key.toString())",
+            "bigint" => "BigInt(message.key.toString())",
+            _ => "message.key.toString()",
+        })
+        .replace("{PRIMARY_KEY_TYPE}", &model.primary_key().js_type)
+        .replace("{PRIMARY_KEY}", &model.primary_key().name);
+    check_rendered(&template_key, &content)?;
+    Ok(content)
+}
+
+fn implement_aggregate(path: PathBuf, model: &Model, templates: &TemplateCache) -> io::Result<()>{
+    let content = render_event_sourcing("AGGREGATE_TEMPLATE", AGGREGATE_TEMPLATE, AGGREGATE_JS_TEMPLATE, model, templates)?;
+    write_file(&path.join(format!("{}Aggregate.ts", model.name)), &content)
+}
+
+// The append-only event log of --event-sourcing and the projection that keeps the model table as its read model.
+fn implement_event_sourcing(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let store = render_event_sourcing("EVENT_STORE_TEMPLATE", EVENT_STORE_TEMPLATE, EVENT_STORE_JS_TEMPLATE, model, templates)?;
+    write_file(&path.join(format!("{}EventStore.ts", model.name)), &store)?;
+
+    let projection = render_event_sourcing("PROJECTION_TEMPLATE", PROJECTION_TEMPLATE, PROJECTION_JS_TEMPLATE, model, templates)?;
+    write_file(&path.join(format!("{}ProjectionRepository.ts", model.name)), &projection)?;
+
+    if options.queue_type.as_deref() == Some("kafka") {
+        let consumer = render_event_sourcing("KAFKA_CONSUMER_TEMPLATE", KAFKA_CONSUMER_TEMPLATE, KAFKA_CONSUMER_JS_TEMPLATE, model, templates)?;
+        write_file(&path.join(format!("{}KafkaConsumer.ts", model.name.to_lowercase())), &consumer)?;
+    }
+    Ok(())
+}
+
+fn implement_factory(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(format!("{}Factory.ts", model.name.to_lowercase()));
+
+    let fields: Vec<&Field> = model.fields
+        .iter()
+        .filter(|field| (model.is_supplied(field) || model.is_tenant(field)) && !field.is_virtual())
+        .collect();
+    let defaults = fields
+        .iter()
+        .map(|field| format!("        {}: {},", field.name, field.factory_value()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // Prisma client delegates are the model name with a lowercase first letter.
+    let mut letters = model.name.chars();
+    let delegate: String = letters.next().map(|first| first.to_lowercase().chain(letters).collect()).unwrap_or_default();
+    let (mut imports, create) = match options.orm.as_str() {
+        "prisma" => (
+            vec!["import { PrismaClient } from \"@prisma/client\";".to_string(), String::new(), "const prisma = new PrismaClient();".to_string()],
+            format!("prisma.{delegate}.create({{ data: build{}(overrides) }})", model.name),
+        ),
+        _ => (
+            vec![format!("import {{ {} }} from \"@infrastructure/models/{}\";", model.name, naming().module("model", model))],
+            format!("{0}.create(build{0}(overrides))", model.name),
+        ),
+    };
+    if fields.iter().any(|field| field.factory_value() == "randomUUID()") {
+        imports.insert(0, "import { randomUUID } from \"crypto\";".to_string());
+    }
+
+    let factory_template_key = template_key("FACTORY_TEMPLATE");
+    let content = copy_template(&factory_template_key, Some(builtin(FACTORY_TEMPLATE, FACTORY_JS_TEMPLATE)), model, templates)?
+        .replace("{FACTORY_IMPORT}", &imports.join("\n"))
+        .replace("{DYNAMIC_FACTORY_DEFAULTS}", &defaults)
+        .replace("{FACTORY_CREATE}", &create);
+    check_rendered(&factory_template_key, &content)?;
+
+    write_file(&file_path, &content)
+}
+
+// --tests runs the use cases of each model against an in-memory repository filled by its factory. The mutating ones
+// are checked for their events under --events, and the use cases of a tenant scoped model for the rows of another tenant.
+fn implement_use_case_tests(path: PathBuf, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    let file_path = path.join(format!("{}UseCases.test.ts", model.name));
+    let name = &model.name;
+    let lower = name.to_lowercase();
+    let cast = if output_style().is_js() { "" } else { " as any" };
+
+    let mut imports: Vec<String> = generated_use_cases(model)
+        .into_iter()
+        .map(|(use_case, artifact)| format!("import {{ {use_case} }} from \"@core/use_cases/{name}/{}\";", naming().module(artifact, model)))
+        .collect();
+    imports.push(format!("import {{ build{name} }} from \"../factories/{lower}Factory\";"));
+    if options.events {
+        imports.push(events_import(model, &[]));
+    }
+
+    let keys = model.primary_keys();
+    let composite = model.has_composite_key();
+    let key_type = if composite { model.key_type() } else { keys[0].js_type.clone() };
+    let key_match = match composite {
+        true => keys.iter().map(|key| format!("row.{0} === id.{0}", key.name)).collect::<Vec<_>>().join(" && "),
+        false => format!("row.{} === id", keys[0].name),
+    };
+    let key_of_row = match composite {
+        true => format!("{{ {} }}", keys.iter().map(|key| format!("{0}: row.{0}", key.name)).collect::<Vec<_>>().join(", ")),
+        false => format!("row.{}", keys[0].name),
+    };
+    // Keys the client leaves out are numbered by the repository.
+    let key_default: String = keys
+        .iter()
+        .filter(|key| !model.is_supplied(key))
+        .map(|key| match key.js_type.as_str() {
+            "number" => format!("{}: rows.length + 1, ", key.name),
+            "bigint" => format!("{}: BigInt(rows.length + 1), ", key.name),
+            _ => format!("{}: String(rows.length + 1), ", key.name),
+        })
+        .collect();
+
+    let tenant = model.tenant();
+    let tenant_name = tenant.map_or("", |tenant| tenant.name.as_str());
+    let tenant_only_parameter = match tenant {
+        Some(tenant) if !output_style().is_js() => format!("{tenant_name}: {}", tenant.js_type),
+        _ => tenant_name.to_string(),
+    };
+    let (tenant_parameter, tenant_match, tenant_assignment, tenant_argument, find_all) = match tenant {
+        Some(_) => (
+            format!("{tenant_only_parameter}, "),
+            format!("row.{tenant_name} === {tenant_name} && "),
+            format!(", {tenant_name}"),
+            format!("{tenant_name}, "),
+            format!("rows.filter((row) => row.{tenant_name} === {tenant_name})"),
+        ),
+        None => (String::new(), String::new(), String::new(), String::new(), "[...rows]".to_string()),
+    };
+
+    let [add, gets, delete, update] = use_case_names(model);
+    let scope = if tenant.is_some() { "tenant, " } else { "" };
+    let publishes = |event: &str, payload: &str| match options.events {
+        true => format!("\n        expect(publish).toHaveBeenCalledWith(\"{name}{event}\", {payload});"),
+        false => String::new(),
+    };
+    let mut tests = Vec::new();
+    if let Some(tenant) = tenant {
+        let (own, other) = if tenant.js_type == "number" { ("1", "2") } else { ("\"tenant-a\"", "\"tenant-b\"") };
+        tests.push(format!("    const tenant = {own};\n    const otherTenant = {other};"));
+    }
+    if options.events {
+        tests.push("    const publish = jest.spyOn(eventBus, \"publish\");\n\n    beforeEach(() => publish.mockClear());".to_string());
+    }
+    if !model.is_empty() {
+        tests.push(format!(
+            "    it(\"adds a {lower}\", async () => {{\n        const repository = memoryRepository();\n        await new {add}(repository{cast}).execute({scope}build{name}());\n        expect(repository.rows).toHaveLength(1);{}\n    }});",
+            publishes("Created", &format!("to{name}Payload(repository.rows[0])")),
+        ));
+    }
+    tests.push(format!(
+        "    it(\"gets the {lower} rows\", async () => {{\n        const repository = memoryRepository();\n        const row = await repository.create({scope}build{name}());\n        await expect(new {gets}(repository{cast}).execute({})).resolves.toEqual([row]);\n    }});",
+        scope.trim_end_matches(", "),
+    ));
+    if !model.is_empty() {
+        tests.push(format!(
+            "    it(\"updates a {lower}\", async () => {{\n        const repository = memoryRepository();\n        const row = await repository.create({scope}build{name}());\n        await new {update}(repository{cast}).execute({scope}{key_of_row}, build{name}());\n        expect(repository.update).toHaveBeenCalledWith({scope}row);{}\n    }});",
+            publishes("Updated", &format!("to{name}Payload(row)")),
+        ));
+    }
+    tests.push(format!(
+        "    it(\"deletes a {lower}\", async () => {{\n        const repository = memoryRepository();\n        const row = await repository.create({scope}build{name}());\n        await new {delete}(repository{cast}).execute({scope}{key_of_row});\n        expect(repository.rows).toHaveLength(0);{}\n    }});",
+        publishes("Deleted", &format!("{{ {0}: row.{0} }}", keys[0].name)),
+    ));
+    if tenant.is_some() {
+        let update_attempt = match model.is_empty() {
+            true => String::new(),
+            false => format!(
+                "\n        await new {update}(repository{cast}).execute(otherTenant, {key_of_row}, build{name}()).catch(() => undefined);\n        expect(repository.update).not.toHaveBeenCalled();"
+            ),
+        };
+        tests.push(format!(
+            "    it(\"keeps the {lower} rows of one tenant from another\", async () => {{\n        const repository = memoryRepository();\n        const row = await repository.create(tenant, build{name}());\n        await expect(new {gets}(repository{cast}).execute(otherTenant)).resolves.toEqual([]);{update_attempt}\n        await new {delete}(repository{cast}).execute(otherTenant, {key_of_row});\n        expect(repository.rows).toEqual([row]);\n    }});"
+        ));
+    }
+
+    let test_template_key = template_key("USE_CASE_TEST_TEMPLATE");
+    let content = copy_template(&test_template_key, Some(builtin(USE_CASE_TEST_TEMPLATE, USE_CASE_TEST_JS_TEMPLATE)), model, templates)?
+        .replace("{USE_CASE_TEST_IMPORTS}", &imports.join("\n"))
+        .replace("{TENANT_PARAMETER}", &tenant_parameter)
+        .replace("{TENANT_ONLY_PARAMETER}", &tenant_only_parameter)
+        .replace("{TENANT_MATCH}", &tenant_match)
+        .replace("{TENANT_ASSIGNMENT}", &tenant_assignment)
+        .replace("{TENANT_ARGUMENT}", &tenant_argument)
+        .replace("{FIND_ALL}", &find_all)
+        .replace("{KEY_TYPE}", &key_type)
+        .replace("{KEY_MATCH}", &key_match)
+        .replace("{KEY_DEFAULT}", &key_default)
+        .replace("{DYNAMIC_USE_CASE_TESTS}", &tests.join("\n\n"));
+    check_rendered(&test_template_key, &content)?;
+
+    write_file(&file_path, &content)
+}
+
+fn implement_crypto(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default(&template_key("CRYPTO_TEMPLATE"), builtin(CRYPTO_TEMPLATE, CRYPTO_JS_TEMPLATE), templates)?;
+
+    write_project_file(&path.join("crypto.ts"), &content)
+}
+
+fn implement_redis_client(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default("REDIS_TEMPLATE", REDIS_TEMPLATE, templates)?;
+
+    write_project_file(&path.join("redis.ts"), &content)
+}
+
+fn implement_api_response(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default(&template_key("API_RESPONSE_TEMPLATE"), builtin(API_RESPONSE_TEMPLATE, API_RESPONSE_JS_TEMPLATE), templates)?;
+
+    write_project_file(&path.join("ApiResponse.ts"), &content)
+}
+
+fn implement_bigint(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default(&template_key("BIGINT_TEMPLATE"), builtin(BIGINT_TEMPLATE, BIGINT_JS_TEMPLATE), templates)?;
+
+    write_project_file(&path.join("bigint.ts"), &content)
+}
+
+// Sends the responses through serializeBigInt, JSON.stringify throws on bigint values.
+// Handlers reading the key from req.params get it through parse{NAME}Key, which answers 400 for a key
+// that does not parse. Templates place the helper with {KEY_PARSER}, otherwise it goes above the first handler.
+fn apply_key_parser(content: &str, model: &Model) -> String {
+    let key = model.primary_key().name.as_str();
+    let local = if model.has_composite_key() { "key" } else { key };
+    let param = format!("req.params.{key}");
+    let handler_regex = Regex::new(r"(?m)^.*=\s*async\s*\(\s*req\b[^)]*\)[^{\n]*\{[ \t]*\n").unwrap();
+
+    let mut rendered = String::new();
+    let mut first_handler = None;
+    let mut rest = content;
+    while let Some(handler) = handler_regex.find(rest) {
+        let body_end = handler_regex.find_at(rest, handler.end()).map_or(rest.len(), |next| next.start());
+        let body = &rest[handler.end()..body_end];
+        rendered.push_str(&rest[..handler.end()]);
+        if body.contains(&param) {
+            first_handler.get_or_insert(rendered.len() - handler.len());
+            rendered.push_str(&format!("    const {local} = parse{}Key(req, res);\n    if ({local} === undefined) {{\n        return;\n    }}\n", model.name));
+            rendered.push_str(&body.replace(&param, local));
+        } else {
+            rendered.push_str(body);
+        }
+        rest = &rest[body_end..];
+    }
+    rendered.push_str(rest);
+
+    let Some(first_handler) = first_handler else {
+        return rendered.replace("{KEY_PARSER}", "");
+    };
+    let parsers = model.primary_keys().iter().map(|field| field.to_param_parser()).collect::<Vec<_>>().join("\n");
+    let value = match model.has_composite_key() {
+        true => format!("{{ {} }}", model.primary_keys().iter().map(|field| field.name.as_str()).collect::<Vec<_>>().join(", ")),
+        false => key.to_string(),
+    };
+    let signature = match output_style().is_js() {
+        true => "(req, res)".to_string(),
+        false => {
+            let key_type = if model.has_composite_key() { model.key_type() } else { model.primary_key().js_type.clone() };
+            format!("(req: Request, res: Response): {key_type} | undefined")
+        }
+    };
+    let parser = format!(
+        "// Reads the {0} key from the route params, answering 400 when it does not parse.\nconst parse{0}Key = {signature} => {{\n{parsers}\n    return {value};\n}};\n",
+        model.name
+    );
+    match rendered.contains("{KEY_PARSER}") {
+        true => rendered.replace("{KEY_PARSER}", parser.trim_end()),
+        false => format!("{}{parser}\n{}", &rendered[..first_handler], &rendered[first_handler..]),
+    }
+}
+
+// The Get, Update and Delete requests carry the key with its own type, templates hardcoding `{ id: number }` included.
+fn apply_key_requests(content: &str, model: &Model) -> String {
+    let key_type = model.key_type();
+    let name = regex::escape(&model.name);
+    let update_regex = Regex::new(&format!(r"(Update{name}Request\s*=.*&\s*)\{{\s*id\s*:\s*\w+;?\s*\}}")).unwrap();
+    let mut content = update_regex
+        .replace_all(content, format!("${{1}}{key_type}"))
+        .replace("{KEY_TYPE}", &key_type)
+        .replace("{PRIMARY_KEY_TYPE}", &model.primary_key().js_type)
+        .replace("{PRIMARY_KEY}", &model.primary_key().name);
+
+    for request in ["Get", "Delete"] {
+        let request_name = format!("{request}{}Request", model.name);
+        if Regex::new(&format!(r"\b{request_name}\b")).unwrap().is_match(&content) {
+            continue;
+        }
+        let declaration = match output_style().is_js() {
+            true => format!("/** @typedef {{{key_type}}} {request_name} */"),
+            false => format!("export type {request_name} = {key_type};"),
+        };
+        content = format!("{}\n{declaration}\n", content.trim_end());
+    }
+    content
+}
+
+// Repositories and use cases written for `id: number` take the key with its own type. The parameter stays `id`,
+// a key with another name is looked up under it and a composite key is spread into the where clause.
+fn apply_key_signatures(content: &str, model: &Model) -> String {
+    let key = model.primary_key();
+    let composite = model.has_composite_key();
+    let key_type = if composite { model.key_type() } else { key.js_type.clone() };
+    let content = Regex::new(r"\bid: number\b").unwrap().replace_all(content, regex::NoExpand(&format!("id: {key_type}")));
+    let lookup = match (composite, key.name.as_str()) {
+        (true, _) => "...id".to_string(),
+        (false, "id") => return content.into_owned(),
+        (false, name) => format!("{name}: id"),
+    };
+    let content = Regex::new(r"where: \{\s*id\s*\}").unwrap().replace_all(&content, regex::NoExpand(&format!("where: {{ {lookup} }}")));
+    match composite {
+        true => Regex::new(r"\.findByPk\(id\)").unwrap().replace_all(&content, ".findOne({ where: { ...id } })").into_owned(),
+        false => content.into_owned(),
+    }
+}
+
+fn apply_bigint(content: &str) -> String {
+    let response = Regex::new(r"\.json\((.+)\);").unwrap();
+    if !response.is_match(content) {
+        return content.to_string();
+    }
+    let content = response.replace_all(content, ".json(serializeBigInt($1));").into_owned();
+    insert_import(&content, "import { parseBigInt, serializeBigInt } from \"@core/utils/bigint\";")
+}
+
+// Wraps what the controllers send in the envelope helpers: 201 is created, 4xx and 5xx fail, the rest ok.
+fn apply_envelope(content: &str) -> String {
+    let response = Regex::new(r"res(\.status\((\d+)\))?\.json\((.+)\);").unwrap();
+    let mut helpers = Vec::new();
+    let content = response.replace_all(content, |captures: &regex::Captures| {
+        let helper = match captures.get(2).map(|status| status.as_str()) {
+            Some("201") => "created",
+            Some(status) if status.starts_with(['4', '5']) => "fail",
+            _ => "ok",
+        };
+        if !helpers.contains(&helper) {
+            helpers.push(helper);
+        }
+        format!("res{}.json({helper}({}));", captures.get(1).map_or("", |status| status.as_str()), &captures[3])
+    }).into_owned();
+
+    if helpers.is_empty() {
+        return content;
+    }
+    insert_import(&content, &format!("import {{ {} }} from \"@core/utils/ApiResponse\";", helpers.join(", ")))
+}
+
+fn implement_unit_of_work(path: PathBuf, templates: &TemplateCache) -> io::Result<()>{
+    let content = template_or_default(&template_key("UNIT_OF_WORK_TEMPLATE"), builtin(UNIT_OF_WORK_TEMPLATE, UNIT_OF_WORK_JS_TEMPLATE), templates)?;
+
+    write_project_file(&path.join("unitOfWork.ts"), &content)
+}
+
+fn read_routes_index(index_path: &Path) -> io::Result<String>{
+    if file_exists(index_path) {
+        read_file(index_path)
+    } else {
+        Ok("import { Router } from \"express\";\n\nconst router = Router();\n\nexport default router;\n".to_string())
+    }
+}
+
+fn register_route(path: PathBuf, import: &str, registration: &str) -> io::Result<()>{
+    let index_path = path.join("index.ts");
+    let mut file_content = read_routes_index(&index_path)?;
+
+    let import = module_import(import);
+    if !file_content.contains(&import) {
+        file_content = format!("{import}\n{file_content}");
+    }
+
+    if !file_content.contains(registration) {
+        file_content = match file_content.rfind("export default").or_else(|| file_content.rfind("module.exports")) {
+            Some(position) => format!("{}{registration}\n\n{}", &file_content[..position], &file_content[position..]),
+            None => format!("{file_content}{registration}\n"),
+        };
+    }
+
+    write_file(&index_path, &file_content)?;
+
+    Ok(())
+}
+
+// Middleware has to run before the routes, so it goes right after the router is created.
+fn register_middleware(path: PathBuf, import: &str, registration: &str) -> io::Result<()>{
+    let index_path = path.join("index.ts");
+    let mut file_content = read_routes_index(&index_path)?;
+
+    let import = module_import(import);
+    if !file_content.contains(&import) {
+        file_content = format!("{import}\n{file_content}");
+    }
+
+    if !file_content.contains(registration) {
+        let router = Regex::new(r"(?m)^(?:export )?const router = (?:express\.)?Router\(\);\n").unwrap();
+        file_content = match router.find(&file_content) {
+            Some(found) => format!("{}\n{registration}\n{}", &file_content[..found.end()], &file_content[found.end()..]),
+            None => {
+                warn(format!("{} has no router declaration, add {registration} before the routes", index_path.display()));
+                file_content
+            }
+        };
+    }
+
+    write_file(&index_path, &file_content)
+}
+
+fn example_model(strict: bool) -> Model {
+    let properties: Vec<Field> = vec![
+        Field::validate(
+            vec!["@PrimaryKey", "@AutoIncrement"],
+            "id",
+            "INTEGER",
+            "number",
+            strict
+        ).unwrap_or_else(|error| {
+            println!("Error in fields: {error}");
+            process::exit(1);
+        }),
+        Field::validate(
+            vec![],
+            "content",
+            "STRING",
+            "string",
+            strict
+        ).unwrap_or_else(|error| {
+            println!("Error in fields: {error}");
+            process::exit(1);
+        }),
+        Field::validate(
+            vec![],
+            "name",
+            "STRING",
+            "string",
+            strict
+        ).unwrap_or_else(|error| {
+            println!("Error in fields: {error}");
+            process::exit(1);
+        })
+    ];
+
+    Model::validate(ModelName::example(NAME), ModelName::example(NAME_PLURAL), properties).unwrap_or_else(|error| {
+        println!("Error in model: {error}");
+        process::exit(1);
+    })
+}
+
+fn naming_map(naming: &Naming) -> Value {
+    Value::Object(naming.patterns.iter().map(|(artifact, pattern)| (artifact.clone(), json!(pattern))).collect())
+}
+
+// Renaming conventions midway would leave both the old and new files around,
+// so it has to be asked for explicitly.
+fn check_naming(root: &Path, options: &Options) -> Result<(), String> {
+    let Ok(content) = fs::read_to_string(root.join(MANIFEST_FILE)) else {
+        return Ok(());
+    };
+    let manifest: Value = serde_json::from_str(&content).map_err(|error| format!("{MANIFEST_FILE}: {error}"))?;
+
+    if manifest["naming"] != naming_map(&options.naming) && !options.force {
+        return Err("[naming] differs from the convention this project was generated with, rerun with --force to switch".to_string());
+    }
+
+    Ok(())
+}
+
+// Records the file names of every generated model under the active convention.
+fn update_manifest(root: &Path, models: &[Model], options: &Options) -> io::Result<()>{
+    let manifest_path = root.join(MANIFEST_FILE);
+    let mut manifest: Value = read_file(&manifest_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_else(|| json!({}));
+
+    manifest["naming"] = naming_map(&options.naming);
+    if !manifest["models"].is_object() {
+        manifest["models"] = json!({});
+    }
+    for model in models {
+        let files: serde_json::Map<String, Value> = ARTIFACTS
+            .iter()
+            .map(|(artifact, _)| (artifact.to_string(), json!(options.naming.file_name(artifact, model))))
+            .collect();
+        manifest["models"][model.name.as_str()] = Value::Object(files);
+    }
+
+    if let Some(parent) = manifest_path.parent() {
+        create_output_dir(parent)?;
+    }
+    write_file(&manifest_path, &serde_json::to_string_pretty(&manifest)?)
+}
+
+fn register_model_routes(path: PathBuf, model: &Model, options: &Options) -> io::Result<()>{
+    let routes = format!("{}Routes", model.name.to_lowercase());
+    let registration = format!("router.use(\"{}\", {routes});", route_prefix(model, options));
+
+    // A changed prefix replaces the old registration instead of adding a second one.
+    let index_path = path.join("index.ts");
+    if file_exists(&index_path) {
+        let content = read_file(&index_path)?;
+        if !content.contains(&registration) {
+            let stale = Regex::new(&format!(r#"router\.use\((?:["'][^"']*["'],\s*)?{}\);\n*"#, regex::escape(&routes))).unwrap();
+            write_file(&index_path, &stale.replace_all(&content, ""))?;
+        }
+    }
+
+    register_route(path, &format!("import {routes} from \"@infrastructure/routes/{}\";", naming().module("routes", model)), &registration)
+}
+
+fn model_directories(model: &Model, options: &Options) -> Vec<(&'static str, Vec<&'static str>)> {
+    let mut core = match options.is_minimal() {
+        true if model.is_empty() => vec!["utils"],
+        true => vec!["services", "utils"],
+        false => vec!["interfaces", "use_cases", "utils"],
+    };
+    if options.events {
+        core.push("events");
+    }
+    if options.entities.is_some() {
+        core.push("entities");
+    }
+    if options.event_sourcing {
+        core.push("aggregates");
+    }
+
+    let mut presentation = if model.is_empty() { vec![] } else { vec!["controllers"] };
+    if options.health {
+        presentation.push("routes");
+    }
+    if options.graphql {
+        presentation.push("graphql");
+    }
+    if !model.file_fields().is_empty() || options.logging.is_some() || model.tenant().is_some() {
+        presentation.push("middleware");
+    }
+
+    let mut infrastructure = if options.orm == "prisma" {
+        vec!["config", "prisma", "repositories", "routes"]
+    } else if options.is_minimal() {
+        vec!["config", "models", "routes"]
+    } else {
+        vec!["config", "models", "repositories", "routes"]
+    };
+    if options.migration {
+        infrastructure.push("migrations");
+    }
+    if options.entities.is_some() {
+        infrastructure.push("mappers");
+    }
+    if options.event_sourcing {
+        infrastructure.push("event_store");
+    }
+    if model.is_empty() {
+        infrastructure.retain(|subdir| *subdir != "routes");
+    }
+
+    let mut directories = vec![
+        ("core",
+            core),
+        ("presentation",
+            presentation),
+        ("infrastructure",
+            infrastructure),
+    ];
+    let mut docs = Vec::new();
+    if options.postman {
+        docs.push("postman");
+    }
+    if options.insomnia {
+        docs.push("insomnia");
+    }
+    if options.queue_type.is_some() {
+        docs.push("asyncapi");
+    }
+    if !docs.is_empty() {
+        directories.push(("docs", docs));
+    }
+    if options.factories {
+        let mut tests = vec!["factories"];
+        if options.tests {
+            tests.push("use_cases");
+        }
+        directories.push(("__tests__", tests));
+    }
+
+    for (_, subdirs) in directories.iter_mut() {
+        subdirs.retain(|subdir| options.generates(subdir));
+    }
+    directories.retain(|(_, subdirs)| !subdirs.is_empty());
+    directories
+}
+
+// Generates the files of one layer directory, independent of every other layer of the model.
+// Project-wide files go in the shared directory, which differs from the current one for grouped models.
+fn implement_layer(dir: &str, subdir: &str, current_dir: &Path, shared_dir: &Path, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()>{
+    match (dir, subdir) {
+        ("infrastructure", "models") => implement_model(current_dir.to_path_buf(), model, options, templates)?,
+        ("docs", "postman") => implement_postman_collection(current_dir.to_path_buf(), model, options)?,
+        ("docs", "insomnia") => implement_insomnia_workspace(current_dir.to_path_buf(), model, options)?,
+        ("docs", "asyncapi") => implement_asyncapi(current_dir.to_path_buf(), model, options)?,
+        ("__tests__", "factories") => implement_factory(current_dir.to_path_buf(), model, options, templates)?,
+        ("__tests__", "use_cases") => implement_use_case_tests(current_dir.to_path_buf(), model, options, templates)?,
+        ("core", "aggregates") => implement_aggregate(current_dir.to_path_buf(), model, templates)?,
+        ("infrastructure", "event_store") => implement_event_sourcing(current_dir.to_path_buf(), model, options, templates)?,
+        ("infrastructure", "migrations") => {
+            implement_migration(current_dir.to_path_buf(), model, options, templates)?;
+            if options.dialect == "postgres" && !schema_enums().is_empty() {
+                implement_enum_migration(current_dir.to_path_buf(), templates)?;
+            }
+            let extensions = schema_extensions();
+            if options.dialect == "postgres" && !extensions.is_empty() {
+                implement_extension_migration(current_dir.to_path_buf(), &extensions, templates)?;
+            }
+        }
+        ("core", "interfaces") => implement_interface(current_dir.to_path_buf(), model, options, templates)?,
+        ("core", "entities") => implement_entity(current_dir.to_path_buf(), model, options, templates)?,
+        ("infrastructure", "mappers") => implement_entity_mapper(current_dir.to_path_buf(), model, options, templates)?,
+        ("core", "utils") => {
+            implement_utils(current_dir.to_path_buf(), model, options, templates)?;
+            if !schema_enums().is_empty() {
+                implement_enums(shared_dir.to_path_buf())?;
+            }
+            if options.envelope {
+                implement_api_response(shared_dir.to_path_buf(), templates)?;
+            }
+            if model.has_bigint() {
+                implement_bigint(shared_dir.to_path_buf(), templates)?;
+            }
+            if options.validation.as_deref() == Some("zod") {
+                implement_zod_schema(current_dir.to_path_buf(), model, options, templates)?;
+            }
+        }
+        ("core", "events") => {
+            implement_events(current_dir.to_path_buf(), model, templates)?;
+            implement_event_bus(shared_dir.to_path_buf(), templates)?;
+        }
+        ("core", "use_cases") => implement_use_case(current_dir.to_path_buf(), model, options, templates)?,
+        ("core", "services") => implement_service(current_dir.to_path_buf(), model, options, templates)?,
+        ("infrastructure", "repositories") => {
+            implement_repository(current_dir.to_path_buf(), model, options, templates)?;
+            if options.cache.is_some() {
+                implement_cached_repository(current_dir.to_path_buf(), model, options, templates)?;
+            }
+        }
+        ("presentation", "controllers") => implement_controllers(current_dir.to_path_buf(), model, options, templates)?,
+        ("presentation", "routes") => implement_health_routes(current_dir.to_path_buf(), templates)?,
+        ("presentation", "middleware") => {
+            if !model.file_fields().is_empty() {
+                implement_upload_middleware(current_dir.to_path_buf(), templates)?;
+            }
+            if let Some(logger) = &options.logging {
+                implement_request_logger(current_dir.to_path_buf(), logger, templates)?;
+            }
+            if let Some(tenant) = model.tenant() {
+                implement_tenant_context(current_dir.to_path_buf(), tenant, templates)?;
+            }
+        }
+        ("presentation", "graphql") => implement_graphql_typedefs(current_dir.to_path_buf(), model, options, templates)?,
+        ("infrastructure", "routes") => {
+            implement_routes(current_dir.to_path_buf(), model, options, templates)?;
+        }
+        ("infrastructure", "config") => {
+            if options.orm == "sequelize" {
+                implement_database_config(current_dir.to_path_buf(), options, templates)?;
+            }
+            if model.has_relations() {
+                implement_unit_of_work(current_dir.to_path_buf(), templates)?;
+            }
+            if options.cache.is_some() {
+                implement_redis_client(current_dir.to_path_buf(), templates)?;
+            }
+            if model.has_encrypted() {
+                implement_crypto(current_dir.to_path_buf(), templates)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// Writes the files that belong to the model alone, its layers in parallel.
+fn generate_model_files(root: &Path, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()> {
+    let mut layers = Vec::new();
+    for (dir, subdirs) in model_directories(model, options){
+
+        for subdir in subdirs{
+            let shared_dir = options.layout.layer_dir(root, dir).join(subdir);
+            let current_dir = if SHARED_LAYER_DIRS.contains(&(dir, subdir)) {
+                shared_dir.clone()
+            } else {
+                options.layout.model_layer_dir(root, dir, model).join(subdir)
+            };
+
+            for directory in [&current_dir, &shared_dir] {
+                if !directory.exists() {
+                    create_output_dir(directory)?;
+                }
+            }
+            match current_dir.to_str() {
+                _ if planning_only() => {}
+                Some(path_str) => status(path_str),
+                None => status("Failed to convert PathBuf to string"),
+            }
+            layers.push((dir, subdir, current_dir, shared_dir));
+        }
+    }
+
+    layers.par_iter().try_for_each(|(dir, subdir, current_dir, shared_dir)| {
+        staging_for(model, || implement_layer(dir, subdir, current_dir, shared_dir, model, options, templates))
+    })
+}
+
+// Files every model adds itself to, so they are read and rewritten by one model at a time.
+fn update_shared_files(root: &Path, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()> {
+    let infrastructure = options.layout.layer_dir(root, "infrastructure");
+
+    if !route_prefix(model, options).is_empty() && !model.is_empty() && options.generates("routes") {
+        register_model_routes(infrastructure.join("routes"), model, options)?;
+    }
+    if options.logging.is_some() && options.generates("middleware") {
+        register_middleware(
+            infrastructure.join("routes"),
+            "import requestLogger from \"@presentation/middleware/requestLogger\";",
+            "router.use(requestLogger);"
+        )?;
+    }
+    if options.health && options.generates("routes") {
+        register_route(
+            infrastructure.join("routes"),
+            "import healthRoutes from \"@presentation/routes/healthRoutes\";",
+            "router.use(healthRoutes);"
+        )?;
+    }
+    // The model is registered with the ORM when the layer of its model file is generated.
+    let orm_layer = if options.orm == "prisma" { "prisma" } else { "models" };
+    match (options.orm.as_str(), options.sequelize_files.is_empty()) {
+        _ if !options.generates(orm_layer) => {}
+        ("prisma", _) => implement_prisma_schema(infrastructure.join("prisma"), model)?,
+        (_, true) => update_sequelize(infrastructure.join("config"), model, options, templates)?,
+        (_, false) => {
+            // One file after the other, each update reads what the previous model wrote.
+            for file in &options.sequelize_files {
+                let sequelize_path = root.join(file);
+                if !file_exists(&sequelize_path) {
+                    warn(format!("{} does not exist, {} is not registered in it", sequelize_path.display(), model.name));
+                    continue;
+                }
+                update_sequelize_file(&sequelize_path, model, options)?;
+            }
+        }
+    }
+    if options.di != "none" && options.generates("config") {
+        update_container(infrastructure.join("config"), model, options, templates)?;
+    }
+    if options.di == "inversify" && options.generates("config") {
+        implement_inversify_binding(infrastructure.join("config"), model, options, templates)?;
+    }
+    if options.di == "tsyringe" && options.generates("config") {
+        implement_tsyringe_registration(infrastructure.join("config"), model, options, templates)?;
+    }
+
+    Ok(())
+}
+
+fn generate_model(root: &Path, model: &Model, options: &Options, templates: &TemplateCache) -> io::Result<()> {
+    generate_model_files(root, model, options, templates)?;
+    staging_for(model, || update_shared_files(root, model, options, templates))
+}
+
+fn generate_models(root: &Path, models: &[Model], options: &Options, templates: &TemplateCache) -> io::Result<()> {
+    for model in models {
+        generate_model(root, model, options, templates)?;
+    }
+
+    Ok(())
+}
+
+// Bounds the models generating at once (--jobs) and serializes the shared file updates.
+struct GenerationQueue {
+    permits: Semaphore,
+    shared_files: AsyncMutex<()>,
+    templates: Arc<TemplateCache>
+}
+
+async fn generate_model_async(
+    root: PathBuf,
+    model: Model,
+    options: Arc<Options>,
+    queue: Arc<GenerationQueue>,
+    previous: Option<oneshot::Receiver<()>>,
+    next: oneshot::Sender<()>,
+) -> io::Result<()> {
+    {
+        let _permit = queue.permits.acquire().await.map_err(io::Error::other)?;
+        let (root, model, options, blocking_queue) = (root.clone(), model.clone(), options.clone(), queue.clone());
+        tokio::task::spawn_blocking(move || generate_model_files(&root, &model, &options, &blocking_queue.templates)).await.map_err(io::Error::other)??;
+    }
+
+    // Models take their turn in schema order, so the shared files come out the same on every run.
+    if let Some(previous) = previous {
+        previous.await.ok();
+    }
+    let _shared_files = queue.shared_files.lock().await;
+    staging_for(&model, || update_shared_files(&root, &model, &options, &queue.templates))?;
+    next.send(()).ok();
+
+    Ok(())
+}
+
+async fn generate_models_async(root: PathBuf, models: Vec<Model>, options: Arc<Options>, templates: Arc<TemplateCache>) -> io::Result<()> {
+    let queue = Arc::new(GenerationQueue { permits: Semaphore::new(options.jobs), shared_files: AsyncMutex::new(()), templates });
+
+    let mut previous = None;
+    let mut tasks = Vec::new();
+    for model in models {
+        let (next, turn) = oneshot::channel();
+        tasks.push(tokio::spawn(generate_model_async(root.clone(), model, options.clone(), queue.clone(), previous.replace(turn), next)));
+    }
+    for task in tasks {
+        task.await.map_err(io::Error::other)??;
+    }
+
+    Ok(())
+}
+
+// Files a generation with these options writes, the shared ones counted once.
+fn estimated_files(models: &[Model], options: &Options) -> usize {
+    let mut files: Vec<String> = vec![".env.example".to_string(), MANIFEST_FILE.to_string()];
+    let mut add = |file: String| {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    };
+    for model in models {
+        for (dir, subdirs) in model_directories(model, options) {
+            for subdir in subdirs {
+                let model_files: Vec<String> = match (dir, subdir) {
+                    ("core", "interfaces") if options.abstract_base => vec![model.name.to_string(), format!("Abstract{}Repository", model.name)],
+                    ("presentation", "graphql") if options.federation => vec![model.name.to_string(), format!("{}Resolvers", model.name)],
+                    ("core", "interfaces") | ("core", "events") | ("core", "entities") | ("presentation", "controllers")
+                    | ("presentation", "graphql") | ("infrastructure", "models") | ("infrastructure", "mappers")
+                    | ("infrastructure", "routes") | ("infrastructure", "migrations") | ("docs", _)
+                    | ("core", "services") | ("__tests__", "factories") | ("core", "aggregates") => vec![model.name.to_string()],
+                    ("infrastructure", "event_store") if options.queue_type.is_some() => vec![
+                        format!("{}EventStore", model.name),
+                        format!("{}ProjectionRepository", model.name),
+                        format!("{}KafkaConsumer", model.name.to_lowercase()),
+                    ],
+                    ("infrastructure", "event_store") => vec![format!("{}EventStore", model.name), format!("{}ProjectionRepository", model.name)],
+                    ("core", "use_cases") if options.abstract_base => {
+                        generated_use_cases(model).into_iter().map(|(name, _)| name).chain([format!("Abstract{}UseCase", model.name)]).collect()
+                    }
+                    ("core", "use_cases") => generated_use_cases(model).into_iter().map(|(name, _)| name).collect(),
+                    ("core", "utils") => ["Request", "types", "validation"]
+                        .iter()
+                        .filter(|file| (**file != "Request" || !model.is_empty()) && (**file != "validation" || options.validation.is_some()))
+                        .map(|file| format!("{}/{file}", model.name))
+                        .collect(),
+                    ("infrastructure", "config") if options.di == "inversify" => vec![format!("{}Bindings", model.name.to_lowercase())],
+                    ("infrastructure", "config") if options.di == "tsyringe" => vec![format!("{}DI", model.name.to_lowercase())],
+                    ("infrastructure", "repositories") if options.cache.is_some() => vec![model.name.to_string(), format!("Cached{}", model.name)],
+                    ("infrastructure", "repositories") => vec![model.name.to_string()],
+                    ("__tests__", "use_cases") => vec![format!("{}UseCases.test", model.name)],
+                    _ => Vec::new(),
+                };
+                for file in model_files {
+                    add(format!("{dir}/{subdir}/{file}"));
+                }
+
+                let shared: Vec<(&str, bool)> = match (dir, subdir) {
+                    ("core", "utils") => vec![("ApiResponse", options.envelope), ("bigint", model.has_bigint()), ("enums", !model.enum_names().is_empty())],
+                    ("core", "events") => vec![("EventBus", env::var("EVENT_BUS_MODULE").is_err())],
+                    ("presentation", "routes") => vec![("healthRoutes", true)],
+                    ("presentation", "middleware") => vec![("upload", !model.file_fields().is_empty()), ("requestLogger", options.logging.is_some()), ("tenantContext", model.tenant().is_some())],
+                    ("infrastructure", "config") => vec![
+                        ("database", options.orm == "sequelize"),
+                        ("sequelize", options.orm == "sequelize"),
+                        ("unitOfWork", model.has_relations()),
+                        ("redis", options.cache.is_some()),
+                        ("crypto", model.has_encrypted()),
+                        ("container", options.di != "none"),
+                    ],
+                    ("infrastructure", "prisma") => vec![("schema", true)],
+                    ("infrastructure", "routes") => vec![("index", !route_prefix(model, options).is_empty() || options.logging.is_some() || options.health)],
+                    ("infrastructure", "migrations") => vec![("enums", options.dialect == "postgres" && !model.enum_names().is_empty())],
+                    _ => Vec::new(),
+                };
+                for (file, written) in shared {
+                    if written {
+                        add(format!("{dir}/{subdir}/shared/{file}"));
+                    }
+                }
+            }
+        }
+    }
+    if options.docker.is_some() {
+        files.extend(["Dockerfile".to_string(), "docker-compose.yml".to_string()]);
+    }
+    files.len()
+}
+
+// Crudify stats reads the schema like generate would and only counts.
+fn schema_stats(args: &GenerateArgs) -> Result<(), String> {
+    let json_output = args.json;
+    let config = load_config()?;
+    let options = Options::from_args(args, &config)?;
+    OUTPUT_STYLE.get_or_init(|| options.style.clone());
+
+    let models = match (&options.schema, &options.schema_dir) {
+        (Some(schema_path), _) => load_models(Path::new(schema_path), options.schema_format.as_deref(), options.strict, options.implicit_id())?,
+        (None, Some(schema_dir)) => {
+            let mut models = Vec::new();
+            for file in load_schema_dir(Path::new(schema_dir), options.recursive, options.schema_format.as_deref(), options.strict, options.implicit_id())? {
+                match file.error {
+                    Some(error) if !options.continue_on_error => return Err(format!("{}: {error}", file.path.display())),
+                    Some(error) => warn(format!("{} is left out: {error}", file.path.display())),
+                    None => models.extend(file.models),
+                }
+            }
+            models
+        }
+        (None, None) => vec![example_model(options.strict)],
+    };
+
+    let fields: Vec<&Field> = models.iter().flat_map(|model| &model.fields).collect();
+    let types: Vec<(&str, usize)> = DB_TYPES
+        .iter()
+        .map(|db_type| (*db_type, fields.iter().filter(|field| field.db_type == *db_type).count()))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    let mut attributes: Vec<(&str, usize)> = Vec::new();
+    for attribute in fields.iter().flat_map(|field| &field.attr).map(|attribute| attr_name(attribute)) {
+        match attributes.iter_mut().find(|(name, _)| *name == attribute) {
+            Some((_, count)) => *count += 1,
+            None => attributes.push((attribute, 1)),
+        }
+    }
+    let attribute_count: usize = attributes.iter().map(|(_, count)| count).sum();
+    let files = estimated_files(&models, &options);
+
+    if json_output {
+        let stats = json!({
+            "models": models.len(),
+            "fields": fields.len(),
+            "types": types.iter().map(|(db_type, count)| (db_type.to_string(), json!(count))).collect::<serde_json::Map<_, _>>(),
+            "attributes": attribute_count,
+            "attributeUsage": attributes.iter().map(|(name, count)| (name.to_string(), json!(count))).collect::<serde_json::Map<_, _>>(),
+            "estimatedFiles": files,
+        });
+        println!("{}", serde_json::to_string_pretty(&stats).map_err(|error| error.to_string())?);
+        return Ok(());
+    }
+
+    println!("{:<20}{}", "Models", models.len());
+    println!("{:<20}{}", "Fields", fields.len());
+    println!("{:<20}{attribute_count}", "Attributes");
+    println!("{:<20}{files}", "Estimated files");
+    println!("\n{:<20}Fields", "Type");
+    for (db_type, count) in &types {
+        println!("{db_type:<20}{count}");
+    }
+    if !attributes.is_empty() {
+        println!("\n{:<20}Uses", "Attribute");
+        for (name, count) in &attributes {
+            println!("{name:<20}{count}");
+        }
+    }
+
+    Ok(())
+}
+
+const GENERATE_HELP: &str = "Templates come from the *_TEMPLATE variables of .env, then crudify-templates/, then the built-ins.
+
+Besides the layers of each model, every run writes .env.example, infrastructure/config/database.ts with --orm sequelize \
+and __tests__/factories unless --no-factories. An existing .env.example only gets its missing variables, an edited database.ts is kept. \
+The generated fields of a model sit between // crudify:fields and // crudify:fields-end, so a rerun replaces them and keeps the rest of the class.";
+
+// The command line. Without a subcommand it takes the flags of generate, so a bare crudify in a configured project generates.
+#[derive(Parser, Debug)]
+#[command(name = "crudify", version, about = "Generates the layers of a TypeScript API from the models of a schema", after_help = GENERATE_HELP, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    generate: GenerateArgs
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Generate the models of the schema, also what runs without a subcommand", after_help = GENERATE_HELP)]
+    Generate(GenerateArgs),
+    #[command(about = "Write a crudify.toml and an example schema.yaml to start a project with")]
+    Init(InitArgs),
+    #[command(about = "Read the tables of a database into a schema")]
+    Introspect(IntrospectArgs),
+    #[command(about = "Read the CREATE TABLE statements of a SQL dump into a schema")]
+    ImportSql(ImportSqlArgs),
+    #[command(about = "Generate, then run the migrations with sequelize-cli or push the Prisma schema")]
+    Apply(ApplyArgs),
+    #[command(about = "Write a seeder from a CSV file")]
+    SeedFromCsv(SeedArgs),
+    #[command(about = "Merge the Postman collections of docs/postman")]
+    MergePostman(MergeArgs),
+    #[command(about = "Merge the Insomnia workspaces of docs/insomnia")]
+    MergeInsomnia(MergeArgs),
+    #[command(about = "Merge the AsyncAPI documents of docs/asyncapi")]
+    MergeAsyncapi(MergeArgs),
+    #[command(about = "Load the {name}Bindings.ts modules of infrastructure/config in one container.ts")]
+    MergeInversify(MergeArgs),
+    #[command(about = "Exit with 1 and list the generated files that differ from what generate would write now")]
+    Check(GenerateArgs),
+    #[command(about = "Report what generate needs that is missing: the config, schema, templates, tools, lock and output directory")]
+    Doctor(GenerateArgs),
+    #[command(about = "Delete the files generated for a model and unregister it from the shared files")]
+    Remove(RemoveArgs),
+    #[command(about = "Print the file generate would write for an artifact, without writing it")]
+    Render(RenderArgs),
+    #[command(about = "Print the global config.toml merged under crudify.toml")]
+    Config(ConfigArgs),
+    #[command(about = "Report the models, fields, types and attributes of a schema without writing")]
+    Stats(GenerateArgs),
+    #[command(about = "Generate bundled edge-case schemas with the templates and report the artifacts that fail")]
+    SelfTest(SelfTestArgs),
+    #[command(about = "Write the built-in templates to crudify-templates")]
+    UpgradeTemplates(UpgradeTemplatesArgs),
+    #[command(about = "Print the completion script of a shell")]
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell
+    }
+}
+
+#[derive(Args, Debug)]
+struct ApplyArgs {
+    #[arg(long, help = "List the pending migrations, or print the SQL of the Prisma push, without touching the database")]
+    dry_run: bool,
+    #[command(flatten)]
+    generate: GenerateArgs
+}
+
+#[derive(Args, Debug)]
+struct InitArgs {
+    #[arg(long, default_value = "postgres", value_parser = PossibleValuesParser::new(DIALECTS), help = "Database dialect written to crudify.toml")]
+    dialect: String,
+    #[arg(long, value_name = "prefix", help = "Route prefix written to crudify.toml, e.g. /api")]
+    route_prefix: Option<String>,
+    #[arg(long, help = "Also write the built-in templates to crudify-templates")]
+    templates: bool,
+    #[arg(long, default_value = "ts", value_parser = PossibleValuesParser::new(LANGUAGES), help = "Language of the templates")]
+    lang: String,
+    #[arg(long, help = "Overwrite a crudify.toml or schema.yaml that exists already")]
+    force: bool
+}
+
+#[derive(Args, Debug)]
+struct RemoveArgs {
+    #[arg(value_name = "Model", help = "Model of the schema to remove, generated with the same flags as the ones that follow")]
+    model: String,
+    #[arg(long, help = "List what would be deleted and unregistered without touching the files")]
+    dry_run: bool,
+    #[command(flatten)]
+    generate: GenerateArgs
+}
+
+#[derive(Args, Debug)]
+struct RenderArgs {
+    #[arg(value_name = "artifact", help = "An artifact of [naming] such as repository or add_use_case, or the path of a generated file such as core/utils/Post/types.ts")]
+    artifact: String,
+    #[arg(long, value_name = "Name", help = "Only the files of this model")]
+    model: Option<String>,
+    #[command(flatten)]
+    generate: GenerateArgs
+}
+
+#[derive(Args, Debug)]
+struct IntrospectArgs {
+    #[arg(long, value_name = "url", help = "postgres:// or mysql:// URL of the database, DATABASE_URL by default")]
+    db_url: Option<String>,
+    #[command(flatten)]
+    schema: SchemaOutputArgs
+}
+
+#[derive(Args, Debug)]
+struct ImportSqlArgs {
+    #[arg(value_name = "file.sql", help = "SQL dump with the CREATE TABLE statements, - reads stdin")]
+    file: PathBuf,
+    #[command(flatten)]
+    schema: SchemaOutputArgs
+}
+
+// Where introspect and import-sql put the schema they read.
+#[derive(Args, Debug)]
+struct SchemaOutputArgs {
+    #[arg(long, value_name = "table,...", value_delimiter = ',', help = "Read only these tables")]
+    tables: Vec<String>,
+    #[arg(long, value_name = "file", help = "Write the schema to the file instead of stdout")]
+    output: Option<PathBuf>,
+    #[arg(long, value_name = "generate flags", num_args = 0.., allow_hyphen_values = true, help = "Generate from the schema, the flags after it go to generate")]
+    generate: Option<Vec<String>>
+}
+
+#[derive(Args, Debug)]
+struct SeedArgs {
+    #[arg(help = "Model the rows are seeded into")]
+    model: String,
+    #[arg(help = "CSV file with a header row")]
+    csv: String,
+    #[arg(long, value_name = "schema", help = "Schema the model is read from, the example model otherwise")]
+    config: Option<String>,
+    #[arg(long, value_name = "csvCol=field", help = "Read the field from a column of another name")]
+    map: Vec<String>,
+    #[arg(long, value_name = "format", help = "chrono format of the date columns")]
+    date_format: Option<String>,
+    #[arg(long, help = "Leave out the rows that fail to convert instead of stopping")]
+    skip_invalid: bool
+}
+
+#[derive(Args, Debug)]
+struct MergeArgs {
+    #[arg(help = "Directory of the files to merge")]
+    directory: Option<PathBuf>,
+    #[arg(long, value_name = "file", help = "File the merged result is written to")]
+    output: Option<PathBuf>
+}
+
+#[derive(Args, Debug)]
+struct ConfigArgs {
+    #[arg(long, help = "Print the file each value is set in")]
+    show_origin: bool
+}
+
+#[derive(Args, Debug)]
+struct SelfTestArgs {
+    #[arg(long, value_name = "dir", help = "Local templates to test, the .env templates otherwise")]
+    templates: Option<PathBuf>,
+    #[arg(long, value_name = "dir", help = "Keep the generated files in the directory")]
+    keep_output: Option<PathBuf>,
+    #[arg(long, default_value = "ts", value_parser = PossibleValuesParser::new(LANGUAGES), help = "Language the schemas are generated in")]
+    lang: String,
+    #[arg(long, help = "Skip the type check with tsc")]
+    no_tsc: bool
+}
+
+#[derive(Args, Debug)]
+struct UpgradeTemplatesArgs {
+    #[arg(long, value_name = "dir", default_value = LOCAL_TEMPLATES_DIR, help = "Directory the templates are written to")]
+    target_dir: PathBuf,
+    #[arg(long, default_value = "ts", value_parser = PossibleValuesParser::new(LANGUAGES), help = "Language of the templates")]
+    lang: String,
+    #[arg(long, help = "Print the differences without writing")]
+    dry_run: bool
+}
+
+// Runs hooks_dir/name.sh when there is one, its stdout printed and its stderr turned into warnings.
+fn run_hook(options: &Options, name: &str, args: &[String]) -> io::Result<()> {
+    let Some(hook_path) = options.hooks_dir.as_ref().map(|hooks_dir| hooks_dir.join(name)).filter(|hook_path| hook_path.is_file()) else {
+        return Ok(());
+    };
+
+    let output = process::Command::new("sh").arg(&hook_path).args(args).output()?;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        status(&format!("{name}: {line}"));
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        warn(format!("{name}: {line}"));
+    }
+    if !output.status.success() {
+        return Err(io::Error::other(format!("{} exited with {}", hook_path.display(), output.status)));
+    }
+
+    Ok(())
+}
+
+// crudify apply: the generated migrations run through sequelize-cli, or the Prisma schema pushed to the database.
+// --dry-run lists the pending migrations, or prints the SQL of the push, without touching the database.
+fn apply_migrations(main: &Path, options: &Options, dry_run: bool) -> Result<(), String> {
+    let infrastructure = options.layout.layer_dir(main, "infrastructure");
+    let tool = if options.orm == "prisma" { "prisma" } else { "sequelize-cli" };
+    let target = match tool {
+        "prisma" => infrastructure.join("prisma").join("schema.prisma"),
+        _ => infrastructure.join("migrations"),
+    };
+    let target = target.to_string_lossy();
+    let args = match (tool, dry_run) {
+        ("prisma", false) => vec!["db", "push", "--schema", &target],
+        ("prisma", true) => vec!["migrate", "diff", "--from-schema-datasource", &target, "--to-schema-datamodel", &target, "--script"],
+        (_, false) => vec!["db:migrate", "--migrations-path", &target],
+        (_, true) => vec!["db:migrate:status", "--migrations-path", &target],
+    };
+
+    let output = process::Command::new("npx")
+        .args(["--no-install", tool])
+        .args(&args)
+        .current_dir(main)
+        .output()
+        .map_err(|error| format!("{tool} was not run, npx is not available: {error}"))?;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        status(&format!("{tool}: {line}"));
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines().filter(|line| !line.trim().is_empty()) {
+        warn(format!("{tool}: {line}"));
+    }
+    if !output.status.success() {
+        return Err(format!("npx {tool} {} exited with {}", args.join(" "), output.status));
+    }
+
+    Ok(())
+}
+
+// Runs a formatter over the files this run wrote in one npx call, --prettier and --eslint-fix.
+// It never fails the run, what it reports becomes warnings.
+fn run_formatter(tool: &str, args: &[&str]) {
+    let sources: Vec<PathBuf> = RunReport::written_files()
+        .into_iter()
+        .filter(|file_path| matches!(file_path.extension().and_then(|extension| extension.to_str()), Some("ts") | Some("js")))
+        .collect();
+    if sources.is_empty() {
+        return;
+    }
+
+    let output = match process::Command::new("npx").args(["--no-install", tool]).args(args).args(&sources).output() {
+        Ok(output) => output,
+        Err(error) => {
+            warn(format!("{tool} was not run, npx is not available: {error}"));
+            return;
+        }
+    };
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        warn(format!("{tool}: {line}"));
+    }
+    // Prettier reports the files it wrote, eslint the problems it could not fix.
+    if !output.status.success() {
+        for line in String::from_utf8_lossy(&output.stdout).lines().filter(|line| !line.trim().is_empty()) {
+            warn(format!("{tool}: {line}"));
+        }
+        warn(format!("{tool} failed ({}), the messages above are left to fix by hand", output.status));
+    }
+
+    // Formatting changes the body of the hash-protected files, their hash follows it so they still count as untouched.
+    for file_path in &sources {
+        let marker = format!("{}{HASH_MARKER}", comment_prefix(file_path));
+        let Ok(content) = fs::read_to_string(file_path) else { continue };
+        let Some((header, body)) = content.split_once('\n').filter(|(header, _)| header.starts_with(&marker)) else { continue };
+        if header[marker.len()..] != content_hash(body) {
+            if let Err(error) = atomic_write(file_path, format!("{marker}{}\n{body}", content_hash(body)).as_bytes()) {
+                warn(format!("{}: {error}", file_path.display()));
+            }
+        }
+    }
+}
+
+// The generated content of a formatted file differs from the formatted one on disk, so it is rewritten
+// every run only for the formatter to give it its old content back. Those files get their mtime back as well.
+fn restore_unchanged(snapshot: Vec<(PathBuf, Vec<u8>, SystemTime)>) {
+    for (file_path, previous, modified) in snapshot {
+        if fs::read(&file_path).ok().as_ref() != Some(&previous) {
+            continue;
+        }
+        let restored = File::options().write(true).open(&file_path).and_then(|file| file.set_modified(modified));
+        match restored {
+            Ok(()) => RunReport::record(|report| {
+                for (path, action, _, _) in report.files.iter_mut().filter(|(path, _, _, _)| *path == file_path) {
+                    debug(&format!("{} ended up unchanged", path.display()));
+                    *action = "skipped";
+                }
+            }),
+            Err(error) => warn(format!("could not restore the mtime of {}: {error}", file_path.display())),
+        }
+    }
+}
+
+// Stages every model and the project files in the open transaction.
+fn plan_project(main: &Path, models: &[Model], options: &Options) -> io::Result<()> {
+    let templates = Arc::new(TemplateCache::new(options.template_cache));
+
+    step("generate", || {
+        if options.jobs > 1 && models.len() > 1 {
+            let runtime = tokio::runtime::Runtime::new()?;
+            runtime.block_on(generate_models_async(main.to_path_buf(), models.to_vec(), Arc::new(options.clone()), templates.clone()))
+        } else {
+            generate_models(main, models, options, &templates)
+        }
+    })?;
+
+    step("env", || update_env_example(main, options))?;
+    if options.tsconfig {
+        step("tsconfig", || implement_tsconfig(main, models, options, &templates))?;
+    }
+    if options.package_json {
+        step("package-json", || implement_package_json(main, models, options, &templates))?;
+    }
+    if let Some(settings) = &options.docker {
+        step("docker", || implement_docker(main, options, settings, &templates))?;
+    }
+
+    step("manifest", || update_manifest(main, models, options))
+}
+
+// Crudify check plans the whole generation and compares it to the disk instead of committing it,
+// so CI catches a schema that changed without being regenerated.
+fn check_project(main: &Path, models: &[Model], options: &Options) -> io::Result<()> {
+    GenerationTransaction::begin();
+    plan_project(main, models, options)?;
+
+    let stale = GenerationTransaction::stale();
+    if stale.is_empty() {
+        println!("Generated files are up to date");
+        return Ok(());
+    }
+    println!("{} generated files are out of date, run crudify generate:", stale.len());
+    for file_path in &stale {
+        println!("  {}", file_path.strip_prefix(main).unwrap_or(file_path).display());
+    }
+    process::exit(1);
+}
+
+fn diagnosis(status: &str, check: &str, detail: impl std::fmt::Display) {
+    println!("{status:<5} {check}: {detail}");
+}
+
+// The tools the generated project is built with, the lock and whether the output can be written, as doctor
+// reports them before the config and schema. Returns the number of failed checks.
+fn doctor_environment(main: &Path) -> usize {
+    let mut failures = 0;
+    for tool in ["node", "npx"] {
+        let program = if cfg!(windows) && tool == "npx" { "npx.cmd" } else { tool };
+        match process::Command::new(program).arg("--version").output() {
+            Ok(output) if output.status.success() => diagnosis("ok", tool, String::from_utf8_lossy(&output.stdout).trim()),
+            _ => diagnosis("warn", tool, "not found, the generated project needs it to build and run"),
+        }
+    }
+    match typescript_compiler(main) {
+        Some(compiler) => diagnosis("ok", "tsc", compiler.display()),
+        None => diagnosis("warn", "tsc", "not found, self-test cannot type-check without it"),
+    }
+
+    let lock_path = main.join(LOCK_FILE);
+    match fs::read_to_string(&lock_path).ok().map(|holder| holder.split_whitespace().next().and_then(|pid| pid.parse::<u32>().ok())) {
+        None => diagnosis("ok", "lock", "free"),
+        Some(Some(pid)) if process_alive(pid) => diagnosis("warn", "lock", format!("pid {pid} holds {}, a generation is running", lock_path.display())),
+        Some(pid) => {
+            let holder = pid.map_or_else(|| "an unknown process".to_string(), |pid| format!("pid {pid}"));
+            diagnosis("warn", "lock", format!("{} is stale, left by {holder}, the next run takes it over", lock_path.display()));
+        }
+    }
+
+    // Probed in the nearest directory that exists, generate creates the rest of the path.
+    let existing = main.ancestors().find(|dir| dir.is_dir()).unwrap_or(Path::new("."));
+    let probe = existing.join(".crudify-doctor");
+    match File::create(&probe) {
+        Ok(_) => {
+            fs::remove_file(&probe).ok();
+            let missing = if existing == main { "" } else { ", generate creates it" };
+            diagnosis("ok", "output", format!("{}{missing}", main.display()));
+        }
+        Err(error) => {
+            diagnosis("fail", "output", format!("{} is not writable: {error}", existing.display()));
+            failures += 1;
+        }
+    }
+    failures
+}
+
+// Crudify doctor plans the generation like check, a missing or broken template fails the plan.
+fn doctor_project(main: &Path, models: &[Model], options: &Options, mut failures: usize) -> io::Result<()> {
+    let config = if Path::new(CONFIG_FILE).is_file() { CONFIG_FILE } else { "no crudify.toml, the defaults apply" };
+    diagnosis("ok", "config", config);
+    let source = options.schema.clone().or_else(|| options.schema_dir.clone()).unwrap_or_else(|| "the example model, no --config".to_string());
+    diagnosis("ok", "schema", format!("{} models from {source}", models.len()));
+
+    GenerationTransaction::begin_plan();
+    match plan_project(main, models, options) {
+        Ok(()) => diagnosis("ok", "templates", format!("{} files planned", GenerationTransaction::take().writes.len())),
+        Err(error) => {
+            GenerationTransaction::take();
+            diagnosis("fail", "templates", error);
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+// Crudify remove plans the generation with and without the model. The files only the model was planned for are
+// deleted while they still hold what generate wrote, the shared files it was registered in lose its imports
+// and the lines that use them. Migrations are kept, the table they created needs one that drops it.
+fn remove_model(main: &Path, models: &[Model], options: &Options, name: &str, dry_run: bool) -> Result<(), String> {
+    let model = models.iter().find(|model| model.name.as_str() == name).ok_or_else(|| format!("the schema has no model {name}"))?;
+    let referencing: Vec<&str> = models
+        .iter()
+        .filter(|other| other.name != model.name)
+        .filter(|other| {
+            other.relation_targets().iter().any(|target| *target == name) || other.fields.iter().any(|field| field.reference().is_some_and(|reference| reference.target == name))
+        })
+        .map(|other| other.name.as_str())
+        .collect();
+    if !referencing.is_empty() {
+        return Err(format!("{} still reference {name}, remove the relations first", referencing.join(", ")));
+    }
+
+    let plan = |models: &[Model]| -> Result<GenerationTransaction, String> {
+        GenerationTransaction::begin_plan();
+        let planned = plan_project(main, models, options);
+        let plan = GenerationTransaction::take();
+        planned.map(|_| plan).map_err(|error| error.to_string())
+    };
+    // The files of the project, such as sequelize.ts and its database config, are planned for whichever model comes
+    // first, so without another model they would go as well.
+    let rest: Vec<Model> = models.iter().filter(|other| other.name != model.name).cloned().collect();
+    if rest.is_empty() {
+        return Err(format!("{name} is the only model of the schema, there is no project left to remove it from"));
+    }
+    let all = plan(models)?;
+    let kept = plan(&rest)?;
+
+    let owned: Vec<&(PathBuf, String)> = all
+        .writes
+        .iter()
+        .filter(|(file_path, _)| all.owners.iter().any(|(path, owners)| path == file_path && owners == &[name]))
+        .filter(|(file_path, _)| kept.staged(file_path).is_none())
+        .filter(|(file_path, _)| !file_path.components().any(|component| component.as_os_str() == "migrations"))
+        .collect();
+    let verb = if dry_run { "Would delete" } else { "Deleted" };
+    let (mut deleted, mut changed) = (0, 0);
+    for (file_path, content) in &owned {
+        let relative = file_path.strip_prefix(main).unwrap_or(file_path);
+        match fs::read_to_string(file_path) {
+            Err(_) => continue,
+            Ok(existing) if existing != *content => {
+                warn(format!("{} differs from what generate writes, it is left in place", relative.display()));
+                changed += 1;
+                continue;
+            }
+            Ok(_) => {}
+        }
+        if !dry_run {
+            fs::remove_file(file_path).map_err(|error| format!("{}: {error}", file_path.display()))?;
+            // The directories named after the model, such as its use cases, go along with their last file.
+            if let Some(parent) = file_path.parent().filter(|parent| parent.file_name().is_some_and(|dir| dir == name)) {
+                fs::remove_dir(parent).ok();
+            }
+        }
+        println!("{verb} {}", relative.display());
+        deleted += 1;
+    }
+
+    // A registration that is already there is not written again, so the files are looked for where
+    // update_shared_files writes them rather than in the plan.
+    let infrastructure = options.layout.layer_dir(main, "infrastructure");
+    let mut registrations: Vec<PathBuf> = options.sequelize_files.iter().map(|file| main.join(file)).collect();
+    for dir in ["config", "prisma", "routes"].map(|subdir| infrastructure.join(subdir)) {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        let mut files: Vec<PathBuf> = entries.filter_map(Result::ok).map(|entry| entry.path()).filter(|path| path.is_file()).collect();
+        files.sort();
+        registrations.extend(files);
+    }
+    registrations.retain(|file_path| !owned.iter().any(|(path, _)| path == file_path));
+
+    let modules: Vec<String> = owned.iter().filter_map(|(file_path, _)| Some(file_path.file_stem()?.to_string_lossy().into_owned())).collect();
+    let verb = if dry_run { "Would unregister" } else { "Unregistered" };
+    let mut unregistered = 0;
+    for file_path in &registrations {
+        let Ok(existing) = fs::read_to_string(file_path) else { continue };
+        let (content, leftovers) = unregister_model(&existing, file_path, model, &modules);
+        let relative = file_path.strip_prefix(main).unwrap_or(file_path);
+        if !leftovers.is_empty() {
+            warn(format!("{} still uses {}, remove it by hand", relative.display(), leftovers.join(", ")));
+        }
+        if content == existing {
+            continue;
+        }
+        if !dry_run {
+            atomic_write(file_path, content.as_bytes()).map_err(|error| format!("{}: {error}", file_path.display()))?;
+        }
+        println!("{verb} {name} from {}", relative.display());
+        unregistered += 1;
+    }
+
+    let manifest_path = main.join(MANIFEST_FILE);
+    if let Some(mut manifest) = fs::read_to_string(&manifest_path).ok().and_then(|content| serde_json::from_str::<Value>(&content).ok()) {
+        let listed = manifest["models"].as_object_mut().and_then(|listed| listed.remove(name)).is_some();
+        if listed && !dry_run {
+            let content = serde_json::to_string_pretty(&manifest).map_err(|error| error.to_string())?;
+            atomic_write(&manifest_path, content.as_bytes()).map_err(|error| format!("{}: {error}", manifest_path.display()))?;
+        }
+    }
+
+    let verb = if dry_run { "would be" } else { "were" };
+    println!("{deleted} files of {name} {verb} deleted, {changed} changed ones kept and {unregistered} shared files updated, remove {name} from the schema too");
+    Ok(())
+}
+
+// Drops the imports of the model's modules from a shared file, then the lines that use what they imported,
+// or only the entry of an array such as models: [Post, Tag]. Returns the names the file still uses.
+fn unregister_model(content: &str, file_path: &Path, model: &Model, modules: &[String]) -> (String, Vec<String>) {
+    if file_path.extension().is_some_and(|extension| extension == "prisma") {
+        let block = Regex::new(&format!(r"(?ms)\n*^model {} \{{.*?^\}}\n?", regex::escape(&model.name))).unwrap();
+        return (block.replace(content, "\n").into_owned(), Vec::new());
+    }
+
+    let import = Regex::new(r#"^\s*(?:import\s+(.+?)\s+from\s+|const\s+(.+?)\s*=\s*require\()["']([^"']+)["']\)?;?\s*$"#).unwrap();
+    let mut names: Vec<String> = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        let imported = import.captures(line).filter(|captures| {
+            let module = captures[3].rsplit('/').next().unwrap_or_default();
+            modules.iter().any(|stem| stem == module.trim_end_matches(".js"))
+        });
+        match imported {
+            Some(captures) => {
+                let bindings = captures.get(1).or_else(|| captures.get(2)).map_or("", |bindings| bindings.as_str());
+                for binding in bindings.trim_matches(['{', '}', ' ']).split(',') {
+                    let local = binding.rsplit(" as ").next().unwrap_or(binding).trim();
+                    if !local.is_empty() {
+                        names.push(local.to_string());
+                    }
+                }
+            }
+            None => lines.push(line),
+        }
+    }
+
+    let mut result: Vec<String> = Vec::new();
+    // Set once a line is dropped, so the blank lines around it collapse into one.
+    let mut dropped = false;
+    'lines: for line in lines {
+        let mut line = line.to_string();
+        for name in &names {
+            let word = Regex::new(&format!(r"\b{}\b", regex::escape(name))).unwrap();
+            let Some(found) = word.find(&line) else { continue };
+            if !(line[..found.start()].contains('[') && line[found.end()..].contains(']')) {
+                dropped = true;
+                continue 'lines;
+            }
+            let entry = Regex::new(&format!(r"\b{0}\b\s*,\s*|\s*,\s*\b{0}\b|\b{0}\b", regex::escape(name))).unwrap();
+            line = entry.replace(&line, "").into_owned();
+        }
+        if line.trim().is_empty() && dropped && result.last().is_some_and(|previous| previous.trim().is_empty()) {
+            continue;
+        }
+        dropped &= line.trim().is_empty();
+        result.push(line);
+    }
+    let mut content = result.join("\n");
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+
+    let leftovers = names
+        .into_iter()
+        .filter(|name| Regex::new(&format!(r"\b{}\b", regex::escape(name))).unwrap().is_match(&content))
+        .collect();
+    (content, leftovers)
+}
+
+// Crudify render plans the generation like check and prints the planned files of the artifact, the
+// header of each file only when there are several.
+fn render_artifact(main: &Path, models: &[Model], options: &Options, artifact: &str, model: Option<&str>) -> Result<(), String> {
+    if let Some(name) = model.filter(|name| !models.iter().any(|model| model.name.as_str() == *name)) {
+        return Err(format!("the schema has no model {name}"));
+    }
+    GenerationTransaction::begin_plan();
+    let planned = plan_project(main, models, options);
+    let plan = GenerationTransaction::take();
+    planned.map_err(|error| error.to_string())?;
+
+    let file_names: Option<Vec<String>> =
+        ARTIFACTS.iter().any(|(name, _)| *name == artifact).then(|| models.iter().map(|model| options.naming.file_name(artifact, model)).collect());
+    let owned = |file_path: &Path| model.is_none_or(|name| plan.owners.iter().any(|(path, owners)| path == file_path && owners.iter().any(|owner| owner == name)));
+    let files: Vec<&(PathBuf, String)> = plan
+        .writes
+        .iter()
+        .filter(|(file_path, _)| owned(file_path))
+        .filter(|(file_path, _)| match &file_names {
+            Some(file_names) => file_path.file_name().is_some_and(|file_name| file_names.iter().any(|name| file_name == name.as_str())),
+            None => file_path.strip_prefix(main).unwrap_or(file_path).ends_with(artifact),
+        })
+        .collect();
+
+    if files.is_empty() {
+        let artifacts: Vec<&str> = ARTIFACTS.iter().map(|(name, _)| *name).collect();
+        return Err(format!("{artifact} matches no planned file, name an artifact ({}) or the path of a generated file", artifacts.join(", ")));
+    }
+    for (index, (file_path, content)) in files.iter().enumerate() {
+        if files.len() > 1 {
+            let separator = if index == 0 { "" } else { "\n" };
+            println!("{separator}// {}", file_path.strip_prefix(main).unwrap_or(file_path).display());
+        }
+        print!("{content}");
+    }
+    Ok(())
+}
+
+// Generates every model and the project files in one transaction.
+// A failing pre-generate hook stops the run, a failing post-generate one only warns as the files are written by then.
+fn generate_project(main: &Path, models: &[Model], options: &Options) -> io::Result<()> {
+    if options.hooks_dir.is_some() {
+        step("pre-generate", || {
+            models.iter().try_for_each(|model| run_hook(options, "pre-generate.sh", &[model.name.to_string(), model.fields.len().to_string()]))
+        })?;
+    }
+
+    GenerationTransaction::begin();
+    plan_project(main, models, options)?;
+    let snapshot = if options.touch_changed_only { GenerationTransaction::snapshot() } else { Vec::new() };
+    step("commit", GenerationTransaction::commit)?;
+
+    if options.prettier {
+        step("prettier", || run_formatter("prettier", &["--write"]));
+    }
+    if options.eslint_fix {
+        step("eslint", || run_formatter("eslint", &["--fix"]));
+    }
+    restore_unchanged(snapshot);
+
+    if options.hooks_dir.is_some() {
+        let written: Vec<String> = RunReport::written_files().iter().map(|file_path| file_path.display().to_string()).collect();
+        if let Err(error) = step("post-generate", || run_hook(options, "post-generate.sh", &written)) {
+            warn(error.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    dotenv().ok();
+    let cli = Cli::parse();
+
+    // The project is the directory crudify runs in. CRUDIFY_OUTPUT moves it, self-test points its runs into their
+    // scratch directories with it.
+    let output = match env::var_os("CRUDIFY_OUTPUT") {
+        Some(output) => PathBuf::from(output),
+        None => env::current_dir()?,
+    };
+    let main = output.as_path();
+
+    let (mut render, mut removal) = (None, None);
+    let (command, args, dry_run) = match cli.command.unwrap_or(Command::Generate(cli.generate)) {
+        Command::Generate(args) => ("generate", args, false),
+        Command::Remove(RemoveArgs { model, dry_run, generate }) => {
+            removal = Some(model);
+            ("remove", generate, dry_run)
+        }
+        Command::Render(RenderArgs { artifact, model, generate }) => {
+            render = Some((artifact, model));
+            ("render", generate, false)
+        }
+        Command::Check(args) => ("check", args, false),
+        Command::Doctor(args) => ("doctor", args, false),
+        Command::Apply(ApplyArgs { dry_run, generate }) => ("apply", generate, dry_run),
+        Command::MergePostman(args) => return merge_postman(main, &args),
+        Command::MergeInsomnia(args) => return merge_insomnia(main, &args),
+        Command::MergeInversify(args) => return merge_inversify(main, &args),
+        Command::MergeAsyncapi(args) => return merge_asyncapi(main, &args),
+        Command::Completions { shell } => {
+            // The script completes the binary under the name it was run as, crudify or Crudify.
+            let name = env::args().next().as_deref().map(Path::new).and_then(Path::file_stem).map(|stem| stem.to_string_lossy().into_owned());
+            clap_complete::generate(shell, &mut Cli::command(), name.unwrap_or_else(|| "crudify".to_string()), &mut io::stdout());
+            return Ok(());
+        }
+        Command::Stats(args) => {
+            schema_stats(&args).unwrap_or_else(|error| {
+                println!("Error in stats: {error}");
+                process::exit(1);
+            });
+            return Ok(());
+        }
+        Command::UpgradeTemplates(args) => {
+            upgrade_templates(&args).unwrap_or_else(|error| {
+                println!("Error in upgrade-templates: {error}");
+                process::exit(1);
+            });
+            return Ok(());
+        }
+        Command::Config(args) => {
+            show_config(&args).unwrap_or_else(|error| {
+                println!("Error in config: {error}");
+                process::exit(1);
+            });
+            return Ok(());
+        }
+        Command::Init(args) => {
+            init_project(&args).unwrap_or_else(|error| {
+                println!("Error in init: {error}");
+                process::exit(1);
+            });
+            return Ok(());
+        }
+        Command::Introspect(args) => {
+            introspect(&args).unwrap_or_else(|error| {
+                println!("Error in introspect: {error}");
+                process::exit(1);
+            });
+            return Ok(());
+        }
+        Command::ImportSql(args) => {
+            import_sql(&args).unwrap_or_else(|error| {
+                println!("Error in import-sql: {error}");
+                process::exit(1);
+            });
+            return Ok(());
+        }
+        Command::SelfTest(args) => {
+            let passed = self_test(&args).unwrap_or_else(|error| {
+                println!("Error in self-test: {error}");
+                process::exit(1);
+            });
+            process::exit(if passed { 0 } else { 1 });
+        }
+        Command::SeedFromCsv(args) => {
+            seed_from_csv(main, &args).unwrap_or_else(|error| {
+                println!("Error in seed: {error}");
+                process::exit(1);
+            });
+            return Ok(());
+        }
+    };
+    let check = command == "check";
+    let doctor = command == "doctor";
+    // Neither writes, so neither takes the lock or leaves a report of the run.
+    let planning = check || doctor || render.is_some();
+    let apply = command == "apply";
+
+    STDOUT_RESERVED.get_or_init(|| doctor || render.is_some() || args.json || args.emit_schema.as_deref() == Some("-"));
+    if !planning && removal.is_none() {
+        // The report keeps the arguments as they were typed, without the subcommand.
+        let typed: Vec<String> = env::args().skip(1).collect();
+        let run_args = typed.strip_prefix(&[command.to_string()]).unwrap_or(&typed);
+        RunReport::begin(command, run_args, args.json);
+    }
+
+    // The checks that need no config come first, the pipeline then stops at the first config or schema error.
+    let environment_failures = if doctor { doctor_environment(main) } else { 0 };
+    let config = load_config().unwrap_or_else(|error| fail("config", error, main));
+
+    let mut options = Options::from_args(&args, &config).unwrap_or_else(|error| fail("arguments", error, main));
+    // Sequelize needs the migration files to apply, Prisma pushes its schema.
+    if apply && options.orm == "sequelize" {
+        options.migration = true;
+    }
+
+    PACKAGE_IMPORTS.get_or_init(|| options.layout.import_aliases(main));
+    NAMING.get_or_init(|| options.naming.clone());
+    PLACEHOLDERS.get_or_init(|| options.placeholders.clone());
+    OUTPUT_STYLE.get_or_init(|| options.style.clone());
+    if let Some(template_root) = &options.template_root {
+        TEMPLATE_ROOT.get_or_init(|| template_root.clone());
+    }
+
+    let _lock = (!planning).then(|| RunLock::acquire(main, options.lock_timeout).unwrap_or_else(|error| fail("lock", error, main)));
+    check_naming(main, &options).unwrap_or_else(|error| fail("naming", error, main));
+
+    // Schema files of --schema-dir that failed validation, under --continue-on-error the others are generated.
+    let mut left_out: Vec<String> = Vec::new();
+    let mut models = match &options.schema {
+        Some(schema_path) => {
+            let schema_hash = read_schema(Path::new(schema_path)).ok().map(|content| content_hash(&content));
+            RunReport::record(|report| report.schema_hash = schema_hash);
+            step("schema", || load_models(Path::new(schema_path), options.schema_format.as_deref(), options.strict, options.implicit_id())).unwrap_or_else(|error| fail("schema", error, main))
+        }
+        None => match &options.schema_dir {
+            Some(schema_dir) => {
+                let schema_files = step("schema", || load_schema_dir(Path::new(schema_dir), options.recursive, options.schema_format.as_deref(), options.strict, options.implicit_id()))
+                    .unwrap_or_else(|error| fail("schema", error, main));
+                left_out = schema_files.iter().filter(|file| file.error.is_some()).map(|file| file.path.display().to_string()).collect();
+                if !left_out.is_empty() && (!options.continue_on_error || left_out.len() == schema_files.len()) {
+                    let failures: Vec<String> = schema_files
+                        .iter()
+                        .filter_map(|file| Some(format!("\n  {}: {}", file.path.display(), file.error.as_ref()?)))
+                        .collect();
+                    fail("schema", format!("{} of {} schema files failed validation:{}", left_out.len(), schema_files.len(), failures.concat()), main);
+                }
+                for file in &schema_files {
+                    if let Some(error) = &file.error {
+                        warn(format!("{} is left out: {error}", file.path.display()));
+                    }
+                }
+                let schema_hash = content_hash(&schema_files.iter().map(|file| read_schema(&file.path).unwrap_or_default()).collect::<String>());
+                RunReport::record(|report| {
+                    report.schema_hash = Some(schema_hash);
+                    report.schema_files = schema_files
+                        .iter()
+                        .map(|file| (file.path.clone(), file.models.iter().map(|model| model.name.to_string()).collect(), file.error.clone()))
+                        .collect();
+                });
+                schema_files.into_iter().flat_map(|file| file.models).collect()
+            }
+            None => vec![example_model(options.strict)],
+        },
+    };
+    if let (Some(target), Some(schema_path)) = (&options.emit_schema, &options.schema) {
+        emit_schema(Path::new(schema_path), options.schema_format.as_deref(), target).unwrap_or_else(|error| fail("emit-schema", error, main));
+    }
+    if !options.composite_pk.is_empty() {
+        apply_composite_key(&mut models, &options.composite_pk).unwrap_or_else(|error| fail("model", error, main));
+    }
+    if let Some(tenant_field) = &options.tenant_field {
+        apply_tenant_field(&mut models, tenant_field, options.tenant_type.as_deref().unwrap_or("INTEGER")).unwrap_or_else(|error| fail("model", error, main));
+    }
+    for model in models.iter().filter(|model| model.is_empty()) {
+        if !options.allow_empty {
+            fail(
+                "model",
+                format!("Model {} has no fields besides its primary key, it needs at least one stored field, or --allow-empty to generate only its model, types and the Gets and Delete use cases", model.name),
+                main,
+            );
+        }
+        warn(format!("{} has no fields besides its primary key, its Add and Update use cases, controllers and routes are left out", model.name));
+    }
+
+    for model in &models {
+        model.check_dialect(&options.dialect).unwrap_or_else(|error| fail("model", error, main));
+    }
+    if models.iter().any(|model| model.group.is_some()) && !options.layout.packages.is_empty() {
+        fail("schema", "model groups cannot be combined with --monorepo-packages", main);
+    }
+    GROUP_IMPORTS.get_or_init(|| options.layout.group_aliases(&models));
+    SCHEMA_MODELS.get_or_init(|| models.clone());
+    RunReport::record(|report| report.sequelize_files = options.sequelize_files.iter().map(|file| output_style().source_path(&main.join(file))).collect());
+
+    // Checked up front, the features would otherwise come out of the templates as code that does nothing.
+    let missing = step("features", || missing_feature_placeholders(&models, &options));
+    if !missing.is_empty() && !options.lenient {
+        fail("templates", format!("the templates lack placeholders of enabled features, add them or pass --lenient:\n  {}", missing.join("\n  ")), main);
+    }
+    for message in missing {
+        warn(message);
+    }
+
+    if check {
+        return check_project(main, &models, &options);
+    }
+    if doctor {
+        return doctor_project(main, &models, &options, environment_failures);
+    }
+    if let Some(model) = &removal {
+        remove_model(main, &models, &options, model, dry_run).unwrap_or_else(|error| fail("remove", error, main));
+        return Ok(());
+    }
+    if let Some((artifact, model)) = &render {
+        render_artifact(main, &models, &options, artifact, model.as_deref()).unwrap_or_else(|error| fail("render", error, main));
+        return Ok(());
+    }
+    if let Err(error) = generate_project(main, &models, &options) {
+        RunReport::finish(main, Some(&error.to_string()));
+        return Err(error);
+    }
+
+    let mut groups: Vec<&str> = Vec::new();
+    for group in models.iter().filter_map(|model| model.group.as_deref()) {
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    for group in groups {
+        for layer in ["core", "infrastructure", "presentation"] {
+            status(&format!("Map @{group}/{layer}/* to {}/* in the tsconfig paths", options.layout.group_dir(group, layer)));
+        }
+    }
+    // --tsconfig writes the decorator options itself.
+    match options.decorator_style.as_deref().filter(|_| !options.tsconfig) {
+        Some("experimental") => status("Set experimentalDecorators and emitDecoratorMetadata to true in the tsconfig compilerOptions"),
+        Some(_) => status("Leave experimentalDecorators out of the tsconfig compilerOptions, TypeScript 5 compiles the Stage 3 decorators by default"),
+        None => {}
+    }
+    if apply {
+        step("apply", || apply_migrations(main, &options, dry_run)).unwrap_or_else(|error| fail("apply", error, main));
+    }
+    if !left_out.is_empty() {
+        fail("schema", format!("left out the schema files that failed validation: {}", left_out.join(", ")), main);
+    }
+    RunReport::finish(main, None);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh directory of the system temp dir, unique to the test and the run.
+    fn scratch_dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("crudify-{test}-{}", process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn model(name: &str, fields: Vec<Field>) -> Model {
+        Model::new(ModelName::parse(name).unwrap(), ModelName::parse(&format!("{name}s")).unwrap(), fields)
+    }
+
+    fn post_with_title() -> Model {
+        model("Post", vec![
+            Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number"),
+            Field::new(vec![], "title", "STRING", "string"),
+        ])
+    }
+
+    const REGENERATED_POST: &str = "import { Table, Column, Model, DataType, PrimaryKey, AutoIncrement } from \"sequelize-typescript\";
+
+@Table({ tableName: \"Posts\" })
+export class Post extends Model {
+\t// crudify:fields
+\t@PrimaryKey
+\t@AutoIncrement
+\t@Column(DataType.INTEGER)
+\tid!: number;
+
+\t@Column(DataType.STRING)
+\ttitle!: string;
+\t// crudify:fields-end
+}
+";
+
+    #[test]
+    fn merge_replaces_the_marked_fields_and_keeps_the_rest_of_the_file() {
+        let existing = "import { Table, Column, Model, DataType, PrimaryKey, BeforeCreate } from \"sequelize-typescript\";
+import { slugify } from \"@core/utils/slugify\";
+
+// Posts of the blog.
+@Table({ tableName: \"Posts\" })
+export class Post extends Model {
+\t// crudify:fields
+\t@PrimaryKey
+\t@Column(DataType.INTEGER)
+\tid!: number;
+\t// crudify:fields-end
+
+\t@BeforeCreate
+\tstatic slug(post: Post) {
+\t\tpost.set(\"slug\", slugify(post.title));
+\t}
+}
+";
+
+        assert_eq!(
+            merge_model_fields(existing, REGENERATED_POST, &post_with_title()).unwrap(),
+            "import { Table, Column, Model, DataType, PrimaryKey, BeforeCreate, AutoIncrement } from \"sequelize-typescript\";
+import { slugify } from \"@core/utils/slugify\";
+
+// Posts of the blog.
+@Table({ tableName: \"Posts\" })
+export class Post extends Model {
+\t// crudify:fields
+\t@PrimaryKey
+\t@AutoIncrement
+\t@Column(DataType.INTEGER)
+\tid!: number;
+
+\t@Column(DataType.STRING)
+\ttitle!: string;
+\t// crudify:fields-end
+
+\t@BeforeCreate
+\tstatic slug(post: Post) {
+\t\tpost.set(\"slug\", slugify(post.title));
+\t}
+}
+"
+        );
+    }
+
+    #[test]
+    fn merge_takes_the_leading_properties_of_a_file_without_markers() {
+        let existing = "import { Table, Column, Model, DataType } from \"sequelize-typescript\";
+import { hash } from \"bcrypt\";
+
+@Table({ tableName: \"Posts\" })
+export class Post extends Model {
+\t@Column(DataType.INTEGER)
+\tid!: number;
+
+\t/** The headline. */
+\t@Column(DataType.STRING)
+\ttitle!: string;
+
+\tasync digest() {
+\t\treturn hash(this.title, 10);
+\t}
+}
+";
+
+        assert_eq!(legacy_field_lines(existing, &post_with_title()), Some((5, 11)));
+        assert_eq!(
+            merge_model_fields(existing, REGENERATED_POST, &post_with_title()).unwrap(),
+            "import { Table, Column, Model, DataType, PrimaryKey, AutoIncrement } from \"sequelize-typescript\";
+import { hash } from \"bcrypt\";
+
+@Table({ tableName: \"Posts\" })
+export class Post extends Model {
+\t// crudify:fields
+\t@PrimaryKey
+\t@AutoIncrement
+\t@Column(DataType.INTEGER)
+\tid!: number;
+
+\t@Column(DataType.STRING)
+\ttitle!: string;
+\t// crudify:fields-end
+
+\tasync digest() {
+\t\treturn hash(this.title, 10);
+\t}
+}
+"
+        );
+    }
+
+    #[test]
+    fn merge_gives_up_on_properties_that_are_not_fields() {
+        let existing = "export class Post extends Model {
+\t@Column(DataType.INTEGER)
+\tid!: number;
+
+\tdraft!: boolean;
+}
+";
+        assert_eq!(legacy_field_lines(existing, &post_with_title()), None);
+        assert_eq!(merge_model_fields(existing, REGENERATED_POST, &post_with_title()), None);
+        assert_eq!(merge_model_fields("export class Comment extends Model {\n}\n", REGENERATED_POST, &post_with_title()), None);
+    }
+
+    #[test]
+    fn keep_blocks_carry_their_edited_bodies_over() {
+        let existing = "\tget title(): string {\n\t\t// crudify:keep title\n\t\treturn this.getDataValue(\"title\").trim();\n\t\t// crudify:end title\n\t}\n";
+        let generated = "\tget title(): string {\n\t\t// crudify:keep title\n\t\treturn this.getDataValue(\"title\");\n\t\t// crudify:end title\n\t}\n\tget body(): string {\n\t\t// crudify:keep body\n\t\treturn this.getDataValue(\"body\");\n\t\t// crudify:end body\n\t}\n";
+
+        assert_eq!(
+            preserve_keep_blocks(existing, generated),
+            "\tget title(): string {\n\t\t// crudify:keep title\n\t\treturn this.getDataValue(\"title\").trim();\n\t\t// crudify:end title\n\t}\n\tget body(): string {\n\t\t// crudify:keep body\n\t\treturn this.getDataValue(\"body\");\n\t\t// crudify:end body\n\t}\n"
+        );
+    }
+
+    #[test]
+    fn zod_schema_leaves_out_auto_increment_keys() {
+        let post = model("Post", vec![
+            Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number"),
+            Field::new(vec![], "title", "STRING", "string"),
+        ]);
+
+        assert_eq!(post.to_zod_schema("postgres"), "\ttitle: z.string(),");
+    }
+
+    #[test]
+    fn zod_schema_leaves_out_keys_the_database_defaults() {
+        let post = model("Post", vec![
+            Field::new(vec!["@PrimaryKey"], "id", "UUID", "string"),
+            Field::new(vec![], "title", "STRING", "string"),
+        ]);
+        assert_eq!(post.to_zod_schema("postgres"), "\ttitle: z.string(),");
+
+        let tag = model("Tag", vec![
+            Field::new(vec!["@PrimaryKey", "@Default(\"general\")"], "slug", "STRING", "string"),
+            Field::new(vec![], "label", "STRING", "string"),
+        ]);
+        assert_eq!(tag.to_zod_schema("postgres"), "\tlabel: z.string(),");
+    }
+
+    #[test]
+    fn zod_schema_keeps_keys_the_client_sends() {
+        // SQLite has no UUID default, the client sends the key.
+        let uuid_keyed = model("Post", vec![
+            Field::new(vec!["@PrimaryKey"], "id", "UUID", "string"),
+            Field::new(vec![], "title", "STRING", "string"),
+        ]);
+        assert_eq!(uuid_keyed.to_zod_schema("sqlite"), "\tid: z.string().uuid(),\n\ttitle: z.string(),");
+
+        let code_keyed = model("Country", vec![
+            Field::new(vec!["@PrimaryKey"], "code", "STRING", "string"),
+            Field::new(vec![], "name", "STRING", "string"),
+        ]);
+        assert_eq!(code_keyed.to_zod_schema("postgres"), "\tcode: z.string(),\n\tname: z.string(),");
+    }
+
+    #[test]
+    fn zod_schema_applies_field_attributes() {
+        let user = model("User", vec![
+            Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number"),
+            Field::new(vec!["@Length(2, 40)"], "name", "STRING", "string"),
+            Field::new(vec!["@AllowNull"], "bio", "TEXT", "string"),
+            Field::new(vec!["@Default(0)"], "age", "INTEGER", "number"),
+            Field::new(vec![], "avatar", "FILE", "string"),
+        ]);
+
+        assert_eq!(
+            user.to_zod_schema("postgres"),
+            "\tname: z.string().min(2).max(40),\n\tbio: z.string().nullable(),\n\tage: z.number().int().optional(),"
+        );
+    }
+
+    #[test]
+    fn table_options_default_to_the_migration_table() {
+        let post = model("Post", vec![Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number")]);
+        assert_eq!(post.table_name(), "Posts");
+        assert_eq!(apply_table_options("@Table({TABLE_OPTIONS})", &post, vec![]), "@Table({ tableName: \"Posts\" })");
+        assert_eq!(apply_table_options("@Table({ tableName: \"posts\" })", &post, vec![]), "@Table({ tableName: \"Posts\" })");
+    }
+
+    #[test]
+    fn table_options_follow_the_schema() {
+        let mut post = model("Post", vec![Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number")]);
+        post.table.table_name = Some("blog_posts".to_string());
+        post.table.paranoid = true;
+        let options = post.table_options();
+
+        assert_eq!(apply_table_options("@Table({TABLE_OPTIONS})", &post, options.clone()), "@Table({ tableName: \"blog_posts\", paranoid: true })");
+        assert_eq!(
+            apply_table_options("{ sequelize, tableName: \"posts\" }", &post, options),
+            "{ sequelize, tableName: \"blog_posts\", paranoid: true }"
+        );
+    }
+
+    #[test]
+    fn array_in_file_gets_the_value_once() {
+        let path = scratch_dir("array-in-file").join("sequelize.ts");
+        fs::write(&path, "new Sequelize({ models: [] });\n").unwrap();
+
+        assert!(update_array_in_file(&path, "models", "Post").unwrap());
+        assert!(update_array_in_file(&path, "models", "Tag").unwrap());
+        assert!(update_array_in_file(&path, "models", "Post").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new Sequelize({ models: [Post, Tag] });\n");
+    }
+
+    #[test]
+    fn array_in_file_keeps_the_layout_of_multi_line_arrays() {
+        let path = scratch_dir("multi-line-array").join("sequelize.ts");
+        fs::write(&path, "const models = [\n    User,\n];\n").unwrap();
+
+        assert!(update_array_in_file(&path, "models", "Post").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "const models = [\n    User,\n    Post,\n];\n");
+    }
+
+    #[test]
+    fn array_in_file_is_false_without_the_array() {
+        let path = scratch_dir("missing-array").join("sequelize.ts");
+        fs::write(&path, "new Sequelize({ dialect: \"postgres\" });\n").unwrap();
+
+        assert!(!update_array_in_file(&path, "models", "Post").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new Sequelize({ dialect: \"postgres\" });\n");
+    }
+
+    #[test]
+    fn contract_rejects_templates_missing_a_required_placeholder() {
+        for (artifact, required, _) in TEMPLATE_CONTRACTS.iter().filter(|(_, required, _)| !required.is_empty()) {
+            let env_key = format!("{artifact}_TEMPLATE");
+            let error = check_contract(&env_key, "export {};\n".to_string()).unwrap_err();
+            let missing: Vec<String> = required.iter().map(|placeholder| format!("{{{placeholder}}}")).collect();
+
+            assert_eq!(
+                error.to_string(),
+                format!("built-in {env_key} is missing the required placeholders {}, add them or opt out with /* crudify:ignore NAME */", missing.join(", ")),
+                "{artifact}"
+            );
+        }
+    }
+
+    #[test]
+    fn contract_takes_templates_with_every_required_placeholder() {
+        for (artifact, required, _) in TEMPLATE_CONTRACTS {
+            let content: String = required.iter().map(|placeholder| format!("{{{placeholder}}}\n")).collect();
+            assert_eq!(check_contract(&format!("{artifact}_TEMPLATE"), content.clone()).unwrap(), content, "{artifact}");
+        }
+    }
+
+    #[test]
+    fn contract_drops_ignored_placeholders_and_their_comments() {
+        let content = "/* crudify:ignore DYNAMIC_ADD_PROPERTIES */\nexport class AddUseCase {}\n".to_string();
+        assert_eq!(check_contract("ADD_USE_CASE_TEMPLATE", content).unwrap(), "export class AddUseCase {}\n");
+
+        let content = "/* crudify:ignore DYNAMIC_PROPERTIES_ATTRIBUTES */\nexport {};\n".to_string();
+        assert_eq!(
+            check_contract("TYPES_UTILS_TEMPLATE", content).unwrap_err().to_string(),
+            "built-in TYPES_UTILS_TEMPLATE is missing the required placeholders {DYNAMIC_PROPERTIES_DETAILS}, add them or opt out with /* crudify:ignore NAME */"
+        );
+    }
+
+    #[test]
+    fn rendering_rejects_placeholders_left_unsubstituted() {
+        for (artifact, required, optional) in TEMPLATE_CONTRACTS {
+            let Some(placeholder) = required.iter().chain(optional.iter()).next() else { continue };
+            let env_key = format!("{artifact}_TEMPLATE");
+            let error = check_rendered(&env_key, &format!("export const value = {{{placeholder}}};\n")).unwrap_err();
+
+            assert_eq!(error.to_string(), format!("built-in {env_key} still has {{{placeholder}}} after rendering"), "{artifact}");
+        }
+    }
+
+    #[test]
+    fn rendering_rejects_unknown_placeholders() {
+        for (artifact, _, _) in TEMPLATE_CONTRACTS {
+            let env_key = format!("{artifact}_TEMPLATE");
+            let error = check_rendered(&env_key, "export const value = {DYNAMIC_ADD_PROPERTEIS};\nconst other = {SERVICE_SLUG};\n").unwrap_err();
+
+            assert_eq!(
+                error.to_string(),
+                format!("built-in {env_key} has the unknown placeholders {{DYNAMIC_ADD_PROPERTEIS}}, {{SERVICE_SLUG}}, which no generator or [placeholders] entry fills"),
+                "{artifact}"
+            );
+        }
+    }
+
+    #[test]
+    fn rendering_keeps_template_literals_and_object_literals() {
+        assert!(check_rendered("ADD_USE_CASE_TEMPLATE", "const url = `${BASE_URL}/posts`;\nconst { id } = request;\n").is_ok());
+    }
+
+    #[test]
+    fn feature_placeholders_are_ones_the_generators_fill() {
+        for requirement in FEATURE_PLACEHOLDERS {
+            for placeholder in requirement.placeholders {
+                assert!(is_builtin_placeholder(placeholder), "{} needs {{{placeholder}}}, which no generator fills", requirement.feature);
+            }
+        }
+    }
+
+    #[test]
+    fn feature_placeholders_are_in_the_embedded_templates() {
+        for requirement in FEATURE_PLACEHOLDERS {
+            for artifact in requirement.templates {
+                let name = artifact.to_lowercase();
+                let Some((_, typescript, javascript)) = BUILTIN_TEMPLATES.iter().find(|(builtin, _, _)| *builtin == name) else { continue };
+                assert!(builtin_template(artifact).is_some(), "{artifact} has a built-in the feature check does not fall back on");
+                for template in [typescript, javascript].into_iter().flatten() {
+                    for placeholder in requirement.placeholders {
+                        assert!(template.contains(&format!("{{{placeholder}}}")), "built-in {name} lacks {{{placeholder}}} of {}", requirement.feature);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn model_names_are_normalized_to_pascal_case() {
+        let names = [
+            ("Post", "Post"),
+            ("post", "Post"),
+            ("  Post  ", "Post"),
+            ("blog post", "BlogPost"),
+            ("blog_post", "BlogPost"),
+            ("blog-post", "BlogPost"),
+            ("blog__post-", "BlogPost"),
+            ("blogPost", "BlogPost"),
+            ("order_item2", "OrderItem2"),
+            ("_draft", "Draft"),
+        ];
+        for (input, expected) in names {
+            assert_eq!(ModelName::parse(input).map(|name| name.to_string()), Ok(expected.to_string()), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn model_names_reject_what_no_class_can_be_named() {
+        let names = [
+            ("", "Model name cannot be empty"),
+            ("   ", "Model name cannot be empty"),
+            ("2fa", "Model name 2fa starts with a digit"),
+            ("blog.post", "Model name blog.post has characters other than letters, digits, _, - and spaces"),
+            ("café", "Model name café has characters other than letters, digits, _, - and spaces"),
+            ("__", "Model name __ has characters other than letters, digits, _, - and spaces"),
+            ("model", "Model name Model is reserved"),
+            ("date", "Model name Date is reserved"),
+            ("data_type", "Model name DataType is reserved"),
+        ];
+        for (input, expected) in names {
+            assert_eq!(ModelName::parse(input), Err(expected.to_string()), "{input:?}");
+        }
+    }
+
+    // One test for the whole lifecycle, the process holds a single lock at a time.
+    #[test]
+    fn run_lock_is_exclusive_and_taken_over_when_stale() {
+        let root = scratch_dir("run-lock");
+        let lock_path = root.join(LOCK_FILE);
+
+        let held = RunLock::acquire(&root, Duration::ZERO).unwrap();
+        let Err(error) = RunLock::acquire(&root, Duration::ZERO) else {
+            panic!("a second run took the held lock");
+        };
+        assert_eq!(error, format!("pid {} still holds {} after 0s, remove it if no generation is running", process::id(), lock_path.display()));
+        drop(held);
+        assert!(!lock_path.exists());
+
+        // The test binary listing its tests exits right away, leaving a pid that no longer runs.
+        let mut finished = process::Command::new(env::current_exe().unwrap()).arg("--list").stdout(process::Stdio::null()).spawn().unwrap();
+        finished.wait().unwrap();
+        fs::write(&lock_path, format!("{} 0\n", finished.id())).unwrap();
+        let taken_over = RunLock::acquire(&root, Duration::ZERO).unwrap();
+        assert!(fs::read_to_string(&lock_path).unwrap().starts_with(&format!("{} ", process::id())));
+        drop(taken_over);
+        assert!(!lock_path.exists());
+    }
+
+    // Points NAME_TEMPLATE at a file of the scratch dir holding the content.
+    fn template_file(dir: &Path, name: &str, content: &str) {
+        let template_path = dir.join(format!("{name}.ts.tpl"));
+        fs::write(&template_path, content).unwrap();
+        env::set_var(format!("{}_TEMPLATE", name.to_uppercase()), &template_path);
+    }
+
+    #[test]
+    fn extends_places_the_template_in_each_base_up_the_chain() {
+        let dir = scratch_dir("extends-chain");
+        template_file(&dir, "chain_layout", "// header\n{CHILD_CONTENT}// footer\n");
+        template_file(&dir, "chain_model", "{EXTENDS chain_layout}\nexport class {NAME_UPPER} {\n{CHILD_CONTENT}}\n");
+        let templates = TemplateCache::new(false);
+
+        assert_eq!(
+            resolve_template("{EXTENDS chain_model}\n\tid!: number;\n".to_string(), &templates, 0).unwrap(),
+            "// header\nexport class {NAME_UPPER} {\n\tid!: number;\n}\n// footer\n"
+        );
+        assert_eq!(resolve_template("export class {NAME_UPPER} {}\n".to_string(), &templates, 0).unwrap(), "export class {NAME_UPPER} {}\n");
+    }
+
+    #[test]
+    fn extends_refuses_cycles_and_bases_without_child_content() {
+        let dir = scratch_dir("extends-errors");
+        template_file(&dir, "cycle_a", "{EXTENDS cycle_b}\na\n");
+        template_file(&dir, "cycle_b", "{EXTENDS cycle_a}\nb\n");
+        template_file(&dir, "plain_base", "// no slot\n");
+        let templates = TemplateCache::new(false);
+        let error = |content: &str| resolve_template(content.to_string(), &templates, 0).unwrap_err().to_string();
+
+        assert_eq!(error("{EXTENDS cycle_a}\nbody\n"), "Templates extend more than 10 levels deep at cycle_a, check for a cycle");
+        assert_eq!(error("{EXTENDS plain_base}\nbody\n"), "Base template plain_base has no {CHILD_CONTENT} placeholder");
+        assert_eq!(error("{EXTENDS plain_base\nbody\n"), "{EXTENDS} directive is missing its closing brace");
+    }
+
+    #[test]
+    fn includes_are_resolved_next_to_the_including_template() {
+        let dir = scratch_dir("include-nested");
+        fs::create_dir_all(dir.join("partials")).unwrap();
+        fs::write(dir.join("partials/imports.tpl"), "import { {NAME_UPPER} } from \"./{NAME_LOWER}\";\n{INCLUDE license}").unwrap();
+        fs::write(dir.join("partials/license"), "// MIT\n").unwrap();
+        fs::write(dir.join("model.tpl"), "{INCLUDE partials/imports}\nexport {};\n{{> partials/license}}").unwrap();
+
+        assert_eq!(
+            read_template_file(&dir.join("model.tpl"), &TemplateCache::new(false)).unwrap(),
+            "import { {NAME_UPPER} } from \"./{NAME_LOWER}\";\n// MIT\n\nexport {};\n// MIT\n"
+        );
+        assert_eq!(
+            include_templates("{{> partials/correlation_header}}".to_string(), None, &TemplateCache::new(false), &mut Vec::new()).unwrap(),
+            BUILTIN_PARTIALS[0].1
+        );
+    }
+
+    #[test]
+    fn includes_refuse_cycles_deep_nesting_and_missing_partials() {
+        let dir = scratch_dir("include-errors");
+        let templates = TemplateCache::new(false);
+        let error = |name: &str| read_template_file(&dir.join(name), &templates).unwrap_err().to_string();
+
+        fs::write(dir.join("a.tpl"), "a\n{INCLUDE b}").unwrap();
+        fs::write(dir.join("b.tpl"), "b\n{INCLUDE a.tpl}").unwrap();
+        let a = canonical_path(&dir.join("a.tpl")).display().to_string();
+        let b = canonical_path(&dir.join("b.tpl")).display().to_string();
+        assert_eq!(error("a.tpl"), format!("Circular template include: {a} -> {b} -> {a}"));
+
+        for level in 0..6 {
+            fs::write(dir.join(format!("level{level}.tpl")), format!("{{INCLUDE level{}}}", level + 1)).unwrap();
+        }
+        fs::write(dir.join("level6.tpl"), "deepest\n").unwrap();
+        assert_eq!(
+            error("level0.tpl"),
+            format!("Template includes nest more than 5 levels deep at {}:1", dir.join("level5.tpl").display())
+        );
+
+        fs::write(dir.join("missing.tpl"), "first\nsecond {INCLUDE nowhere}\n").unwrap();
+        assert_eq!(
+            error("missing.tpl"),
+            format!("Partial nowhere included at {}:2 does not exist", dir.join("missing.tpl").display())
+        );
+    }
+
+    #[test]
+    fn conditionals_keep_the_blocks_whose_attribute_a_field_has() {
+        let post = model("Post", vec![
+            Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number"),
+            Field::new(vec!["@Unique"], "slug", "STRING", "string"),
+        ]);
+
+        assert_eq!(process_conditionals("a{IF @Unique}b{/IF}c{IF @Index}d{/IF}e", &post), "abce");
+        assert_eq!(process_conditionals("{IF !@Index}no index{/IF}{IF !@Unique}no unique{/IF}", &post), "no index");
+        assert_eq!(process_conditionals("{IF true}kept{/IF}{IF false}dropped{/IF}", &post), "kept");
+        assert_eq!(
+            process_conditionals("{IF @PrimaryKey}key({IF @AutoIncrement}serial{/IF}{IF @Index}, indexed{/IF}){/IF}", &post),
+            "key(serial)"
+        );
+        assert_eq!(process_conditionals("{IF @Index}outer {IF @Unique}inner{/IF}{/IF}rest", &post), "rest");
+    }
+
+    fn post_with_slug() -> Model {
+        model("Post", vec![
+            Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number"),
+            Field::new(vec!["@Unique"], "slug", "STRING", "string"),
+            Field::new(vec!["@AllowNull"], "views", "INTEGER", "number"),
+        ])
+    }
+
+    #[test]
+    fn loops_repeat_their_body_per_field() {
+        let post = post_with_slug();
+
+        assert_eq!(
+            process_loops("type Row = { {FOREACH field}{FIELD_NAME}: {FIELD_JS_TYPE} /* {FIELD_DB_TYPE} */; {/FOREACH}};", &post.fields),
+            "type Row = { id: number /* INTEGER */; slug: string /* STRING */; views: number /* INTEGER */; };"
+        );
+        assert_eq!(process_loops("[{FOREACH field}{FIELD_NAME}{/FOREACH}] [{FOREACH field}{FIELD_JS_TYPE} {/FOREACH}]", &post.fields[1..]), "[slugviews] [string number ]");
+        assert_eq!(
+            process_loops("{FOREACH field}{FIELD_NAME}({FOREACH field WHERE @Unique}{FIELD_NAME}{/FOREACH}) {/FOREACH}", &post.fields),
+            "id(slug) slug(slug) views(slug) "
+        );
+        assert_eq!(process_loops("{FOREACH field}{FIELD_NAME}", &post.fields), "{FOREACH field}{FIELD_NAME}");
+    }
+
+    #[test]
+    fn add_and_update_properties_put_one_field_per_line() {
+        let post = post_with_slug();
+        let template = "\t\treturn {\n\t\t\t{DYNAMIC_ADD_PROPERTIES}\n\t\t};\n\t\t{DYNAMIC_UPDATE_PROPERTIES}\n";
+
+        let content = replace_indented(template, "{DYNAMIC_ADD_PROPERTIES}", &dynamic_add_properties(&post));
+        assert_eq!(
+            replace_indented(&content, "{DYNAMIC_UPDATE_PROPERTIES}", &dynamic_update_properties(&post)),
+            "\t\treturn {\n\t\t\tslug: request.slug,\n\t\t\tviews: request.views,\n\t\t};\n\t\tpost.slug = request.slug;\n\t\tpost.views = request.views;\n"
+        );
+        assert_eq!(replace_indented("const row = { {VALUE} };", "{VALUE}", "a,\nb"), "const row = { a,\nb };");
+    }
+
+    #[test]
+    fn where_clauses_filter_the_fields_of_a_loop() {
+        let post = post_with_slug();
+
+        assert_eq!(process_loops("{FOREACH field WHERE @PrimaryKey}{FIELD_NAME}{/FOREACH}", &post.fields), "id");
+        assert_eq!(process_loops("{FOREACH field WHERE !@PrimaryKey}{FIELD_NAME},{/FOREACH}", &post.fields), "slug,views,");
+        assert_eq!(process_loops("{FOREACH field WHERE ! @AllowNull}{FIELD_NAME},{/FOREACH}", &post.fields), "id,slug,");
+        assert_eq!(process_loops("[{FOREACH field WHERE @Index}{FIELD_NAME}{/FOREACH}]", &post.fields), "[]");
+        assert_eq!(process_loops("{FOREACH field SORTED}{FIELD_NAME}{/FOREACH}", &post.fields), "idslugviews");
+    }
+
+    #[test]
+    fn loops_number_their_fields_and_mark_the_first_and_last() {
+        let post = post_with_slug();
+        let render = |content: &str| process_conditionals(&process_loops(content, &post.fields), &post);
+
+        assert_eq!(render("{FOREACH field}${COUNTER_1} = {FIELD_NAME}[{COUNTER}] {/FOREACH}"), "$1 = id[0] $2 = slug[1] $3 = views[2] ");
+        assert_eq!(render("({FOREACH field}{FIELD_NAME}{IF IS_LAST}){/IF}{IF !IS_LAST}, {/IF}{/FOREACH}"), "(id, slug, views)");
+        assert_eq!(render("{FOREACH field WHERE !@PrimaryKey}{IF IS_FIRST}SET {/IF}{FIELD_NAME} = ${COUNTER_1}{IF IS_LAST};{/IF}{/FOREACH}"), "SET slug = $1views = $2;");
+        assert_eq!(render("{FOREACH field WHERE @Unique}{IS_FIRST}/{IS_LAST}{/FOREACH}"), "true/true");
+        assert_eq!(
+            render("{FOREACH field}{IF IS_FIRST}[{/IF}{FIELD_NAME}{IF @Unique}!{/IF}{IF IS_LAST}]{/IF}{/FOREACH}"),
+            "[id!slug!views!]"
+        );
+    }
+
+    #[test]
+    fn built_in_templates_share_the_header_partials() {
+        let templates = TemplateCache::new(false);
+        let include = |template: &str| include_templates(template.to_string(), None, &templates, &mut Vec::new()).unwrap();
+
+        for template in [SERVICE_TEMPLATE, SERVICE_JS_TEMPLATE, ENTITY_MAPPER_TEMPLATE, ENTITY_MAPPER_JS_TEMPLATE, PROJECTION_TEMPLATE, PROJECTION_JS_TEMPLATE] {
+            assert!(template.starts_with("{{> partials/model_import}}\n"), "{template}");
+            assert!(include(template).starts_with("import { {NAME_UPPER} } from \"@infrastructure/models/{NAME_LOWER}Model\";\n"));
+        }
+        for template in [REQUEST_LOGGER_PINO_TEMPLATE, REQUEST_LOGGER_MORGAN_TEMPLATE, REQUEST_LOGGER_PINO_JS_TEMPLATE, REQUEST_LOGGER_MORGAN_JS_TEMPLATE] {
+            assert!(include(template).contains("\nexport const CORRELATION_HEADER = \"x-correlation-id\";\n"), "{template}");
+        }
+    }
+
+    #[test]
+    fn models_need_exactly_one_primary_key() {
+        let validate = |fields: Vec<Field>| {
+            Model::validate(ModelName::parse("Post").unwrap(), ModelName::parse("Posts").unwrap(), fields).map(|model| model.primary_key().name.clone())
+        };
+        let key = |name: &str| Field::new(vec!["@PrimaryKey"], name, "INTEGER", "number");
+        let title = || Field::new(vec![], "title", "STRING", "string");
+
+        assert_eq!(validate(vec![key("id"), title()]), Ok("id".to_string()));
+        assert_eq!(validate(vec![title()]), Err("Model Post has no @PrimaryKey field".to_string()));
+        assert_eq!(
+            validate(vec![key("id"), key("slug"), title()]),
+            Err("Model Post has 2 @PrimaryKey fields, pass --composite-pk for a composite key".to_string())
+        );
+        assert_eq!(validate(vec![key("id"), Field::new(vec!["@AutoIncrement"], "position", "INTEGER", "number")]), Ok("id".to_string()));
+    }
+
+    #[test]
+    fn foreign_keys_need_an_integer_or_uuid_type() {
+        let validate = |db_type: &str, js_type: &str, strict: bool| {
+            Field::validate(vec!["@ForeignKey(() => Author)"], "authorId", db_type, js_type, strict).map(|field| field.db_type)
+        };
+
+        for (db_type, js_type) in [("INTEGER", "number"), ("BIGINT", "bigint"), ("UUID", "string")] {
+            assert_eq!(validate(db_type, js_type, true), Ok(db_type.to_string()));
+        }
+        assert_eq!(
+            validate("STRING", "string", true),
+            Err("Field authorId has @ForeignKey but type STRING, expected INTEGER, BIGINT or UUID".to_string())
+        );
+        assert_eq!(validate("STRING", "string", false), Ok("STRING".to_string()));
+    }
+
+    #[test]
+    fn field_names_are_identifiers_and_no_reserved_words() {
+        let validate = |name: &str| Field::validate(vec![], name, "STRING", "string", false).map(|field| field.name);
+
+        for name in ["title", "_internal", "createdAt2", "Status"] {
+            assert_eq!(validate(name), Ok(name.to_string()));
+        }
+        assert_eq!(validate(" "), Err("Field name cannot be empty".to_string()));
+        for name in ["first name", "first-name", "2fa", "café", "total$"] {
+            assert_eq!(validate(name), Err(format!("Field name {name} is not a valid identifier")));
+        }
+        for name in ["class", "new", "return", "await"] {
+            assert_eq!(validate(name), Err(format!("Field name {name} is a reserved word")));
+        }
+    }
+
+    fn status_field(attr: Vec<&str>) -> Field {
+        let mut status = Field::new(attr, "status", "ENUM", "string");
+        status.enumeration = Some(("PostStatus".to_string(), vec!["draft".to_string(), "published".to_string()]));
+        status
+    }
+
+    #[test]
+    fn sequelize_string_of_keys_defaults_enums_bigint_and_nullable_fields() {
+        let sequelize = |field: Field| field.to_sequelize_string(false);
+
+        assert_eq!(
+            sequelize(Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number")),
+            "\t@PrimaryKey\n\t@AutoIncrement\n\t@Column(DataType.INTEGER)\n\tid!: number;"
+        );
+        assert_eq!(sequelize(Field::new(vec!["@Default(0)"], "views", "INTEGER", "number")), "\t@Default(0)\n\t@Column(DataType.INTEGER)\n\tviews!: number;");
+        assert_eq!(sequelize(Field::new(vec!["@AllowNull"], "body", "TEXT", "string")), "\t@AllowNull\n\t@Column(DataType.TEXT)\n\tbody!: string;");
+        assert_eq!(sequelize(status_field(vec!["@Default(\"draft\")"])), "\t@Default(\"draft\")\n\t@Column(DataType.ENUM(...PostStatus))\n\tstatus!: PostStatus;");
+        assert_eq!(
+            sequelize(Field::new(vec![], "total", "BIGINT", "bigint")),
+            "\t@Column({\n\t\ttype: DataType.BIGINT,\n\t\tget() {\n\t\t\tconst value = this.getDataValue(\"total\");\n\t\t\treturn value === null || value === undefined ? value : BigInt(value);\n\t\t},\n\t})\n\ttotal!: bigint;"
+        );
+        assert_eq!(sequelize(Field::new(vec![], "total", "BIGINT", "string")), "\t@Column(DataType.BIGINT)\n\ttotal!: string;");
+        assert_eq!(
+            Field::new(vec!["@Index"], "slug", "STRING", "string").to_sequelize_string(true),
+            "\t@Column(DataType.STRING)\n\tslug!: string;"
+        );
+        assert_eq!(Field::new(vec!["@Index"], "slug", "STRING", "string").to_sequelize_string(false), "\t@Index\n\t@Column(DataType.STRING)\n\tslug!: string;");
+    }
+
+    #[test]
+    fn prisma_string_of_keys_defaults_enums_bigint_and_nullable_fields() {
+        let prisma = |field: Field| field.to_prisma_string();
+
+        assert_eq!(prisma(Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number")), "id Int @id @default(autoincrement())");
+        assert_eq!(prisma(Field::new(vec!["@PrimaryKey"], "id", "UUID", "string")), "id String @id @default(uuid()) @db.Uuid");
+        assert_eq!(prisma(Field::new(vec!["@Default(0)"], "views", "INTEGER", "number")), "views Int @default(0)");
+        assert_eq!(prisma(Field::new(vec!["@AllowNull", "@Length(120)"], "title", "STRING", "string")), "title String? @db.VarChar(120)");
+        assert_eq!(prisma(Field::new(vec!["@AllowNull"], "body", "TEXT", "string")), "body String? @db.Text");
+        assert_eq!(prisma(Field::new(vec!["@Unique"], "total", "BIGINT", "bigint")), "total BigInt @unique");
+        assert_eq!(prisma(status_field(vec!["@Default(\"draft\")"])), "status PostStatus @default(draft)");
+
+        let mut state = Field::new(vec![], "state", "ENUM", "string");
+        state.enumeration = Some(("PostState".to_string(), vec!["in-review".to_string()]));
+        assert_eq!(prisma(state), "state String");
+
+        let post = model("Post", vec![
+            Field::new(vec!["@PrimaryKey", "@AutoIncrement"], "id", "INTEGER", "number"),
+            status_field(vec![]),
+            status_field(vec![]),
+        ]);
+        assert_eq!(post.to_prisma_enums(), vec![("PostStatus", "enum PostStatus {\n  draft\n  published\n}".to_string())]);
+    }
+
+    #[test]
+    fn graphql_type_of_keys_enums_bigint_dates_and_nullable_fields() {
+        let post = model("Post", vec![
+            Field::new(vec!["@PrimaryKey"], "id", "UUID", "string"),
+            Field::new(vec!["@Default(0)"], "views", "INTEGER", "number"),
+            Field::new(vec!["@AllowNull"], "rating", "DECIMAL", "number"),
+            Field::new(vec![], "published", "BOOLEAN", "boolean"),
+            status_field(vec!["@Default(\"draft\")"]),
+            Field::new(vec![], "total", "BIGINT", "bigint"),
+            Field::new(vec!["@AllowNull"], "publishedAt", "DATE", "Date"),
+        ]);
+
+        assert_eq!(
+            post.to_graphql_type(),
+            "  # Date and time columns are exposed as String, import a DateTime scalar to type them precisely
+  # bigint columns are exposed as String, Int only holds 32 bits
+  type Post {
+    id: ID!
+    views: Int!
+    rating: Float
+    published: Boolean!
+    status: String!
+    total: String!
+    publishedAt: String
+  }"
+        );
+        assert_eq!(model("Tag", vec![Field::new(vec!["@PrimaryKey"], "id", "INTEGER", "number")]).to_graphql_type(), "  type Tag {\n    id: Int!\n  }");
+    }
+
+    #[test]
+    fn csv_cells_become_the_stored_json_values() {
+        let cell = |db_type: &str, js_type: &str, value: &str, date_format: Option<&str>| {
+            parse_cell(&Field::new(vec![], "value", db_type, js_type), value, date_format)
+        };
+
+        assert_eq!(cell("INTEGER", "number", "-42", None), Ok(json!(-42)));
+        assert_eq!(cell("INTEGER", "number", "4.2", None), Err("`4.2` is not a valid INTEGER".to_string()));
+        assert_eq!(cell("BIGINT", "bigint", "9007199254740993", None), Ok(json!("9007199254740993")));
+        assert_eq!(cell("DECIMAL", "number", "19.99", None), Ok(json!(19.99)));
+        for (value, parsed) in [("true", true), ("1", true), ("FALSE", false), ("0", false)] {
+            assert_eq!(cell("BOOLEAN", "boolean", value, None), Ok(json!(parsed)));
+        }
+        assert_eq!(cell("BOOLEAN", "boolean", "yes", None), Err("`yes` is not a valid BOOLEAN".to_string()));
+
+        assert_eq!(cell("DATE", "Date", "2024-03-01T12:30:00Z", None), Ok(json!("2024-03-01T12:30:00.000Z")));
+        assert_eq!(cell("DATE", "Date", "2024-03-01 12:30:00", None), Ok(json!("2024-03-01T12:30:00.000Z")));
+        assert_eq!(cell("DATEONLY", "string", "2024-03-01", None), Ok(json!("2024-03-01")));
+        assert_eq!(cell("DATE", "Date", "01/03/2024", None), Err("`01/03/2024` is not a valid DATE".to_string()));
+        assert_eq!(cell("DATE", "Date", "01/03/2024", Some("%d/%m/%Y")), Ok(json!("2024-03-01T00:00:00.000Z")));
+        assert_eq!(cell("DATEONLY", "string", "01/03/2024 08:15", Some("%d/%m/%Y %H:%M")), Ok(json!("2024-03-01")));
+        assert_eq!(cell("DATE", "Date", "2024-03-01", Some("%d/%m/%Y")), Err("`2024-03-01` is not a valid DATE".to_string()));
+
+        assert_eq!(cell("STRING", "string", "", None), Err("empty value for a non-nullable field".to_string()));
+        assert_eq!(parse_cell(&Field::new(vec!["@AllowNull"], "value", "INTEGER", "number"), "", None), Ok(Value::Null));
+    }
+
+    #[test]
+    fn failed_commit_restores_the_written_files_and_leaves_no_staging_files() {
+        let dir = scratch_dir("commit-rollback");
+        fs::write(dir.join("existing.ts"), "old\n").unwrap();
+        fs::create_dir(dir.join("blocked.ts")).unwrap();
+
+        GenerationTransaction::begin();
+        {
+            let mut transaction = TRANSACTION.lock().unwrap();
+            let transaction = transaction.as_mut().unwrap();
+            transaction.stage(&dir.join("existing.ts"), "new\n".to_string());
+            transaction.stage(&dir.join("nested/created.ts"), "created\n".to_string());
+            transaction.stage(&dir.join("blocked.ts"), "never written\n".to_string());
+        }
+        assert!(GenerationTransaction::commit().is_err());
+
+        assert_eq!(fs::read_to_string(dir.join("existing.ts")).unwrap(), "old\n");
+        assert!(!dir.join("nested/created.ts").exists());
+        assert!(dir.join("blocked.ts").is_dir());
+        let mut left: Vec<String> = Vec::new();
+        for directory in [dir.clone(), dir.join("nested")] {
+            left.extend(fs::read_dir(directory).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned()));
+        }
+        left.sort();
+        assert_eq!(left, ["blocked.ts", "existing.ts", "nested"]);
+        assert!(TRANSACTION.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn commonjs_rewrites_imports_and_named_exports() {
+        let source = "import \"reflect-metadata\";
+import express, { Router as ExpressRouter } from \"express\";
+import * as path from \"path\";
+import { Post } from \"@infrastructure/models/postModel\";
+
+export const postRouter = ExpressRouter();
+export async function listPosts() {
+    return Post.findAll();
+}
+const root = path.resolve(\".\");
+export { root as postRoot };
+";
+
+        assert_eq!(
+            to_commonjs(source),
+            "require(\"reflect-metadata\");
+const express = require(\"express\");
+const { Router: ExpressRouter } = express;
+const path = require(\"path\");
+const { Post } = require(\"@infrastructure/models/postModel\");
+
+const postRouter = ExpressRouter();
+async function listPosts() {
+    return Post.findAll();
+}
+const root = path.resolve(\".\");
+
+module.exports = { postRouter, listPosts, postRoot: root };
+"
+        );
+        assert_eq!(to_commonjs("const { Post } = require(\"./post\");\n\nmodule.exports = { Post };\n"), "const { Post } = require(\"./post\");\n\nmodule.exports = { Post };\n");
+    }
+
+    #[test]
+    fn commonjs_hangs_named_exports_off_the_default_export() {
+        assert_eq!(
+            to_commonjs("export class PostService {}\nexport default new PostService();\n"),
+            "class PostService {}\nmodule.exports = new PostService();\n\nmodule.exports.PostService = PostService;\n"
+        );
+    }
+
+    #[test]
+    fn commonjs_requires_re_exports() {
+        assert_eq!(
+            to_commonjs("export { Post, PostAttributes as Attributes } from \"./post\";\nexport * as enums from \"./enums\";\nexport * from \"./types\";\n"),
+            "const { Post, PostAttributes: Attributes } = require(\"./post\");
+const enums = require(\"./enums\");
+
+module.exports = { Post, Attributes, enums };
+Object.assign(module.exports, require(\"./types\"));
+"
+        );
+        assert_eq!(to_commonjs("export * from './types';\n"), "Object.assign(module.exports, require('./types'));\n");
+    }
+
+    #[test]
+    fn amd_wraps_the_commonjs_body_below_the_leading_comments() {
+        let source = "// crudify-hash: 0123\n// Post routes.\nimport { Router } from \"express\";\n\nexport const postRouter = Router();\n";
+        let wrapped = "// crudify-hash: 0123
+// Post routes.
+define(function (require, exports, module) {
+const { Router } = require(\"express\");
+
+const postRouter = Router();
+
+module.exports = { postRouter };
+});
+";
+
+        assert_eq!(to_amd(source), wrapped);
+        assert_eq!(to_amd(wrapped), wrapped);
+        assert_eq!(
+            to_amd(&wrapped.replace("define(", "import { Post } from \"./post\";\ndefine(")),
+            wrapped.replace("define(function (require, exports, module) {\n", "define(function (require, exports, module) {\nconst { Post } = require(\"./post\");\n")
+        );
+    }
+
+    #[test]
+    fn line_diff_shows_inserted_deleted_and_changed_lines_in_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+
+        assert_eq!(line_diff(old, old), "");
+        assert_eq!(line_diff(old, "a\nb\nc\nd\ne\nnew\nf\ng\nh\ni\nj\n"), "@@\n d\n e\n+new\n f\n g\n");
+        assert_eq!(line_diff(old, "b\nc\nd\ne\nf\ng\nh\ni\nj\n"), "@@\n-a\n b\n c\n");
+        assert_eq!(line_diff(old, "a\nb\nc\nd\ne\nf\ng\nh\ni\nJ\n"), "@@\n h\n i\n-j\n+J\n");
+        assert_eq!(
+            line_diff(old, "A\nb\nc\nd\ne\nf\ng\nh\ni\nJ\n"),
+            "@@\n-a\n+A\n b\n c\n@@\n h\n i\n-j\n+J\n"
+        );
+        assert_eq!(line_diff("a\nb\nc\n", "a\nB\nc\n"), "@@\n a\n-b\n+B\n c\n");
+        assert_eq!(line_diff(old, "a\nB\nc\nd\nE\nf\ng\nh\ni\nj\n"), "@@\n a\n-b\n+B\n c\n d\n-e\n+E\n f\n g\n");
+    }
 }
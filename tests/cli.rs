@@ -0,0 +1,76 @@
+use std::process::{Command, Output};
+
+fn crudify(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_Crudify")).args(args).current_dir(std::env::temp_dir()).env("CRUDIFY_OUTPUT", "crudify-cli-test").output().unwrap()
+}
+
+#[test]
+fn completions_cover_the_subcommands_and_their_flags() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = crudify(&["completions", shell]);
+        let script = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{shell}: {}", String::from_utf8_lossy(&output.stderr));
+        for word in ["generate", "init", "remove", "check", "doctor", "import-sql", "render", "self-test", "seed-from-csv", "composite-pk", "show-origin", "keep-output"] {
+            assert!(script.contains(word), "{shell} completions lack {word}");
+        }
+    }
+
+    let output = crudify(&["completions", "tcsh"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid value 'tcsh'"));
+}
+
+#[test]
+fn generate_help_documents_the_placeholder_flags() {
+    let output = crudify(&["generate", "--help"]);
+    let help = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    for (flag, placeholder) in [
+        ("--dialect", "{DIALECT}"),
+        ("--indexes", "{TABLE_INDEXES}"),
+        ("--di", "{DI_INJECTABLE}"),
+        ("--cache", "{REPOSITORY_INSTANCE}"),
+        ("--events", "{DYNAMIC_PUBLISH_EVENT}"),
+        ("--logging", "{LOG_ERROR}"),
+        ("--validation", "{ZOD_SCHEMA_BODY}"),
+        ("--version-prefix", "{VERSION_PREFIX}"),
+        ("--api-versioning", "{VERSION_MIDDLEWARE}"),
+        ("--set", "{KEY}"),
+        ("--graphql", "{DYNAMIC_GRAPHQL_TYPE}"),
+        ("--migration", "{DYNAMIC_MIGRATION_COLUMNS}"),
+        ("--domain-entities", "{TO_ENTITY}"),
+    ] {
+        let line = help.lines().position(|line| line.trim_start().starts_with(&format!("{flag} ")) || line.trim() == flag);
+        let line = line.unwrap_or_else(|| panic!("{flag} is missing from\n{help}"));
+        assert!(help.lines().nth(line + 1).unwrap().contains(placeholder), "{flag} does not mention {placeholder}\n{help}");
+    }
+    assert!(help.contains("possible values: postgres, mysql, sqlite, mssql"), "{help}");
+}
+
+#[test]
+fn generate_help_lists_the_project_files_every_run_writes() {
+    let output = crudify(&["generate", "--help"]);
+    let help = String::from_utf8_lossy(&output.stdout);
+    for file in [".env.example", "infrastructure/config/database.ts", "__tests__/factories", "// crudify:fields"] {
+        assert!(help.contains(file), "{file} is missing from\n{help}");
+    }
+}
+
+#[test]
+fn conflicting_flags_are_rejected_before_generating() {
+    for (args, message) in [
+        (&["--module", "cjs", "--target-lang", "amd"][..], "'--module <MODULE>' cannot be used with '--target-lang <TARGET_LANG>'"),
+        (&["generate", "--config", "schema.yaml", "--schema-dir", "schemas"][..], "cannot be used with '--schema-dir <dir>'"),
+        (&["generate", "--recursive"][..], "--schema-dir <dir>"),
+        (&["generate", "--federation"][..], "--graphql"),
+        (&["generate", "--tenant-type", "UUID"][..], "--tenant-field <name>"),
+        (&["generate", "--orm", "mongoose"][..], "invalid value 'mongoose' for '--orm <ORM>'"),
+        (&["generate", "--only", "models", "--skip", "routes"][..], "'--only <layer,...>' cannot be used with '--skip <layer,...>'"),
+        (&["--only", "model"][..], "invalid value 'model' for '--only <layer,...>'"),
+    ] {
+        let output = crudify(args);
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{args:?}: {stderr}");
+    }
+}
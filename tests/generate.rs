@@ -0,0 +1,957 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output, Stdio};
+
+// A fresh project directory holding the fixture templates and the given schema.
+fn project(test: &str, schema: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("crudify-it-{test}-{}", process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    let templates = dir.join("crudify-templates");
+    fs::create_dir_all(&templates).unwrap();
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/templates");
+    for entry in fs::read_dir(fixtures).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), templates.join(entry.file_name())).unwrap();
+    }
+    fs::write(dir.join("schema.yaml"), schema).unwrap();
+    dir
+}
+
+fn generate(project: &Path, flags: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_Crudify"))
+        .args(["generate", "--config", "schema.yaml", "--dialect", "postgres"])
+        .args(flags)
+        .current_dir(project)
+        .env("CRUDIFY_OUTPUT", "out")
+        .output()
+        .unwrap()
+}
+
+fn generated(project: &Path, path: &str) -> String {
+    fs::read_to_string(project.join("out").join(path)).unwrap_or_else(|err| panic!("{path}: {err}"))
+}
+
+#[test]
+fn integer_key_is_auto_incremented_and_looked_up_by_pk() {
+    let dir = project("integer-key", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let model = generated(&dir, "infrastructure/models/postModel.ts");
+    assert!(model.contains("@Table({ tableName: \"Posts\" })"), "{model}");
+    assert!(model.contains("\t@PrimaryKey\n\t@AutoIncrement\n\t@Column(DataType.INTEGER)\n\tid!: number;"), "{model}");
+
+    let repository = generated(&dir, "infrastructure/repositories/postRepository.ts");
+    assert!(repository.contains("async findById(id: number): Promise<Post | null> {\n        return Post.findByPk(id);"), "{repository}");
+    assert!(repository.contains("async delete(id: number): Promise<void> {\n        await Post.destroy({ where: { id } });"), "{repository}");
+
+    let types = generated(&dir, "core/utils/Post/types.ts");
+    assert!(types.contains("export type PostDetails = {\n\ttitle: string;\n};"), "{types}");
+}
+
+#[test]
+fn uuid_key_is_typed_as_a_string() {
+    let dir = project("uuid-key", &fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("self_test/uuid_key.yaml")).unwrap());
+    let output = generate(&dir, &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let model = generated(&dir, "infrastructure/models/sessionModel.ts");
+    assert!(model.contains("\t@PrimaryKey\n\t@Column(DataType.UUID)\n\tid!: string;"), "{model}");
+    assert!(!model.contains("@AutoIncrement\n"), "{model}");
+
+    let repository = generated(&dir, "infrastructure/repositories/sessionRepository.ts");
+    assert!(repository.contains("async findById(id: string): Promise<Session | null> {\n        return Session.findByPk(id);"), "{repository}");
+    assert!(repository.contains("async delete(id: string): Promise<void> {\n        await Session.destroy({ where: { id } });"), "{repository}");
+    assert!(!repository.contains("id: number"), "{repository}");
+
+    let interface = generated(&dir, "core/interfaces/ISessionRepository.ts");
+    assert!(interface.contains("findById(id: string): Promise<Session | null>;"), "{interface}");
+    assert!(interface.contains("delete(id: string): Promise<void>;"), "{interface}");
+}
+
+#[test]
+fn named_string_key_is_looked_up_under_its_own_name() {
+    let dir = project("named-key", "models:
+  Country:
+    fields:
+      code: { db_type: STRING, js_type: string, attr: [\"@PrimaryKey\"] }
+      name: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let model = generated(&dir, "infrastructure/models/countryModel.ts");
+    assert!(model.contains("\t@PrimaryKey\n\t@Column(DataType.STRING)\n\tcode!: string;"), "{model}");
+
+    let repository = generated(&dir, "infrastructure/repositories/countryRepository.ts");
+    assert!(repository.contains("async findById(id: string): Promise<Country | null> {\n        return Country.findByPk(id);"), "{repository}");
+    assert!(repository.contains("await Country.destroy({ where: { code: id } });"), "{repository}");
+}
+
+#[test]
+fn composite_key_is_spread_into_the_where_clause() {
+    let dir = project("composite-key", &fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("self_test/composite_key.yaml")).unwrap());
+    let output = generate(&dir, &["--composite-pk", "orderId,productId"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let model = generated(&dir, "infrastructure/models/orderlineModel.ts");
+    assert!(model.contains("\t@PrimaryKey\n\t@Column(DataType.INTEGER)\n\torderId!: number;"), "{model}");
+    assert!(model.contains("\t@PrimaryKey\n\t@Column(DataType.INTEGER)\n\tproductId!: number;"), "{model}");
+
+    let repository = generated(&dir, "infrastructure/repositories/orderlineRepository.ts");
+    let key = "id: { orderId: number; productId: number }";
+    assert!(repository.contains(&format!("async findById({key}): Promise<OrderLine | null> {{\n        return OrderLine.findOne({{ where: {{ ...id }} }});")), "{repository}");
+    assert!(repository.contains(&format!("async delete({key}): Promise<void> {{\n        await OrderLine.destroy({{ where: {{ ...id }} }});")), "{repository}");
+    assert!(!repository.contains("findByPk"), "{repository}");
+
+    let request = generated(&dir, "core/utils/OrderLine/Request.ts");
+    assert!(request.contains("orderId"), "{request}");
+    assert!(request.contains("productId"), "{request}");
+}
+
+const ID_ONLY_SCHEMA: &str = "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+  Tag:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+";
+
+#[test]
+fn id_only_model_fails_validation_without_allow_empty() {
+    let dir = project("id-only-rejected", ID_ONLY_SCHEMA);
+    let output = generate(&dir, &[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "{stdout}");
+    assert!(stdout.contains("Model Tag has no fields besides its primary key, it needs at least one stored field, or --allow-empty"), "{stdout}");
+    assert!(!dir.join("out/infrastructure/models/postModel.ts").exists());
+    assert!(!dir.join("out/infrastructure/models/tagModel.ts").exists());
+}
+
+#[test]
+fn id_only_model_skips_its_write_layers_with_allow_empty() {
+    let dir = project("id-only-allowed", ID_ONLY_SCHEMA);
+    let output = generate(&dir, &["--allow-empty"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Warning: Tag has no fields besides its primary key, its Add and Update use cases, controllers and routes are left out"), "{stdout}");
+
+    for path in [
+        "infrastructure/models/tagModel.ts",
+        "infrastructure/repositories/tagRepository.ts",
+        "core/interfaces/ITagRepository.ts",
+        "core/use_cases/Tag/GetTags.ts",
+        "core/use_cases/Tag/DeleteTag.ts",
+    ] {
+        assert!(dir.join("out").join(path).exists(), "{path} was not generated");
+    }
+    for path in [
+        "core/use_cases/Tag/AddTag.ts",
+        "core/use_cases/Tag/UpdateTag.ts",
+        "core/utils/Tag/Request.ts",
+        "presentation/controllers/tagControllers.ts",
+        "infrastructure/routes/tagRoutes.ts",
+    ] {
+        assert!(!dir.join("out").join(path).exists(), "{path} was generated");
+    }
+    assert!(dir.join("out/presentation/controllers/postControllers.ts").exists());
+
+    let types = generated(&dir, "core/utils/Tag/types.ts");
+    assert!(types.contains("export type TagAttributes = {\n\tid: number;\n};"), "{types}");
+    assert!(generated(&dir, "infrastructure/config/sequelize.ts").contains("models: [Post, Tag],"));
+}
+
+#[test]
+fn tenant_filter_is_in_every_repository_query() {
+    let dir = project("tenant", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &["--tenant-field", "tenantId"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let model = generated(&dir, "infrastructure/models/postModel.ts");
+    assert!(model.contains("\t@Index\n\t@Column(DataType.INTEGER)\n\ttenantId!: number;"), "{model}");
+
+    let repository = generated(&dir, "infrastructure/repositories/postRepository.ts");
+    for query in [
+        "return Post.create({ ...details, tenantId });",
+        "return Post.findAll({ ...options, where: { ...options?.where, tenantId } });",
+        "return Post.findOne({ where: { id, tenantId } });",
+        "return (post.tenantId === tenantId ? post.save() : Promise.reject(new Error(\"Post belongs to another tenant\")));",
+        "await Post.destroy({ where: { id, tenantId } });",
+    ] {
+        assert!(repository.contains(query), "{query} is missing from\n{repository}");
+    }
+    assert!(!repository.contains("findByPk"), "{repository}");
+    assert!(!repository.contains("findAll(options)"), "{repository}");
+    let methods = repository.lines().filter(|line| line.trim_start().starts_with("async ")).collect::<Vec<_>>();
+    assert_eq!(methods.len(), 5, "{repository}");
+    assert!(methods.iter().all(|line| line.contains("(tenantId: number, ")), "{repository}");
+
+    let interface = generated(&dir, "core/interfaces/IPostRepository.ts");
+    let signatures = interface.lines().filter(|line| line.contains("): Promise<")).collect::<Vec<_>>();
+    assert_eq!(signatures.len(), 5, "{interface}");
+    assert!(signatures.iter().all(|line| line.contains("(tenantId: number, ")), "{interface}");
+
+    let controllers = generated(&dir, "presentation/controllers/postControllers.ts");
+    assert_eq!(controllers.matches(".execute(req.tenant.id").count(), 4, "{controllers}");
+    assert!(dir.join("out/presentation/middleware/tenantContext.ts").exists());
+}
+
+#[test]
+fn tsconfig_enables_the_experimental_decorators_only_when_the_output_uses_them() {
+    let schema = "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+";
+    for (test, flags, experimental) in [
+        ("tsconfig-sequelize", &["--tsconfig"][..], true),
+        ("tsconfig-prisma", &["--tsconfig", "--orm", "prisma"][..], false),
+        ("tsconfig-prisma-di", &["--tsconfig", "--orm", "prisma", "--di", "tsyringe"][..], true),
+    ] {
+        let dir = project(test, schema);
+        let output = generate(&dir, flags);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+        for layer in ["core", "infrastructure", "presentation"] {
+            let tsconfig = generated(&dir, &format!("{layer}/tsconfig.json"));
+            assert!(tsconfig.contains(&format!("\"experimentalDecorators\": {experimental},")), "{test}: {tsconfig}");
+            assert!(tsconfig.contains(&format!("\"emitDecoratorMetadata\": {experimental},")), "{test}: {tsconfig}");
+        }
+    }
+}
+
+#[test]
+fn decorator_style_decides_the_tsconfig_decorator_settings() {
+    let schema = "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+";
+    for (style, experimental) in [("stage3", false), ("experimental", true)] {
+        let dir = project(&format!("decorator-style-{style}"), schema);
+        let output = generate(&dir, &["--tsconfig", "--orm", "prisma", "--decorator-style", style]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+        let tsconfig = generated(&dir, "core/tsconfig.json");
+        assert!(tsconfig.contains(&format!("\"experimentalDecorators\": {experimental},")), "{style}: {tsconfig}");
+    }
+
+    let dir = project("decorator-style-hint", schema);
+    let output = generate(&dir, &["--orm", "prisma", "--decorator-style", "stage3"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Leave experimentalDecorators out of the tsconfig compilerOptions"), "{stdout}");
+
+    let dir = project("decorator-style-sequelize", schema);
+    let output = generate(&dir, &["--decorator-style", "stage3"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "{stdout}");
+    assert!(stdout.contains("--orm sequelize needs the experimental decorators"), "{stdout}");
+}
+
+#[test]
+fn output_defaults_to_the_current_directory() {
+    let dir = project("current-dir", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify"))
+        .args(["generate", "--config", "schema.yaml"])
+        .current_dir(&dir)
+        .env_remove("CRUDIFY_OUTPUT")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(dir.join("infrastructure/models/postModel.ts").is_file());
+    assert!(dir.join("infrastructure/repositories/postRepository.ts").is_file());
+    assert!(!dir.join("out").exists());
+}
+
+#[test]
+fn bare_invocation_generates_like_generate() {
+    let schema = "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+";
+    let explicit = project("explicit-generate", schema);
+    assert!(generate(&explicit, &[]).status.success());
+    let bare = project("bare-generate", schema);
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify"))
+        .args(["--config", "schema.yaml", "--dialect", "postgres"])
+        .current_dir(&bare)
+        .env("CRUDIFY_OUTPUT", "out")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    for path in ["infrastructure/models/postModel.ts", "infrastructure/repositories/postRepository.ts", "presentation/controllers/postControllers.ts"] {
+        assert_eq!(generated(&bare, path), generated(&explicit, path), "{path}");
+    }
+}
+
+// Paths of the files generate wrote under out, the run bookkeeping left out.
+fn written_files(project: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut directories = vec![project.join("out")];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(&directory).unwrap() {
+            let path = entry.unwrap().path();
+            match path.is_dir() {
+                true => directories.push(path),
+                false => files.push(path.strip_prefix(project.join("out")).unwrap().to_string_lossy().replace('\\', "/")),
+            }
+        }
+    }
+    files.retain(|file| !file.starts_with(".crudify/") && file != ".env.example");
+    files.sort();
+    files
+}
+
+#[test]
+fn only_generates_the_named_layers() {
+    let dir = project("only", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &["--only", "models,repositories", "--route-prefix", "/api"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert_eq!(
+        written_files(&dir),
+        ["infrastructure/config/sequelize.ts", "infrastructure/models/postModel.ts", "infrastructure/repositories/postRepository.ts"]
+    );
+    assert!(generated(&dir, "infrastructure/config/sequelize.ts").contains("models: [Post],"));
+}
+
+#[test]
+fn skip_leaves_out_the_named_layers_and_their_registrations() {
+    let dir = project("skip", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &["--skip", "controllers,routes,factories", "--route-prefix", "/api"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let files = written_files(&dir);
+    assert!(files.iter().all(|file| !file.contains("controllers/") && !file.contains("routes/") && !file.contains("factories/")), "{files:?}");
+    for file in ["infrastructure/models/postModel.ts", "infrastructure/repositories/postRepository.ts", "core/use_cases/Post/AddPost.ts", "core/utils/Post/types.ts"] {
+        assert!(files.iter().any(|written| written == file), "{file} is missing from {files:?}");
+    }
+
+    let unfiltered = project("unfiltered", &fs::read_to_string(dir.join("schema.yaml")).unwrap());
+    assert!(generate(&unfiltered, &["--route-prefix", "/api"]).status.success());
+    assert!(generated(&unfiltered, "infrastructure/routes/index.ts").contains("router.use(\"/api\", postRoutes);"));
+}
+
+const DUMP: &str = "-- pg_dump
+CREATE TYPE public.post_status AS ENUM ('draft', 'published');
+CREATE TABLE public.blog_posts (
+    id integer NOT NULL,
+    title character varying(255) NOT NULL,
+    body text,
+    status public.post_status DEFAULT 'draft'::public.post_status NOT NULL
+);
+ALTER TABLE public.blog_posts ALTER COLUMN id ADD GENERATED BY DEFAULT AS IDENTITY (SEQUENCE NAME public.blog_posts_id_seq);
+ALTER TABLE ONLY public.blog_posts ADD CONSTRAINT blog_posts_pkey PRIMARY KEY (id);
+/* mysqldump */
+CREATE TABLE IF NOT EXISTS `categories` (
+  `id` int(11) NOT NULL AUTO_INCREMENT,
+  `name` varchar(100) NOT NULL,
+  `active` tinyint(1) NOT NULL DEFAULT '1',
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `name` (`name`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+";
+
+#[test]
+fn import_sql_reads_the_create_table_statements_of_a_dump() {
+    let dir = project("import-sql", "");
+    fs::write(dir.join("dump.sql"), DUMP).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify")).args(["import-sql", "dump.sql"]).current_dir(&dir).output().unwrap();
+    let schema = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{schema}");
+    assert!(schema.starts_with("enums:\n  PostStatus:\n  - draft\n  - published\nmodels:\n  BlogPost:\n    plural: BlogPosts\n    tableName: blog_posts\n"), "{schema}");
+    assert!(schema.contains("      id:\n        db_type: INTEGER\n        js_type: number\n        attr:\n        - '@PrimaryKey'\n        - '@AutoIncrement'\n      title:"), "{schema}");
+    assert!(schema.contains("      body:\n        db_type: TEXT\n        js_type: string\n        attr:\n        - '@AllowNull'\n"), "{schema}");
+    assert!(schema.contains("      status:\n        db_type: ENUM\n        js_type: string\n        enum: PostStatus\n"), "{schema}");
+    assert!(schema.contains("  Category:\n    plural: Categories\n    tableName: categories\n"), "{schema}");
+    assert!(schema.contains("      active:\n        db_type: BOOLEAN\n        js_type: boolean\n"), "{schema}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify")).args(["import-sql", "dump.sql", "--tables", "categories"]).current_dir(&dir).output().unwrap();
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("BlogPost"));
+
+    fs::write(dir.join("empty.sql"), "SELECT 1;").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify")).args(["import-sql", "empty.sql"]).current_dir(&dir).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Error in import-sql: empty.sql has no CREATE TABLE statements"));
+}
+
+#[test]
+fn import_sql_generates_the_tables_with_generate() {
+    let dir = project("import-sql-generate", "");
+    fs::write(dir.join("dump.sql"), DUMP).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify"))
+        .args(["import-sql", "dump.sql", "--output", "schema.yaml", "--generate", "--dialect", "postgres"])
+        .current_dir(&dir)
+        .env("CRUDIFY_OUTPUT", "out")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(fs::read_to_string(dir.join("schema.yaml")).unwrap().contains("tableName: blog_posts"));
+    let model = generated(&dir, "infrastructure/models/blogpostModel.ts");
+    assert!(model.contains("@Table({ tableName: \"blog_posts\", timestamps: false })"), "{model}");
+    assert!(generated(&dir, "infrastructure/models/categoryModel.ts").contains("\tactive!: boolean;"));
+}
+
+#[test]
+fn init_writes_a_config_and_a_schema_generate_runs_with() {
+    let dir = project("init", "");
+    let init = |flags: &[&str]| Command::new(env!("CARGO_BIN_EXE_Crudify")).arg("init").args(flags).current_dir(&dir).output().unwrap();
+    let output = init(&["--dialect", "mysql"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Error in init: schema.yaml exists already, rerun with --force to overwrite it"));
+    assert!(!dir.join("crudify.toml").exists());
+
+    let output = init(&["--dialect", "mysql", "--route-prefix", "/api", "--force"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert_eq!(fs::read_to_string(dir.join("crudify.toml")).unwrap(), "dialect = \"mysql\"\nroute_prefix = \"/api\"\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify")).args(["generate", "--config", "schema.yaml"]).current_dir(&dir).env("CRUDIFY_OUTPUT", "out").output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(generated(&dir, "infrastructure/models/postModel.ts").contains("\tcontent!: string;"));
+    assert!(generated(&dir, "infrastructure/config/sequelize.ts").contains("dialect: \"mysql\","));
+    assert!(generated(&dir, "infrastructure/routes/index.ts").contains("router.use(\"/api\", postRoutes);"));
+}
+
+#[test]
+fn init_writes_the_built_in_templates_with_templates() {
+    let dir = project("init-templates", "");
+    fs::remove_file(dir.join("schema.yaml")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify")).args(["init", "--templates"]).current_dir(&dir).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(dir.join("crudify-templates/sequelize.ts.tpl").is_file());
+    assert!(dir.join("crudify-templates/partials/correlation_header.tpl").is_file());
+    // The fixture templates copied into the project are left as they were.
+    assert_eq!(
+        fs::read_to_string(dir.join("crudify-templates/model.ts.tpl")).unwrap(),
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/templates/model.ts.tpl")).unwrap()
+    );
+}
+
+#[test]
+fn doctor_plans_the_generation_without_writing() {
+    let dir = project("doctor", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let doctor = || Command::new(env!("CARGO_BIN_EXE_Crudify")).args(["doctor", "--config", "schema.yaml"]).current_dir(&dir).env("CRUDIFY_OUTPUT", "out").output().unwrap();
+    let output = doctor();
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{report}");
+    for line in ["ok    lock: free", "ok    output: out, generate creates it", "ok    config: no crudify.toml, the defaults apply", "ok    schema: 1 models from schema.yaml"] {
+        assert!(report.lines().any(|reported| reported == line), "{line} is missing from\n{report}");
+    }
+    assert!(report.lines().any(|line| line.starts_with("ok    templates: ") && line.ends_with(" files planned")), "{report}");
+    assert!(!dir.join("out").exists());
+
+    fs::create_dir_all(dir.join("out/.crudify")).unwrap();
+    fs::write(dir.join("out/.crudify/lock"), "4000000 0\n").unwrap();
+    fs::remove_file(dir.join("crudify-templates/repository.ts.tpl")).unwrap();
+    let output = doctor();
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "{report}");
+    assert!(report.contains("warn  lock: out/.crudify/lock is stale, left by pid 4000000, the next run takes it over"), "{report}");
+    assert!(report.contains("fail  templates: REPOSITORY_TEMPLATE not set in .env file and crudify-templates/repository.ts.tpl does not exist"), "{report}");
+    assert_eq!(fs::read_dir(dir.join("out")).unwrap().count(), 1);
+}
+
+#[test]
+fn render_prints_the_planned_file_of_an_artifact_without_writing() {
+    let dir = project("render", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+  Tag:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      label: { db_type: STRING, js_type: string }
+");
+    let render = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_Crudify")).arg("render").args(args).args(["--config", "schema.yaml"]).current_dir(&dir).env("CRUDIFY_OUTPUT", "out").output().unwrap()
+    };
+
+    let output = render(&["repository", "--model", "Tag"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.starts_with("import { Tag } from \"@infrastructure/models/tagModel\";"), "{stdout}");
+    assert!(!stdout.contains("Post"), "{stdout}");
+    assert!(!dir.join("out").exists());
+
+    let output = render(&["model"]);
+    let headers: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().filter(|line| line.starts_with("// ")).map(str::to_string).collect();
+    assert_eq!(headers, ["// infrastructure/models/postModel.ts", "// infrastructure/models/tagModel.ts"]);
+
+    let output = render(&["core/utils/Post/types.ts"]);
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("export type PostAttributes = {"));
+
+    for (args, message) in [(&["models"][..], "Error in render: models matches no planned file"), (&["model", "--model", "Author"][..], "Error in render: the schema has no model Author")] {
+        let output = render(args);
+        assert_eq!(output.status.code(), Some(1), "{args:?}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains(message), "{args:?}");
+    }
+    assert!(!dir.join("out").exists());
+}
+
+#[test]
+fn remove_deletes_the_unchanged_files_of_a_model_and_unregisters_it() {
+    let dir = project("remove", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+  Tag:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      label: { db_type: STRING, js_type: string }
+");
+    assert!(generate(&dir, &["--route-prefix", "/api"]).status.success());
+    let edited = dir.join("out/core/use_cases/Tag/AddTag.ts");
+    fs::write(&edited, fs::read_to_string(&edited).unwrap() + "// kept\n").unwrap();
+    let remove = |flags: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_Crudify"))
+            .args(["remove", "Tag", "--config", "schema.yaml", "--dialect", "postgres", "--route-prefix", "/api"])
+            .args(flags)
+            .current_dir(&dir)
+            .env("CRUDIFY_OUTPUT", "out")
+            .output()
+            .unwrap()
+    };
+
+    let before = written_files(&dir);
+    let output = remove(&["--dry-run"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Would delete infrastructure/models/tagModel.ts"), "{stdout}");
+    assert!(stdout.contains("Would unregister Tag from infrastructure/config/sequelize.ts"), "{stdout}");
+    assert_eq!(written_files(&dir), before);
+
+    let output = remove(&[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Warning: core/use_cases/Tag/AddTag.ts differs from what generate writes, it is left in place"), "{stdout}");
+    let files = written_files(&dir);
+    let left: Vec<&String> = files.iter().filter(|file| file.contains("Tag") || file.contains("tag")).collect();
+    assert_eq!(left, ["core/use_cases/Tag/AddTag.ts"], "{files:?}");
+    assert!(!dir.join("out/core/utils/Tag").exists());
+    assert!(files.iter().any(|file| file == "infrastructure/models/postModel.ts"), "{files:?}");
+
+    let sequelize = generated(&dir, "infrastructure/config/sequelize.ts");
+    assert!(sequelize.contains("models: [Post],") && !sequelize.contains("tagModel"), "{sequelize}");
+    let index = generated(&dir, "infrastructure/routes/index.ts");
+    assert!(index.contains("router.use(\"/api\", postRoutes);") && !index.contains("tagRoutes"), "{index}");
+    assert!(!generated(&dir, ".crudify/manifest.json").contains("\"Tag\""));
+}
+
+#[test]
+fn remove_refuses_a_model_that_is_referenced_or_the_last_one() {
+    let relations = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("self_test/relations.yaml")).unwrap();
+    for (schema, model, message) in [
+        (relations.as_str(), "Author", "Error in remove: Book still reference Author, remove the relations first"),
+        ("models:\n  Post:\n    fields:\n      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\"] }\n      title: { db_type: STRING, js_type: string }\n", "Post", "Error in remove: Post is the only model of the schema"),
+        (relations.as_str(), "Comment", "Error in remove: the schema has no model Comment"),
+    ] {
+        let dir = project("remove-refused", schema);
+        let output = Command::new(env!("CARGO_BIN_EXE_Crudify")).args(["remove", model, "--config", "schema.yaml"]).current_dir(&dir).env("CRUDIFY_OUTPUT", "out").output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "{model}");
+        assert!(String::from_utf8_lossy(&output.stdout).contains(message), "{}", String::from_utf8_lossy(&output.stdout));
+    }
+}
+
+#[test]
+fn concurrent_generations_register_both_models_once() {
+    let dir = project("concurrent", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    fs::write(dir.join("author.yaml"), "models:
+  Author:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      name: { db_type: STRING, js_type: string }
+").unwrap();
+    let runs: Vec<_> = ["schema.yaml", "author.yaml"]
+        .into_iter()
+        .map(|schema| {
+            Command::new(env!("CARGO_BIN_EXE_Crudify"))
+                .args(["generate", "--config", schema, "--dialect", "postgres"])
+                .current_dir(&dir)
+                .env("CRUDIFY_OUTPUT", "out")
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for run in runs {
+        let output = run.wait_with_output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    }
+
+    let sequelize = generated(&dir, "infrastructure/config/sequelize.ts");
+    for (class, module) in [("Post", "postModel"), ("Author", "authorModel")] {
+        let import = format!("import {{ {class} }} from \"@infrastructure/models/{module}\";");
+        assert_eq!(sequelize.matches(&import).count(), 1, "{sequelize}");
+        let models = sequelize.split("models: [").nth(1).unwrap().split(']').next().unwrap();
+        assert_eq!(models.split(',').filter(|entry| entry.trim() == class).count(), 1, "{sequelize}");
+    }
+    assert!(!dir.join("out/.crudify/lock").exists());
+}
+
+#[test]
+fn cached_bigint_models_go_through_a_bigint_aware_json() {
+    let dir = project("cached-bigint", "models:
+  Tweet:
+    fields:
+      id: { db_type: BIGINT, js_type: bigint, attr: [\"@PrimaryKey\"] }
+      views: { db_type: BIGINT, bigint_as: string }
+      body: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &["--cache", "redis", "--domain-entities", "--validation", "zod", "--lenient"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let cached = generated(&dir, "infrastructure/repositories/CachedTweetRepository.ts");
+    assert!(!cached.contains("JSON.stringify(result)"), "{cached}");
+    assert_eq!(cached.matches("await redis.set(key, toCache(result), \"EX\", this.ttl);").count(), 2, "{cached}");
+    assert!(cached.contains("if (typeof value === \"bigint\") {\n        return value.toString();\n    }"), "{cached}");
+    assert!(cached.contains("import { reviveBigInt } from \"@core/utils/bigint\";"), "{cached}");
+    assert!(cached.contains("return JSON.parse(cached, reviveBigInt([\"id\"])) as TweetEntity;"), "{cached}");
+    assert!(generated(&dir, "core/utils/bigint.ts").contains("export const reviveBigInt"));
+
+    let model = generated(&dir, "infrastructure/models/tweetModel.ts");
+    assert!(model.contains("\tviews!: string;"), "{model}");
+    let validation = generated(&dir, "core/utils/Tweet/validation.ts");
+    assert!(validation.contains("\tviews: z.string().regex(/^-?\\d+$/),"), "{validation}");
+
+    fs::write(dir.join("schema.yaml"), "models:
+  Tweet:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\"] }
+      views: { db_type: INTEGER, bigint_as: string }
+").unwrap();
+    let output = generate(&dir, &[]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Tweet.views: bigint_as applies to BIGINT fields, not INTEGER"));
+}
+
+#[test]
+fn ambiguous_model_files_are_overwritten_with_a_backup() {
+    let dir = project("merge-backup", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    assert!(generate(&dir, &[]).status.success());
+    let edited = "import { Table, Column, Model, DataType } from \"sequelize-typescript\";
+
+@Table({ tableName: \"Posts\" })
+export class Post extends Model {
+\t@Column(DataType.INTEGER)
+\tid!: number;
+
+\tdraft!: boolean;
+}
+";
+    let path = dir.join("out/infrastructure/models/postModel.ts");
+    fs::write(&path, edited).unwrap();
+
+    let cache = dir.join("cache");
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify"))
+        .args(["generate", "--config", "schema.yaml", "--dialect", "postgres"])
+        .current_dir(&dir)
+        .env("CRUDIFY_OUTPUT", "out")
+        .env("XDG_CACHE_HOME", &cache)
+        .env("LOCALAPPDATA", &cache)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("the generated fields of") && stdout.contains("could not be told apart"), "{stdout}");
+
+    let model = fs::read_to_string(&path).unwrap();
+    assert!(model.contains("\t// crudify:fields\n") && !model.contains("draft"), "{model}");
+
+    let mut pending = vec![cache];
+    let mut backups = Vec::new();
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            match path.is_dir() {
+                true => pending.push(path),
+                false => backups.push(path),
+            }
+        }
+    }
+    assert_eq!(backups.len(), 1, "{backups:?}");
+    assert!(backups[0].ends_with("out/infrastructure/models/postModel.ts.bak"), "{:?}", backups[0]);
+    assert_eq!(fs::read_to_string(&backups[0]).unwrap(), edited);
+}
+
+#[test]
+fn models_without_a_primary_key_are_keyed_by_an_id_unless_no_id() {
+    let dir = project("implicit-id", "models:
+  Post:
+    fields:
+      title: { db_type: STRING, js_type: string }
+  Session:
+    fields:
+      id: { db_type: UUID, js_type: string, attr: [\"@Default(DataType.UUIDV4)\"] }
+      token: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Notice: Post declares no @PrimaryKey and is keyed by its id, pass --no-id to declare the key yourself"), "{stdout}");
+
+    let post = generated(&dir, "infrastructure/models/postModel.ts");
+    assert!(post.contains("\t@PrimaryKey\n\t@AutoIncrement\n\t@Column(DataType.INTEGER)\n\tid!: number;\n\n\t@Column(DataType.STRING)\n\ttitle!: string;"), "{post}");
+    let session = generated(&dir, "infrastructure/models/sessionModel.ts");
+    assert!(session.contains("\t@PrimaryKey\n\t@Default(DataType.UUIDV4)\n\t@Column(DataType.UUID)\n\tid!: string;"), "{session}");
+
+    let output = generate(&dir, &["--no-id"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.lines().any(|line| line == "Error in schema: Post: Model Post has no @PrimaryKey field"), "{stdout}");
+}
+
+#[test]
+fn prisma_schema_declares_the_enums_of_its_fields() {
+    let dir = project("prisma-enum", "enums:
+  PostStatus: [draft, published]
+models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      status: { db_type: ENUM, js_type: string, enum: PostStatus, attr: [\"@Default(\\\"draft\\\")\"] }
+");
+    for _ in 0..2 {
+        let output = generate(&dir, &["--orm", "prisma"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    }
+
+    let schema = generated(&dir, "infrastructure/prisma/schema.prisma");
+    assert!(schema.ends_with("\n\nenum PostStatus {\n  draft\n  published\n}\n\nmodel Post {\n  id Int @id @default(autoincrement())\n  status PostStatus @default(draft)\n}\n"), "{schema}");
+}
+
+#[test]
+fn seed_from_csv_converts_the_cells_and_reports_invalid_rows_by_line() {
+    let dir = project("seed-csv", "models:
+  Post:
+    timestamps: false
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      views: { db_type: INTEGER, js_type: number }
+      published: { db_type: BOOLEAN, js_type: boolean }
+      publishedOn: { db_type: DATEONLY, js_type: string }
+");
+    fs::write(dir.join("posts.csv"), "id,views,published,publishedOn\n1,10,true,01/03/2024\n2,many,0,02/03/2024\n3,30,FALSE,2024-03-03\n").unwrap();
+    let seed = |flags: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_Crudify"))
+            .args(["seed-from-csv", "Post", "posts.csv", "--config", "schema.yaml", "--date-format", "%d/%m/%Y"])
+            .args(flags)
+            .current_dir(&dir)
+            .env("CRUDIFY_OUTPUT", "out")
+            .output()
+            .unwrap()
+    };
+    let seeders = || fs::read_dir(dir.join("out/infrastructure/seeders")).map_or(Vec::new(), |entries| entries.map(|entry| entry.unwrap().path()).collect());
+
+    let output = seed(&[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "{stdout}");
+    assert!(stdout.contains("Line 3: views: `many` is not a valid INTEGER\n"), "{stdout}");
+    assert!(stdout.contains("Line 4: publishedOn: `2024-03-03` is not a valid DATEONLY\n"), "{stdout}");
+    assert!(stdout.contains("Error in seed: 2 invalid rows in posts.csv, rerun with --skip-invalid to leave them out"), "{stdout}");
+    assert!(seeders().is_empty(), "{:?}", seeders());
+
+    let output = seed(&["--skip-invalid"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("(1 rows, 2 skipped)"), "{stdout}");
+    let seeders = seeders();
+    assert_eq!(seeders.len(), 1, "{seeders:?}");
+    let seeder = fs::read_to_string(&seeders[0]).unwrap();
+    assert!(seeder.contains("[\n\t\t\t{\"id\":1,\"views\":10,\"published\":true,\"publishedOn\":\"2024-03-01\" },\n\t\t]"), "{seeder}");
+}
+
+#[test]
+fn tests_run_the_use_cases_on_factory_rows_with_their_events_and_tenant() {
+    let dir = project("use-case-tests", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &["--tests"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let tests = generated(&dir, "__tests__/use_cases/PostUseCases.test.ts");
+    for line in [
+        "import { buildPost } from \"../factories/postFactory\";",
+        "const find = (id: number) => rows.find((row) => row.id === id);",
+        "const row = { id: rows.length + 1, ...details };",
+        "await new AddPost(repository as any).execute(buildPost());",
+        "await new UpdatePost(repository as any).execute(row.id, buildPost());",
+        "await new DeletePost(repository as any).execute(row.id);",
+    ] {
+        assert!(tests.contains(line), "{line} is missing from\n{tests}");
+    }
+
+
+    let output = generate(&dir, &["--tests", "--events", "--lenient"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let tests = generated(&dir, "__tests__/use_cases/PostUseCases.test.ts");
+    for line in [
+        "import { toPostPayload } from \"@core/events/PostEvents\";",
+        "const publish = jest.spyOn(eventBus, \"publish\");",
+        "expect(publish).toHaveBeenCalledWith(\"PostCreated\", toPostPayload(repository.rows[0]));",
+        "expect(publish).toHaveBeenCalledWith(\"PostUpdated\", toPostPayload(row));",
+        "expect(publish).toHaveBeenCalledWith(\"PostDeleted\", { id: row.id });",
+    ] {
+        assert!(tests.contains(line), "{line} is missing from\n{tests}");
+    }
+
+
+    let output = generate(&dir, &["--tests", "--tenant-field", "tenantId"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let tests = generated(&dir, "__tests__/use_cases/PostUseCases.test.ts");
+    for line in [
+        "const find = (tenantId: number, id: number) => rows.find((row) => row.tenantId === tenantId && row.id === id);",
+        "const row = { id: rows.length + 1, ...details, tenantId };",
+        "findAll: jest.fn(async (tenantId: number) => rows.filter((row) => row.tenantId === tenantId)),",
+        "await new AddPost(repository as any).execute(tenant, buildPost());",
+        "it(\"keeps the post rows of one tenant from another\", async () => {",
+        "await expect(new GetPosts(repository as any).execute(otherTenant)).resolves.toEqual([]);",
+        "await new UpdatePost(repository as any).execute(otherTenant, row.id, buildPost()).catch(() => undefined);",
+        "await new DeletePost(repository as any).execute(otherTenant, row.id);",
+        "expect(repository.rows).toEqual([row]);",
+    ] {
+        assert!(tests.contains(line), "{line} is missing from\n{tests}");
+    }
+
+    let output = generate(&dir, &["--tests", "--no-factories"]);
+    assert!(!output.status.success());
+    let output = generate(&dir, &["--tests", "--style", "minimal"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("--tests works on the repository and use case layers"), "{}", String::from_utf8_lossy(&output.stdout));
+
+    fs::remove_dir_all(dir.join("out")).unwrap();
+    let output = generate(&dir, &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(!dir.join("out/__tests__/use_cases").exists());
+}
+
+#[test]
+fn kafka_consumer_projects_the_added_updated_and_deleted_events_of_the_aggregate() {
+    let dir = project("kafka-consumer", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &["--event-sourcing", "--queue-type", "kafka"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let aggregate = generated(&dir, "core/aggregates/PostAggregate.ts");
+    let consumer = generated(&dir, "infrastructure/event_store/postKafkaConsumer.ts");
+    let asyncapi = generated(&dir, "docs/asyncapi/post.asyncapi.yaml");
+    for event in ["PostAdded", "PostUpdated", "PostDeleted"] {
+        assert!(aggregate.contains(&format!("{{ type: \"{event}\"; payload: ")), "{event} is missing from\n{aggregate}");
+        assert!(consumer.contains(&format!("case \"{event}\":\n                return this.on{event}(id, event);")), "{event} is missing from\n{consumer}");
+        assert!(consumer.contains(&format!("async on{event}(id: number, event: PostEventOf<\"{event}\">) {{")), "{event} is missing from\n{consumer}");
+        assert!(asyncapi.contains(&format!("$ref: '#/components/messages/{event}'")), "{event} is missing from\n{asyncapi}");
+    }
+    assert!(!consumer.contains("PostCreated"), "{consumer}");
+}
+
+#[test]
+fn tsyringe_registers_and_injects_the_repository_under_its_class_name() {
+    let dir = project("tsyringe", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    let output = generate(&dir, &["--di", "tsyringe"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let registration = generated(&dir, "infrastructure/config/postDI.ts");
+    assert!(registration.contains("container.register(\"PostRepository\", { useClass: PostRepository });"), "{registration}");
+    let repository = generated(&dir, "infrastructure/repositories/postRepository.ts");
+    assert!(repository.contains("@injectable()\nexport class PostRepository"), "{repository}");
+    for use_case in ["AddPost", "GetPosts", "UpdatePost", "DeletePost"] {
+        let content = generated(&dir, &format!("core/use_cases/Post/{use_case}.ts"));
+        assert!(content.contains("constructor(@inject(\"PostRepository\") private postRepository: IPostRepository) {}"), "{content}");
+    }
+}
+
+#[test]
+fn inversify_binds_and_injects_the_repository_by_the_symbol_of_its_interface() {
+    let dir = project("inversify", "models:
+  Post:
+    fields:
+      id: { db_type: INTEGER, js_type: number, attr: [\"@PrimaryKey\", \"@AutoIncrement\"] }
+      title: { db_type: STRING, js_type: string }
+");
+    fs::write(dir.join("crudify-templates/add_use_case.ts.tpl"), "{DI_IMPORT}
+import { Add{NAME_UPPER}Request } from \"@core/utils/{NAME_UPPER}/Request\";
+
+{DI_INJECTABLE}
+export class Add{NAME_UPPER} {
+    constructor({DI_INJECT_REPOSITORY}private {NAME_LOWER}Repository: I{NAME_UPPER}Repository) {}
+
+    async execute(request: Add{NAME_UPPER}Request) {
+        return this.{NAME_LOWER}Repository.create({
+            {DYNAMIC_ADD_PROPERTIES}
+        });
+    }
+}
+").unwrap();
+    let output = generate(&dir, &["--di", "inversify", "--lenient"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let interface = generated(&dir, "core/interfaces/IPostRepository.ts");
+    assert!(interface.ends_with("}\n\nexport const IPostRepository = Symbol.for(\"IPostRepository\");\n"), "{interface}");
+    let bindings = generated(&dir, "infrastructure/config/postBindings.ts");
+    assert!(bindings.contains("import { IPostRepository } from \"@core/interfaces/IPostRepository\";"), "{bindings}");
+    assert!(bindings.contains("\tbind(IPostRepository).to(PostRepository);"), "{bindings}");
+    let use_case = generated(&dir, "core/use_cases/Post/AddPost.ts");
+    assert!(use_case.contains("import { IPostRepository } from \"@core/interfaces/IPostRepository\";"), "{use_case}");
+    assert!(use_case.contains("constructor(@inject(IPostRepository) private postRepository: IPostRepository) {}"), "{use_case}");
+
+    // A second run finds the symbol in place and leaves the interface as it was.
+    let output = generate(&dir, &["--di", "inversify", "--lenient"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert_eq!(generated(&dir, "core/interfaces/IPostRepository.ts"), interface);
+}
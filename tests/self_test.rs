@@ -0,0 +1,41 @@
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+
+// Runs every bundled self_test schema through the generator with the fixture templates.
+#[test]
+fn bundled_schemas_generate_with_the_fixture_templates() {
+    let dir = std::env::temp_dir().join(format!("crudify-it-self-test-{}", process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO_BIN_EXE_Crudify"))
+        .args(["self-test", "--no-tsc", "--templates"])
+        .arg(crate_dir.join("tests/templates"))
+        .arg("--keep-output")
+        .arg(dir.join("schemas"))
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+
+    let mut rows = stdout.lines();
+    let header = rows.next().unwrap().split_whitespace().collect::<Vec<_>>();
+    assert_eq!(header[..2], ["schema", "generate"], "{stdout}");
+    let schemas = fs::read_dir(crate_dir.join("self_test"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path().file_stem().unwrap().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let rows = rows.map(|row| row.split_whitespace().collect::<Vec<_>>()).filter(|row| !row.is_empty()).collect::<Vec<_>>();
+    assert_eq!(rows.len(), schemas.len(), "{stdout}");
+    for schema in &schemas {
+        let row = rows.iter().find(|row| row[0] == schema).unwrap_or_else(|| panic!("{schema} is missing from\n{stdout}"));
+        assert_eq!(row.len(), header.len(), "{stdout}");
+        assert!(row[1..].iter().all(|cell| *cell == "ok" || *cell == "-"), "{schema} failed\n{stdout}");
+        assert_eq!(row[1], "ok", "{stdout}");
+        assert!(dir.join("schemas").join(schema).join("schema.yaml").exists());
+    }
+}
@@ -0,0 +1,12 @@
+import { I{NAME_UPPER}Repository } from "@core/interfaces/I{NAME_UPPER}Repository";
+import { Add{NAME_UPPER}Request } from "@core/utils/{NAME_UPPER}/Request";
+
+export class Add{NAME_UPPER} {
+    constructor(private {NAME_LOWER}Repository: I{NAME_UPPER}Repository) {}
+
+    async execute(request: Add{NAME_UPPER}Request) {
+        return this.{NAME_LOWER}Repository.create({
+            {DYNAMIC_ADD_PROPERTIES}
+        });
+    }
+}
@@ -0,0 +1,30 @@
+import { Request, Response } from "express";
+import { {NAME_UPPER}Repository } from "@infrastructure/repositories/{NAME_LOWER}Repository";
+import { Add{NAME_UPPER} } from "@core/use_cases/{NAME_UPPER}/Add{NAME_UPPER}";
+import { Get{NAME_UPPER_PLURAL} } from "@core/use_cases/{NAME_UPPER}/Get{NAME_UPPER_PLURAL}";
+import { Update{NAME_UPPER} } from "@core/use_cases/{NAME_UPPER}/Update{NAME_UPPER}";
+import { Delete{NAME_UPPER} } from "@core/use_cases/{NAME_UPPER}/Delete{NAME_UPPER}";
+
+const {NAME_LOWER}Repository = new {NAME_UPPER}Repository();
+
+export const add{NAME_UPPER} = async (req: Request, res: Response) => {
+    const result = await new Add{NAME_UPPER}({NAME_LOWER}Repository).execute({
+                {DYNAMIC_PROPERTIES_DETAILS}
+    });
+    res.status(201).json(result);
+};
+
+export const get{NAME_UPPER_PLURAL} = async (req: Request, res: Response) => {
+    const result = await new Get{NAME_UPPER_PLURAL}({NAME_LOWER}Repository).execute();
+    res.json(result);
+};
+
+export const update{NAME_UPPER} = async (req: Request, res: Response) => {
+    const result = await new Update{NAME_UPPER}({NAME_LOWER}Repository).execute(req.params.{PRIMARY_KEY}, req.body);
+    res.json(result);
+};
+
+export const delete{NAME_UPPER} = async (req: Request, res: Response) => {
+    await new Delete{NAME_UPPER}({NAME_LOWER}Repository).execute(req.params.{PRIMARY_KEY});
+    res.status(204).send();
+};
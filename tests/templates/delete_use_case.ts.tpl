@@ -0,0 +1,9 @@
+import { I{NAME_UPPER}Repository } from "@core/interfaces/I{NAME_UPPER}Repository";
+
+export class Delete{NAME_UPPER} {
+    constructor(private {NAME_LOWER}Repository: I{NAME_UPPER}Repository) {}
+
+    async execute(id: number) {
+        return this.{NAME_LOWER}Repository.delete(id);
+    }
+}
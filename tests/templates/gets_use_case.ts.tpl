@@ -0,0 +1,9 @@
+import { I{NAME_UPPER}Repository } from "@core/interfaces/I{NAME_UPPER}Repository";
+
+export class Get{NAME_UPPER_PLURAL} {
+    constructor(private {NAME_LOWER}Repository: I{NAME_UPPER}Repository) {}
+
+    async execute() {
+        return this.{NAME_LOWER}Repository.findAll();
+    }
+}
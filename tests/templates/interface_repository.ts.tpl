@@ -0,0 +1,10 @@
+import { {NAME_UPPER} } from "@infrastructure/models/{NAME_LOWER}Model";
+import { {NAME_UPPER}Details } from "@core/utils/{NAME_UPPER}/types";
+
+export interface I{NAME_UPPER}Repository {
+    create(details: {NAME_UPPER}Details): Promise<{NAME_UPPER}>;
+    findAll(): Promise<{NAME_UPPER}[]>;
+    findById(id: number): Promise<{NAME_UPPER} | null>;
+    update({NAME_LOWER}: {NAME_UPPER}): Promise<{NAME_UPPER}>;
+    delete(id: number): Promise<void>;
+}
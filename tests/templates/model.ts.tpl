@@ -0,0 +1,6 @@
+import { Table, Column, Model, DataType, PrimaryKey, AutoIncrement } from "sequelize-typescript";
+
+@Table({TABLE_OPTIONS})
+export class {NAME_UPPER} extends Model {
+{DYNAMIC_PROPERTIES}
+}
@@ -0,0 +1,25 @@
+import { {NAME_UPPER} } from "@infrastructure/models/{NAME_LOWER}Model";
+import { I{NAME_UPPER}Repository } from "@core/interfaces/I{NAME_UPPER}Repository";
+import { {NAME_UPPER}Details } from "@core/utils/{NAME_UPPER}/types";
+
+export class {NAME_UPPER}Repository implements I{NAME_UPPER}Repository {
+    async create(details: {NAME_UPPER}Details): Promise<{NAME_UPPER}> {
+        return {NAME_UPPER}.create(details);
+    }
+
+    async findAll(): Promise<{NAME_UPPER}[]> {
+        return {NAME_UPPER}.findAll();
+    }
+
+    async findById(id: number): Promise<{NAME_UPPER} | null> {
+        return {NAME_UPPER}.findByPk(id);
+    }
+
+    async update({NAME_LOWER}: {NAME_UPPER}): Promise<{NAME_UPPER}> {
+        return {NAME_LOWER}.save();
+    }
+
+    async delete(id: number): Promise<void> {
+        await {NAME_UPPER}.destroy({ where: { id } });
+    }
+}
@@ -0,0 +1,4 @@
+import { {NAME_UPPER}Details } from "./types";
+
+export type Add{NAME_UPPER}Request = {NAME_UPPER}Details;
+export type Update{NAME_UPPER}Request = Partial<{NAME_UPPER}Details>;
@@ -0,0 +1,12 @@
+import { Router } from "express";
+import { add{NAME_UPPER}, get{NAME_UPPER_PLURAL}, update{NAME_UPPER}, delete{NAME_UPPER} } from "@presentation/controllers/{NAME_LOWER}Controllers";
+{DYNAMIC_UPLOAD_IMPORT}
+
+const router = Router();
+
+router.post("/{NAME_LOWER_PLURAL}", {DYNAMIC_UPLOAD_MIDDLEWARE}add{NAME_UPPER});
+router.get("/{NAME_LOWER_PLURAL}", get{NAME_UPPER_PLURAL});
+router.put("/{NAME_LOWER_PLURAL}{KEY_PARAMS}", {DYNAMIC_UPLOAD_MIDDLEWARE}update{NAME_UPPER});
+router.delete("/{NAME_LOWER_PLURAL}{KEY_PARAMS}", delete{NAME_UPPER});
+
+export default router;
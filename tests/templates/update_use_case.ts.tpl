@@ -0,0 +1,15 @@
+import { I{NAME_UPPER}Repository } from "@core/interfaces/I{NAME_UPPER}Repository";
+import { Update{NAME_UPPER}Request } from "@core/utils/{NAME_UPPER}/Request";
+
+export class Update{NAME_UPPER} {
+    constructor(private {NAME_LOWER}Repository: I{NAME_UPPER}Repository) {}
+
+    async execute(id: number, request: Update{NAME_UPPER}Request) {
+        const {NAME_LOWER} = await this.{NAME_LOWER}Repository.findById(id);
+        if (!{NAME_LOWER}) {
+            throw new Error("{NAME_UPPER} not found");
+        }
+        {DYNAMIC_UPDATE_PROPERTIES}
+        return this.{NAME_LOWER}Repository.update({NAME_LOWER});
+    }
+}